@@ -1,190 +1,543 @@
-use std::collections::HashMap;
-use std::sync::{Arc, mpsc, Mutex};
+use std::path::PathBuf;
+use std::process;
+use std::sync::mpsc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use piston_window::{Button, clear, G2d, Glyphs, Key, OpenGL, PistonWindow, PressEvent, ReleaseEvent, Size, UpdateEvent, WindowSettings};
-use rand::{Rng, thread_rng};
+use clap::Parser;
+use log::{error, info, warn};
+use piston_window::{Button, clear, G2d, Glyphs, Key, MouseScrollEvent, OpenGL, PistonWindow, PressEvent, ReleaseEvent, Size, UpdateEvent, WindowSettings};
 
 use robotics_lib::interface::Tools;
-use robotics_lib::runner::{Robot, Runner};
-use robotics_lib::world::environmental_conditions::EnvironmentalConditions;
-use robotics_lib::world::environmental_conditions::WeatherType::{Rainy, Sunny};
-use robotics_lib::world::tile::{Content, Tile};
-use robotics_lib::world::tile::Content::{
-    Bank, Bin, Building, Bush, Coin, Crate, Fire, Fish, Garbage, JollyBlock, Market, Rock,
-    Scarecrow, Tree, Water,
-};
-use robotics_lib::world::tile::TileType::{
-    DeepWater, Grass, Hill, Lava, Mountain, Sand, ShallowWater, Snow, Street, Teleport,
-};
-use robotics_lib::world::world_generator::Generator;
+use robotics_lib::runner::Runner;
+use Visualizer::compare::{diff_overlay, ExplorationDiff, ReplayComparison, RunView};
+use Visualizer::config::{Theme, VisualizerConfig};
+use Visualizer::control::{control_channel, ControlCommand, ControlStatus};
+use Visualizer::events::{EventFilter, EventKind};
+use Visualizer::fog::{fog_overlay, DiscoveryTracker, FogConfig, UnknownStyle, ViewArea, UNKNOWN_HATCH_LINE};
 use Visualizer::grid::*;
+use Visualizer::groundtruth::{CapturingGenerator, GroundTruth, GROUND_TRUTH_OPACITY};
+use Visualizer::headless::{HeadlessOptions, run_headless, RunSummary, METRICS_FILE};
+use Visualizer::history::RunHistory;
+use Visualizer::lifecycle::{export_run, Lifecycle, LifecycleHandle, RunPhase};
 //use worldgen_unwrap::*;
 //use worldgen_unwrap::*;
-use Visualizer::robot::{ExampleRobot, Visualizable};
-use Visualizer::util::{convert_content_to_color_matrix, convert_robot_content_view_to_color_matrix, convert_robot_view_to_color_matrix, convert_to_color_matrix, Infos};
+use Visualizer::multi::{indexed_path, RobotSlots, RobotSpec, ROBOT_COLORS};
+use Visualizer::registry::{global_registry, RobotSettings, VisualizedRobot};
+use Visualizer::state::{ColorMatrix, VisualizerState};
+use Visualizer::teleport::{JumpAnimation, TeleportLinks};
+use Visualizer::terminal::TerminalRenderer;
+use Visualizer::util::{convert_robot_content_view_to_color_matrix, convert_robot_view_to_color_matrix, convert_robot_view_to_quantity_matrix, EnvironmentInfo};
+use Visualizer::worldfile::{export_world, FileGenerator};
+use Visualizer::worldgen::{SeededGenerator, WorldPreset, WorldSource};
+use Visualizer::worldimage::ImageGenerator;
 
-const DEFAULT_FONT_PATH: &str = "../font/font.otf";
+//how often the terminal is redrawn
+const TERMINAL_FRAME_INTERVAL: Duration = Duration::from_millis(100);
 
-pub const MAP_DIM: usize = MAP_SIZE;
+/// Visualizer of the robotics_lib robots.
+///
+/// Every option can also be set in a JSON config file (see `VisualizerConfig`);
+/// the options given on the command line override the ones of the file.
+#[derive(Parser, Debug)]
+struct Args {
+    /// JSON config file, missing keys keep their default value
+    #[arg(long)]
+    config: Option<String>,
+    /// Write the resulting configuration to the given file and exit
+    #[arg(long)]
+    save_config: Option<String>,
+    /// Run without opening a window: write history, replay and summary, then exit
+    #[arg(long)]
+    headless: bool,
+    /// Draw the robot in the terminal instead of opening a window
+    #[arg(long, conflicts_with = "headless")]
+    terminal: bool,
+    /// World saved with --export-world, used instead of the generator
+    #[arg(long)]
+    world_file: Option<PathBuf>,
+    /// Image painted with the tile colors, used instead of the generator
+    #[arg(long, conflicts_with = "world_file")]
+    world_image: Option<PathBuf>,
+    /// Image painted with the content colors over --world-image, transparent where empty
+    #[arg(long, requires = "world_image")]
+    content_image: Option<PathBuf>,
+    /// Write the generated world to the given file and exit
+    #[arg(long)]
+    export_world: Option<String>,
+    /// Name of the robot to run, see --list-robots
+    #[arg(long)]
+    robot: Option<String>,
+    /// Behavior of the example robot: alternate, serpentine, random_walk, collect_and_drop or script
+    #[arg(long)]
+    behavior: Option<String>,
+    /// Run several robots on copies of the same world, e.g. example:alternate,example:serpentine
+    #[arg(long, value_delimiter = ',', conflicts_with = "robot")]
+    robots: Option<Vec<String>>,
+    /// Moves followed by the script behavior, one direction per line (e.g. "right 5")
+    #[arg(long)]
+    script: Option<PathBuf>,
+    /// Show the first two robots of --robots side by side instead of a merged map
+    #[arg(long, requires = "robots")]
+    split: bool,
+    /// Play two replays saved by --headless side by side, synchronized by tick
+    #[arg(long, num_args = 2, value_names = ["LEFT", "RIGHT"], conflicts_with_all = ["headless", "terminal"])]
+    compare: Option<Vec<String>>,
+    /// Print the robots that can be selected with --robot and exit
+    #[arg(long)]
+    list_robots: bool,
+    /// Kind of world generated: flat, noise, islands or maze
+    #[arg(long)]
+    world: Option<WorldPreset>,
+    /// Size of the generated world
+    #[arg(long)]
+    world_size: Option<usize>,
+    /// Seed of the world generator, random if not given
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Amount of ticks after which the simulation stops
+    #[arg(long)]
+    ticks: Option<usize>,
+    /// Pause between two ticks, in milliseconds
+    #[arg(long)]
+    tick_delay: Option<u64>,
+    /// How often the state is sent to the window, in milliseconds
+    #[arg(long)]
+    send_interval: Option<u64>,
+    /// Tiles shown on each side of the robot in the robot view
+    #[arg(long)]
+    view_radius: Option<usize>,
+    /// Save a frame of the discovered map on every move
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    record_frames: Option<bool>,
+    /// Convert the saved frames to a gif at the end of the run (needs ffmpeg)
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    gif: Option<bool>,
+    /// Write every event received by the robot as a JSON line in the given file
+    #[arg(long)]
+    trace: Option<PathBuf>,
+    /// Csv file the energy and score history is exported to
+    #[arg(long)]
+    history: Option<PathBuf>,
+    /// Directory where the headless outputs are written
+    #[arg(long)]
+    output: Option<PathBuf>,
+    /// Play a sound for the backpack and time events
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    sound: Option<bool>,
+    /// Colors of the window: dark or light
+    #[arg(long)]
+    theme: Option<Theme>,
+    /// How the undiscovered tiles are drawn: hidden, gray or hatched
+    #[arg(long)]
+    fog: Option<UnknownStyle>,
+    /// Darken the discovered tiles that no robot is seeing
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    dim_out_of_view: Option<bool>,
+    /// Highlight the tiles discovered in the last given ticks, 0 disables the highlight
+    #[arg(long)]
+    highlight_ticks: Option<usize>,
+    /// Send the real world to the window, G shows it under the discovered map
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    ground_truth: Option<bool>,
+    /// Width of the window
+    #[arg(long)]
+    window_width: Option<u32>,
+    /// Height of the window
+    #[arg(long)]
+    window_height: Option<u32>,
+    /// Font used by the window
+    #[arg(long)]
+    font: Option<PathBuf>,
+}
+
+impl Args {
+    /// Overrides the values of the config with the ones given on the command line.
+    fn apply(&self, config: &mut VisualizerConfig) {
+        if let Some(robot) = &self.robot {
+            config.robot = robot.clone();
+        }
+        if let Some(behavior) = &self.behavior {
+            config.behavior = behavior.clone();
+        }
+        if let Some(robots) = &self.robots {
+            config.robots = robots.clone();
+        }
+        if self.script.is_some() {
+            config.script_path = self.script.clone();
+        }
+        if self.world_file.is_some() {
+            config.world_file = self.world_file.clone();
+        }
+        if self.world_image.is_some() {
+            config.world_image = self.world_image.clone();
+        }
+        if self.content_image.is_some() {
+            config.content_image = self.content_image.clone();
+        }
+        if let Some(world) = self.world {
+            config.world = world;
+        }
+        if let Some(world_size) = self.world_size {
+            config.world_size = world_size;
+        }
+        if self.seed.is_some() {
+            config.seed = self.seed;
+        }
+        if let Some(ticks) = self.ticks {
+            config.ticks = ticks;
+        }
+        if let Some(tick_delay) = self.tick_delay {
+            config.tick_delay_ms = tick_delay;
+        }
+        if let Some(send_interval) = self.send_interval {
+            config.send_interval_ms = send_interval;
+        }
+        if let Some(view_radius) = self.view_radius {
+            config.view_radius = view_radius;
+        }
+        if let Some(record_frames) = self.record_frames {
+            config.record_frames = record_frames;
+        }
+        if let Some(gif) = self.gif {
+            config.gif = gif;
+        }
+        if self.trace.is_some() {
+            config.trace_path = self.trace.clone();
+        }
+        if let Some(history) = &self.history {
+            config.history_path = history.clone();
+        }
+        if let Some(output) = &self.output {
+            config.output_dir = output.clone();
+        }
+        if let Some(sound) = self.sound {
+            config.sound = sound;
+        }
+        if let Some(theme) = self.theme {
+            config.theme = theme;
+        }
+        if let Some(fog) = self.fog {
+            config.fog.unknown = fog;
+        }
+        if let Some(dim_out_of_view) = self.dim_out_of_view {
+            config.fog.dim_out_of_view = dim_out_of_view;
+        }
+        if let Some(highlight_ticks) = self.highlight_ticks {
+            config.fog.highlight_recent = highlight_ticks > 0;
+            config.fog.highlight_ticks = highlight_ticks;
+        }
+        if let Some(ground_truth) = self.ground_truth {
+            config.ground_truth = ground_truth;
+        }
+        if let Some(window_width) = self.window_width {
+            config.window_width = window_width;
+        }
+        if let Some(window_height) = self.window_height {
+            config.window_height = window_height;
+        }
+        if let Some(font) = &self.font {
+            config.font_path = font.clone();
+        }
+    }
+}
 
 fn main() {
-    // ROBOT ANDREA
-    /*
-    use andrea_ai::AndreaRobot;
-    use clap::Parser;
-    let args = andrea_ai::Args::parse();
-    let r = AndreaRobot::new(Robot::new(), Arc::new(Mutex::new(0)), args);
-    */
-    // Channel to send to the visualizer the robot_map while the robot moves in the process_tick()
-    let (matrix_sender, matrix_receiver) = mpsc::channel();
-    let r = ExampleRobot::new(Robot::new(), Arc::new(Mutex::new(0)));
-    let init_frames = r.get_init_frames().clone();
-    let current_robot_map = r.get_current_robot_map().clone();
-    let current_robot_view = r.get_current_robot_view().clone();
-    let current_robot_backpack = r.get_current_robot_backpack().clone();
-    let _score = r.get_score().clone();
-    let current_robot_coordinates = r.get_current_robot_coordinates().clone();
-    let current_robot_energy = r.get_current_energy().clone();
+    let args = Args::parse();
 
-    //IMPLEMENTATION OF THE WORLDGENERATOR AND PROCESS TICK
-    thread::spawn(move || {
-        struct WorldGenerator {
-            size: usize,
-        }
-        impl WorldGenerator {
-            fn init(size: usize) -> Self {
-                WorldGenerator { size }
-            }
-        }
-        impl Generator for WorldGenerator {
-            fn gen(
-                &mut self,
-            ) -> (
-                Vec<Vec<Tile>>,
-                (usize, usize),
-                EnvironmentalConditions,
-                f32,
-                Option<HashMap<Content, f32>>,
-            ) {
-                let mut rng = thread_rng();
-                let mut map: Vec<Vec<Tile>> = Vec::new();
-                // Initialize the map with default tiles
-                for i in 0..self.size {
-                    let mut row: Vec<Tile> = Vec::new();
-                    for _ in 0..self.size {
-                        let i_tiletype;// = 3;//rng.gen_range(0..=9);//rng.gen_range(0..TileType::iter().len());
-                        let i_content;//=rng.gen_range(0..=2); //rng.gen_range(0..Content::iter().len());
-
-
-                        if i == 0 {
-                            i_content = 16;  //first row
-                        } else if i == 1 {
-                            i_content = 1;  //second row
-                        } else if i == 2 {
-                            i_content = 16; //third row
-                        } else {
-                            i_content = 16  //other rows
-                        }
+    //log level and targets can be selected with RUST_LOG, e.g. RUST_LOG=Visualizer::frame=debug,sender=warn
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
-                        /*
-                        if i == 0 {
-                            i_tiletype = 2;
-                        } else if i == 2 {
-                            i_tiletype = 1;
-                        } else {
-                            i_tiletype = 3
-                        }   //first row filled with Sand, third row filled with Street, other rows are Grass
-                         */
-
-                        i_tiletype = 3; //only grass for better debug
-
-
-                        let i_size = rng.gen_range(0..=20);
-                        let tile_type = match i_tiletype {
-                            0 => DeepWater,
-                            1 => ShallowWater,
-                            2 => Sand,
-                            3 => Grass,
-                            4 => Street,
-                            5 => Hill,
-                            6 => Mountain,
-                            7 => Snow,
-                            8 => Lava,
-                            9 => Teleport(false),
-                            _ => Grass,
-                        };
-                        let content = match i_content {
-                            0 => Rock(i_size),
-                            1 => Coin(i_size),
-                            2 => Garbage(i_size),
-                            3 => Fire,
-                            4 => Tree(i_size),
-                            5 => Bin(2..3),
-                            6 => Crate(2..3),
-                            7 => Bank(3..54),
-                            8 => Water(20),
-                            10 => Fish(3),
-                            11 => Market(20),
-                            12 => Building,
-                            13 => Bush(2),
-                            14 => JollyBlock(2),
-                            15 => Scarecrow,
-                            _ => Content::None,
-                        };
-                        row.push(Tile {
-                            tile_type,
-                            content,
-                            elevation: 0,
-                        });
-                    }
-                    map.push(row);
+    let mut config = match &args.config {
+        Some(path) => match VisualizerConfig::load(path) {
+            Ok(config) => config,
+            Err(e) => {
+                error!("{}", e);
+                process::exit(1);
+            }
+        },
+        None => VisualizerConfig::default(),
+    };
+    args.apply(&mut config);
+    if args.list_robots {
+        if let Ok(registry) = global_registry().lock() {
+            for (name, description) in registry.robots() {
+                println!("{:<16}{}", name, description);
+            }
+        }
+        return;
+    }
+    if let Some(path) = &args.save_config {
+        if let Err(e) = config.save(path) {
+            error!("{}", e);
+            process::exit(1);
+        }
+        info!("configuration written to {}", path);
+        return;
+    }
+    if let Some(paths) = &args.compare {
+        match ReplayComparison::load(&paths[0], &paths[1]) {
+            Ok(comparison) => run_comparison(comparison, &config),
+            Err(e) => {
+                error!("{}", e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+    //the seed is always logged, so that an interesting run can be repeated
+    let seed = config.seed.unwrap_or_else(rand::random);
+    let mut world = match (&config.world_file, &config.world_image) {
+        (Some(path), _) => match FileGenerator::load(&path.display().to_string()) {
+            Ok(generator) => {
+                info!("world loaded from {}", path.display());
+                WorldSource::File(generator)
+            }
+            Err(e) => {
+                error!("{}", e);
+                process::exit(1);
+            }
+        },
+        (None, Some(path)) => {
+            let content_path = config.content_image.as_ref().map(|path| path.display().to_string());
+            match ImageGenerator::load(&path.display().to_string(), content_path.as_deref()) {
+                Ok(generator) => {
+                    info!("world loaded from {}", path.display());
+                    WorldSource::Image(generator)
                 }
-                let environmental_conditions =
-                    EnvironmentalConditions::new(&[Sunny, Rainy], 15, 12).unwrap();
-
-                let max_score = rand::random::<f32>();
+                Err(e) => {
+                    error!("{}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        (None, None) => {
+            info!("world: {} {}x{}, seed: {}", config.world, config.world_size, config.world_size, seed);
+            match SeededGenerator::new(config.world, config.world_size, seed, config.densities.clone()) {
+                Ok(generator) => WorldSource::Seeded(generator),
+                Err(e) => {
+                    error!("{}", e);
+                    process::exit(1);
+                }
+            }
+        }
+    };
+    let world_size = world.size();
+    if let Some(path) = &args.export_world {
+        if let Err(e) = export_world(&mut world, path) {
+            error!("{}", e);
+            process::exit(1);
+        }
+        info!("world written to {}", path);
+        return;
+    }
 
-                (map, (0, 0), environmental_conditions, max_score, None)
+    //this binary has no hook for robots of other crates, only the ones of this crate are registered:
+    //another crate registers its robots with Visualizer::registry::register_robot from its own binary
+    let specs: Vec<RobotSpec> = if config.robots.is_empty() {
+        vec![RobotSpec::parse(&config.robot)]
+    } else {
+        config.robots.iter().map(|spec| RobotSpec::parse(spec)).collect()
+    };
+    let mut robots = Vec::new();
+    for (index, spec) in specs.iter().enumerate() {
+        //only the first robot records the frames and uses the given paths, the others get their index appended
+        let settings = RobotSettings {
+            view_radius: config.view_radius,
+            play_sounds: config.sound && index == 0,
+            audio: config.audio.clone(),
+            record_frames: config.record_frames && index == 0,
+            trace_path: config.trace_path.as_ref().map(|path| indexed_path(path, index)),
+            behavior: spec.behavior.clone().unwrap_or_else(|| config.behavior.clone()),
+            script_path: config.script_path.clone(),
+            seed,
+        };
+        let created_robot = match global_registry().lock() {
+            Ok(registry) => registry.create(&spec.name, &settings, world_size),
+            Err(e) => Err(format!("Couldnt lock the robot registry: {}", e)),
+        };
+        match created_robot {
+            Ok(robot) => {
+                info!("robot: {}, behavior: {}, seed: {}", spec.name, settings.behavior, seed);
+                robots.push(robot);
+            }
+            Err(e) => {
+                error!("{}", e);
+                process::exit(1);
+            }
+        }
+    }
+    // Channel to send to the visualizer the robot_map while the robot moves in the process_tick()
+    let (matrix_sender, matrix_receiver) = mpsc::channel();
+    let (ground_truth_sender, ground_truth_receiver) = mpsc::channel::<GroundTruth>();
+    if args.headless {
+        let options = HeadlessOptions {
+            ticks: config.ticks,
+            output_dir: config.output_dir.clone(),
+            //the gif needs ffmpeg, runs without window (e.g. in CI) only make it when asked with --gif
+            gif: args.gif.unwrap_or(false),
+        };
+        if robots.len() > 1 {
+            warn!(target: "runner", "only the first robot runs without window");
+        }
+        match run_headless(robots.remove(0), &mut world, world_size, &options) {
+            Ok(summary) => {
+                info!(target: "runner", "{}", summary.to_text());
+                return;
+            }
+            Err(e) => {
+                error!(target: "runner", "{}", e);
+                process::exit(1);
             }
         }
-        let mut generator = WorldGenerator::init(MAP_DIM);
-        //fine
+    }
+
+    //the runners of all the robots tick together, each one in its own copy of the world
+    let labels: Vec<String> = specs.iter().map(|spec| spec.label()).collect();
+    let mut runnables = Vec::new();
+    let mut state_collectors = Vec::new();
+    let mut exports = Vec::new();
+    let mut tile_updates = Vec::new();
+    for (index, robot) in robots.into_iter().enumerate() {
+        let VisualizedRobot {
+            runnable,
+            collector,
+            init_frames,
+            history,
+            metrics,
+            tile_updates: robot_tile_updates,
+        } = robot;
+        runnables.push(runnable);
+        tile_updates.push(robot_tile_updates);
+        state_collectors.push(collector);
+        //the metrics are written next to the history
+        let metrics_path = config.history_path.with_file_name(METRICS_FILE);
+        exports.push((
+            history,
+            init_frames,
+            indexed_path(&config.history_path, index),
+            metrics,
+            indexed_path(&metrics_path, index),
+        ));
+    }
 
+    //IMPLEMENTATION OF THE WORLDGENERATOR AND PROCESS TICK
+    let tick_limit = config.ticks;
+    let (control, mut clock) = control_channel(ControlStatus::from_tick_delay(Duration::from_millis(
+        config.tick_delay_ms,
+    )));
+    let make_gif = config.gif;
+    let publish_ground_truth = config.ground_truth && !args.terminal;
+    let mut lifecycle = Lifecycle::new();
+    lifecycle.spawn("runner", move |lifecycle| {
         // WorldGenerator del nostro gruppo
         //let mut generator = worldgen_unwrap::public::WorldgeneratorUnwrap::init(false, None);
 
         struct Tool;
         impl Tools for Tool {}
-        let i = r.iterations.clone();
-        let mut run = Runner::new(Box::new(r), &mut generator);
-        loop {
-            match run {
-                Ok(ref mut runner) => {
-                    //sleep(Duration::from_secs_f64(0.2));    //se si vuole che il robot vada più lento, scommentare + modificare il valore all'interno (f64)
-                    let _ = runner.game_tick();
-                    if *i.lock().unwrap() > 2000 {
-                        match init_frames.lock() {
-                            Ok(lock) => {
-                                if let Err(e) = lock.convert_frames_to_gif() {
-                                    println!("error creating the gif: {}", e)
-                                }
-                            }
-                            Err(e) => {
-                                eprintln!("Couldnt lock INIT_FRAMES implies impossible to create a gif: {}", e)
+        let mut runners = Vec::new();
+        let mut ground_truth = None;
+        for (index, runnable) in runnables.into_iter().enumerate() {
+            //the ground truth is the world given to the runner of the first robot, the one drawn by the window
+            let mut generator = CapturingGenerator::new(world.clone());
+            match Runner::new(runnable, &mut generator) {
+                Ok(runner) => {
+                    runners.push(runner);
+                    if publish_ground_truth && index == 0 {
+                        if let Some(tiles) = generator.take_tiles() {
+                            let truth = GroundTruth::new(&tiles);
+                            if ground_truth_sender.send(truth.clone()).is_err() {
+                                warn!(target: "runner", "the window is gone, the ground truth was not sent");
                             }
+                            ground_truth = Some(truth);
                         }
-                        break;
                     }
                 }
-                Err(e) => println!("{:?}", e),
+                Err(e) => {
+                    error!(target: "runner", "Couldnt create the runner: {:?}", e);
+                    lifecycle.set_phase(RunPhase::Failed(format!("Couldnt create the runner: {:?}", e)));
+                    return;
+                }
             }
         }
+        //runs until the tick limit or until the window is closed, the history is exported in both cases;
+        //the ticks are counted here, like in the headless mode, since not every robot records its history
+        let mut ticks = 0;
+        while !lifecycle.is_shutting_down() && ticks < tick_limit {
+            //waits for the speed set with --tick-delay or the window, and while paused
+            clock.wait_for_tick();
+            for runner in runners.iter_mut() {
+                if let Err(e) = runner.game_tick() {
+                    error!(target: "runner", "{:?}", e);
+                }
+            }
+            ticks += 1;
+            //published for the window: the TICKS bar, the fog and the summary
+            lifecycle.set_ticks(ticks);
+            //the tiles changed by the first robot are applied to the ground truth, sent again when it changed;
+            //the changes of the other robots are dropped, they run in their own copy of the world
+            for (index, updates) in tile_updates.iter().enumerate() {
+                let updates = match updates.lock() {
+                    Ok(mut lock) => std::mem::take(&mut *lock),
+                    Err(e) => {
+                        error!(target: "runner", "Couldnt lock TILE_UPDATES: {}", e);
+                        continue;
+                    }
+                };
+                if let Some(truth) = ground_truth.as_mut().filter(|_| index == 0 && !updates.is_empty()) {
+                    for (coordinates, tile) in &updates {
+                        truth.update_tile(*coordinates, tile);
+                    }
+                    if ground_truth_sender.send(truth.clone()).is_err() {
+                        warn!(target: "runner", "the window is gone, the ground truth was not sent");
+                    }
+                }
+            }
+        }
+        lifecycle.set_phase(RunPhase::Exporting);
+        let mut failure = None;
+        for (index, (history, init_frames, history_path, metrics, metrics_path)) in exports.iter().enumerate() {
+            let history_path = history_path.display().to_string();
+            let metrics_path = metrics_path.display().to_string();
+            if let Err(e) = export_run(history, init_frames, &history_path, metrics, &metrics_path, make_gif && index == 0) {
+                error!(target: "runner", "{}", e);
+                failure.get_or_insert(e);
+            }
+        }
+        match failure {
+            Some(e) => lifecycle.set_phase(RunPhase::Failed(e)),
+            None => lifecycle.set_phase(RunPhase::Finished),
+        }
+    });
+
+    //send the new state
+    let send_interval = Duration::from_millis(config.send_interval_ms);
+    lifecycle.spawn("sender", move |lifecycle| {
+        while !lifecycle.is_shutting_down() {
+            let states = state_collectors.iter().map(|collector| collector.collect()).collect();
+            if let Err(e) = matrix_sender.send(states) {
+                //the window or the terminal is gone
+                error!(target: "sender", "Failed to send data through the channel: {}", e);
+                break;
+            }
+            thread::sleep(send_interval);
+        }
     });
 
-    let window_size = Size::from((WINDOW_SIZE.0 as u32, WINDOW_SIZE.1 as u32));
-    println!("building window");
+    if args.terminal {
+        let last_state = run_terminal(matrix_receiver, &lifecycle.handle());
+        match (lifecycle.phase(), last_state) {
+            (RunPhase::Finished, Some(state)) => println!("{}", RunSummary::from_state(&state, lifecycle.ticks()).to_text()),
+            (RunPhase::Failed(e), _) => eprintln!("run failed: {}", e),
+            _ => {}
+        }
+        drop(control);
+        lifecycle.shutdown();
+        return;
+    }
+
+    let window_size = Size::from((config.window_width, config.window_height));
+    info!(target: "window", "building window");
     let mut window: PistonWindow = WindowSettings::new("grid", window_size)
         .exit_on_esc(true)
         .resizable(false)
@@ -192,128 +545,18 @@ fn main() {
         .build()
         .unwrap();
 
-    let mut glyphs = match window.load_font(DEFAULT_FONT_PATH) {
+    let mut glyphs = match window.load_font(&config.font_path) {
         Ok(_glyphs) => Some(_glyphs),
         Err(e) => {
-            eprintln!("Couldnt load glyphs: {}", e);
+            error!(target: "window", "Couldnt load glyphs: {}", e);
             None
         }
     };
-    // initiate color matrix
-    let initial_color_matrix = Arc::new(Mutex::new(vec![
-        vec![[0.0, 0.0, 0.0, 1.0]; MAP_DIM];
-        MAP_DIM
-    ]));
-    let initial_content_color_matrix =
-        Arc::new(Mutex::new(vec![
-            vec![[0.0, 0.0, 0.0, 1.0]; MAP_DIM];
-            MAP_DIM
-        ]));
-
-    //send the new map
-    let matrix_sender = matrix_sender.clone();
-    thread::spawn(move || {
-        loop {
-            let updated_energy_to_be_sent = match current_robot_energy.lock() {
-                Ok(lock) => lock.clone(),
-                Err(e) => {
-                    eprintln!("Couldnt lock CURRENT_ROBOT_ENERGY in sender thread: {} -> value has been set to a default value", e);
-                    0
-                }
-            };
-
-            let updated_score_to_be_sent = match _score.lock() {
-                Ok(lock) => lock.clone(),
-                Err(e) => {
-                    eprintln!("Couldnt lock SCORE in sender thread: {} -> value has been set to a default value", e);
-                    0.0f32
-                }
-            };
-
-            let updated_tile_matrix = match current_robot_map.lock() {
-                Ok(lock) => lock.clone(),
-                Err(e) => {
-                    eprintln!("Couldnt lock CURRENT_ROBOT_MAP in sender thread: {} -> value has been set to a default value", e);
-                    None
-                }
-            };
-
-            //create a matrix containing the colors for each tile_type/contend at index [i][j]
-            convert_content_to_color_matrix(&updated_tile_matrix, &initial_content_color_matrix);
-            convert_to_color_matrix(&updated_tile_matrix, &initial_color_matrix);
-
-            let matrix_to_be_sent = match initial_color_matrix.lock() {
-                Ok(lock) => lock.clone(),
-                Err(e) => {
-                    eprintln!("Couldnt lock in sender thread: {} -> default value", e);
-                    vec![vec![[0.0,0.0,0.0,1.0];MAP_DIM];MAP_DIM]
-                }
-            };
-
-            let matrix_content_to_be_sent = match initial_content_color_matrix.lock() {
-                Ok(lock) => lock.clone(),
-                Err(e) => {
-                    eprintln!("Couldnt lock in sender thread: {} -> default value", e);
-                    vec![vec![[0.0,0.0,0.0,1.0];MAP_DIM];MAP_DIM]
-                }
-            };
-
-            let coord_to_be_sent = match current_robot_coordinates.lock() {
-                Ok(lock) => *lock,
-                Err(e) => {
-                    eprintln!("Couldnt lock CURRENT_ROBOT_COORDINATES in sender thread: {} -> coordinates has been set to a default value:(0,0)", e);
-                    (0, 0)
-                }
-            };
-
-            let view_to_be_sent = match current_robot_view.lock() {
-                Ok(lock) => lock.clone(),
-                Err(e) => {
-                    eprintln!("Couldnt lock CURRENT_ROBOT_VIEW in sender thread: {} -> robot_view has been set to a default value", e);
-                    vec![vec![None; 3]; 3]
-                }
-            };
-
-            let backpack_to_be_sent = match current_robot_backpack.lock() {
-                Ok(lock) => lock.clone(),
-                Err(e) => {
-                    eprintln!("Couldnt lock CURRENT_ROBOT_BACKPACK in sender thread: {} -> robot_backpack has been set to a default value", e);
-                    String::new()
-                }
-            };
-
-            match matrix_sender.send((
-                matrix_to_be_sent,
-                matrix_content_to_be_sent,
-                coord_to_be_sent,
-                view_to_be_sent,
-                backpack_to_be_sent,
-                updated_energy_to_be_sent,
-                updated_score_to_be_sent
-            )) {
-                Ok(_) => {
-                    // Successfully sent
-                }
-                Err(e) => {
-                    // Handle the error
-                    eprintln!("Failed to send data through the channel: {}\nWindow will use the default value for each informaton", e);
-                }
-            }
-            thread::sleep(Duration::from_secs_f64(0.01));
-            //thread::sleep(Duration::from_secs_f64(3.0));
-        }
-    });
-
-    let mut current_tuple_information: Infos = (
-        vec![vec![[0.0, 0.0, 0.0, 1.0]; MAP_DIM]; MAP_DIM],
-        vec![vec![[0.0, 0.0, 0.0, 1.0]; MAP_DIM]; MAP_DIM],
-        (0, 0),
-        vec![vec![None; 3]; 3],
-        String::new(),
-        0usize,
-        0.0f32
-    );
+    let mut robot_slots = RobotSlots::new(labels, world_size);
 
+    let theme = config.theme;
+    let layout = HudLayout::new(config.window_width as f64, config.window_height as f64);
+    let rect_size = layout.grid_pixels / world_size as f64;
     let mut scroll_offset = [0.0, 0.0];
     let mut zoom_factor = 1.0;
     //let mut zoom_in_pressed = false;
@@ -324,17 +567,131 @@ fn main() {
     let mut down_pressed = false;
     let mut should_draw_robot_view = true;
     let mut should_draw_info_text = true;
+    let mut should_draw_environment = true;
+    let mut should_draw_event_log = true;
+    let mut should_draw_metrics = true;
+    let mut should_draw_charts = true;
+    let mut event_filter = EventFilter::default();
+    let mut event_log_scroll: usize = 0;
+    let mut split_view = args.split && robot_slots.len() > 1;
+    let mut should_draw_diff = true;
+    let mut diff_layer: Option<ColorMatrix> = None;
+    let mut fog = config.fog.clone();
+    let mut discoveries = DiscoveryTracker::new(world_size);
+    let mut fog_layer: Option<ColorMatrix> = None;
+    let mut ground_truth: Option<(GroundTruth, ColorMatrix)> = None;
+    let mut should_draw_ground_truth = config.ground_truth;
+    let mut teleports = TeleportLinks::default();
+    //the jumps being animated, with the index of the robot
+    let mut jumps: Vec<(JumpAnimation, usize)> = Vec::new();
+    let mut should_draw_teleport_links = false;
 
     while let Some(event) = window.next() {
-        if let Ok(updated_information) = matrix_receiver.try_recv() {
-            current_tuple_information = updated_information;
+        //only the latest states are drawn
+        let mut latest_states = None;
+        while let Ok(newer_states) = matrix_receiver.try_recv() {
+            latest_states = Some(newer_states);
+        }
+        if let Some(updated_states) = latest_states {
+            let previous_jumps: Vec<Option<usize>> =
+                robot_slots.slots().iter().map(|slot| slot.state.last_jump.map(|jump| jump.number)).collect();
+            robot_slots.update(updated_states);
+            teleports.update(robot_slots.slots().iter().flat_map(|slot| &slot.state.teleports));
+            //the robots detect their jumps when they move, a new jump is animated once
+            let now = Instant::now();
+            for (index, (slot, previous)) in robot_slots.slots().iter().zip(previous_jumps).enumerate() {
+                if let Some(jump) = slot.state.last_jump.filter(|jump| Some(jump.number) != previous) {
+                    jumps.push((JumpAnimation::new(&jump, now), index));
+                }
+            }
+            discoveries.update(robot_slots.tile_colors(), lifecycle.ticks());
+            diff_layer = None;
+            fog_layer = None;
+        }
+        //the runner sends the ground truth again when the robot changes the world, only the newest is drawn
+        if let Some(truth) = ground_truth_receiver.try_iter().last() {
+            let faded = truth.faded(GROUND_TRUTH_OPACITY);
+            ground_truth = Some((truth, faded));
+            fog_layer = None;
+        }
+        let showing_ground_truth = should_draw_ground_truth && ground_truth.is_some();
+        let run_phase = lifecycle.phase();
+        let run_ticks = lifecycle.ticks();
+
+        //Tab selects the robot shown by the HUD, F1-F8 show/hide the map discovered by each robot
+        if let Some(Button::Keyboard(key)) = event.press_args() {
+            match key {
+                Key::Tab => robot_slots.focus_next(),
+                Key::S if robot_slots.len() > 1 => split_view = !split_view,
+                Key::X => {
+                    should_draw_diff = !should_draw_diff;
+                    diff_layer = None;
+                }
+                Key::F1 | Key::F2 | Key::F3 | Key::F4 | Key::F5 | Key::F6 | Key::F7 | Key::F8 => {
+                    robot_slots.toggle_map(key as usize - Key::F1 as usize);
+                    fog_layer = None;
+                }
+                //fog of war: F cycles the style of the undiscovered tiles, I dims the tiles out of view,
+                //H highlights the recent discoveries
+                Key::F => {
+                    fog.unknown = fog.unknown.next();
+                    fog_layer = None;
+                }
+                Key::I => {
+                    fog.dim_out_of_view = !fog.dim_out_of_view;
+                    fog_layer = None;
+                }
+                Key::H => {
+                    fog.highlight_recent = !fog.highlight_recent;
+                    fog_layer = None;
+                }
+                //J connects the active teleports, the robot can jump between any two of them
+                Key::J => should_draw_teleport_links = !should_draw_teleport_links,
+                //G switches between what the robot knows and the real world under it
+                Key::G if ground_truth.is_some() => {
+                    should_draw_ground_truth = !should_draw_ground_truth;
+                    fog_layer = None;
+                }
+                _ => {}
+            }
+        }
+        //the diff of the first two robots is only computed again when their maps change
+        if split_view && should_draw_diff && diff_layer.is_none() {
+            let slots = robot_slots.slots();
+            diff_layer = Some(diff_overlay(&slots[0].state.tile_colors, &slots[1].state.tile_colors));
+        }
+        //like the diff, the fog only changes with the maps or the fog settings
+        if !split_view && fog_layer.is_none() {
+            let views: Vec<ViewArea> = robot_slots
+                .slots()
+                .iter()
+                .filter(|slot| slot.show_map)
+                .map(|slot| ViewArea::new(slot.state.coordinates, slot.state.robot_view.len()))
+                .collect();
+            //the undiscovered tiles show the ground truth instead of their fog color
+            let fog_config = if showing_ground_truth {
+                FogConfig {
+                    unknown: UnknownStyle::Hidden,
+                    ..fog.clone()
+                }
+            } else {
+                fog.clone()
+            };
+            fog_layer = Some(fog_overlay(
+                robot_slots.tile_colors(),
+                &discoveries,
+                lifecycle.ticks(),
+                &views,
+                &fog_config,
+            ));
         }
+        let state = robot_slots.focused_state();
 
         let coord_text = format!(
             "robot coordinates:({},{})",
-            current_tuple_information.2.1, current_tuple_information.2.0
+            state.coordinates.1, state.coordinates.0
         );
-        let coord_as_f64 = (current_tuple_information.2.1 as f64, current_tuple_information.2.0 as f64);
+        let coord_as_f64 = (state.coordinates.1 as f64, state.coordinates.0 as f64);
 
         //key pressed handling
         if let Some(Button::Keyboard(key)) = event.press_args() {
@@ -361,6 +718,33 @@ fn main() {
                 Key::T => {
                     should_draw_info_text = !should_draw_info_text
                 }
+                Key::N => {
+                    should_draw_environment = !should_draw_environment
+                }
+                Key::L => {
+                    should_draw_event_log = !should_draw_event_log
+                }
+                Key::C => {
+                    should_draw_charts = !should_draw_charts
+                }
+                Key::M => {
+                    should_draw_metrics = !should_draw_metrics
+                }
+                Key::PageUp => {
+                    event_log_scroll += 1;
+                }
+                Key::PageDown => {
+                    event_log_scroll = event_log_scroll.saturating_sub(1);
+                }
+                Key::Space => control.send(ControlCommand::TogglePause),
+                Key::Period => control.send(ControlCommand::Step),
+                Key::RightBracket => control.send(ControlCommand::Faster),
+                Key::LeftBracket => control.send(ControlCommand::Slower),
+                Key::D1 | Key::D2 | Key::D3 | Key::D4 | Key::D5 | Key::D6 | Key::D7 => {
+                    let index = key as usize - Key::D1 as usize;
+                    event_filter.toggle(EventKind::ALL[index]);
+                    event_log_scroll = 0;
+                }
                 _ => {}
             }
         }
@@ -381,6 +765,15 @@ fn main() {
             }
         });
 
+        //mouse wheel scrolls the event log, older entries when scrolling up
+        if let Some([_, wheel]) = event.mouse_scroll_args() {
+            if wheel > 0.0 {
+                event_log_scroll += 1;
+            } else if wheel < 0.0 {
+                event_log_scroll = event_log_scroll.saturating_sub(1);
+            }
+        }
+
         //keys released -> stop scrolling
         if let Some(Button::Keyboard(key)) = event.release_args() {
             match key {
@@ -414,91 +807,485 @@ fn main() {
         }
 
         window.draw_2d(&event, |context, graphics, device| {
-            clear([0.0, 0.0, 0.0, 1.0], graphics);
+            clear(theme.background(), graphics);
 
-            //draws a 3x3 grid with rectangles for the tile_type and circles for the content
+            //draws the robot view with rectangles for the tile_type, circles and quantities for the content
            if should_draw_robot_view {
                draw_robot_view(
-                   &convert_robot_view_to_color_matrix(&current_tuple_information.3),
-                   &convert_robot_content_view_to_color_matrix(&current_tuple_information.3),
+                   &convert_robot_view_to_color_matrix(&state.robot_view),
+                   &convert_robot_content_view_to_color_matrix(&state.robot_view),
+                   &convert_robot_view_to_quantity_matrix(&state.robot_view),
                    context,
                    graphics,
-                   50.0,
+                   glyphs.as_mut(),
+                   layout.robot_view,
                );
            }
 
-            draw_optimized_grid(
-                &current_tuple_information.0,
-                context,
-                graphics,
-                (MAP_DIM, MAP_DIM),
-                RECT_SIZE,
-                scroll_offset,
-                zoom_factor,
-                //the following is used to draw the robot position
-                coord_as_f64.0,
-                coord_as_f64.1,
-            );
+            if split_view {
+                //the first two robots side by side, each one with its own map and trail
+                let slots = robot_slots.slots();
+                let views = [&slots[0], &slots[1]].map(|slot| RunView {
+                    label: &slot.label,
+                    tile_colors: &slot.state.tile_colors,
+                    coordinates: slot.state.coordinates,
+                    energy: slot.state.energy,
+                    score: slot.state.score,
+                });
+                draw_split_view(
+                    [(&views[0], slots[0].color), (&views[1], slots[1].color)],
+                    diff_layer.as_ref(),
+                    context,
+                    graphics,
+                    glyphs.as_mut(),
+                    (world_size, world_size),
+                    rect_size,
+                    scroll_offset,
+                    zoom_factor,
+                    layout.grid_pixels,
+                );
+                let contexts = split_view_contexts(context, layout.grid_pixels, layout.grid_pixels);
+                for (slot, side_context) in slots.iter().zip(contexts) {
+                    draw_robot_trail(&slot.trail, slot.color, side_context, graphics, rect_size, scroll_offset, zoom_factor);
+                }
+            } else {
+                if let Some((_, faded)) = ground_truth.as_ref().filter(|_| showing_ground_truth) {
+                    draw_color_layer(
+                        faded,
+                        context,
+                        graphics,
+                        (world_size, world_size),
+                        rect_size,
+                        scroll_offset,
+                        zoom_factor,
+                        layout.window_size,
+                    );
+                }
+                draw_optimized_grid(
+                    robot_slots.tile_colors(),
+                    context,
+                    graphics,
+                    (world_size, world_size),
+                    rect_size,
+                    scroll_offset,
+                    zoom_factor,
+                    //the following is used to draw the robot position
+                    coord_as_f64.0,
+                    coord_as_f64.1,
+                    layout.window_size,
+                );
+
+                if let Some(fog_layer) = &fog_layer {
+                    draw_color_layer(
+                        fog_layer,
+                        context,
+                        graphics,
+                        (world_size, world_size),
+                        rect_size,
+                        scroll_offset,
+                        zoom_factor,
+                        layout.window_size,
+                    );
+                }
+                if fog.unknown == UnknownStyle::Hatched {
+                    draw_hatching(
+                        robot_slots.tile_colors(),
+                        UNKNOWN_HATCH_LINE,
+                        context,
+                        graphics,
+                        (world_size, world_size),
+                        rect_size,
+                        scroll_offset,
+                        zoom_factor,
+                        layout.window_size,
+                    );
+                }
+
+                draw_teleport_links(
+                    &teleports,
+                    should_draw_teleport_links,
+                    context,
+                    graphics,
+                    glyphs.as_mut(),
+                    rect_size,
+                    scroll_offset,
+                    zoom_factor,
+                );
+
+                //every robot in its own color, with the last positions it went through
+                for slot in robot_slots.slots() {
+                    draw_robot_trail(
+                        &slot.trail,
+                        slot.color,
+                        context,
+                        graphics,
+                        rect_size,
+                        scroll_offset,
+                        zoom_factor,
+                    );
+                }
+
+                let now = Instant::now();
+                jumps.retain(|(jump, _)| !jump.is_over(now));
+                for (jump, index) in &jumps {
+                    draw_teleport_jump(
+                        jump,
+                        robot_slots.slots()[*index].color,
+                        now,
+                        context,
+                        graphics,
+                        rect_size,
+                        scroll_offset,
+                        zoom_factor,
+                    );
+                }
+            }
+
+            //night darkening and rain overlay
+            if should_draw_environment {
+                draw_environment_overlay(
+                    &state.environment,
+                    context,
+                    graphics,
+                    (world_size, world_size),
+                    rect_size,
+                    scroll_offset,
+                    zoom_factor,
+                );
+            }
 
             if should_draw_info_text {
                 if let Some(ref mut glyphs) = glyphs {
-                    let starting_text_x: u32 = 50;
-                    let starting_text_y: u32 = 785;
+                    let [starting_text_x, starting_text_y] = layout.coordinates_text;
                     //coordinates
                     draw_text(
                         &context,
                         graphics,
                         glyphs,
-                        [1.0; 4],
+                        theme.text(),
                         [starting_text_x, starting_text_y],
                         coord_text.as_str(),
                     );
 
-                    //robot view
-                    draw_texts(
-                        &current_tuple_information.3.get(0),
-                        &current_tuple_information.3.get(1),
-                        &current_tuple_information.3.get(2),
+                    if let Some((truth, _)) = ground_truth.as_ref().filter(|_| showing_ground_truth) {
+                        draw_text(
+                            &context,
+                            graphics,
+                            glyphs,
+                            theme.text(),
+                            [starting_text_x + 280, starting_text_y],
+                            format!("truth: {} unknown", truth.unknown_tiles(robot_slots.tile_colors())).as_str(),
+                        );
+                    }
+
+                    //backpack
+                    draw_backpack_panel(
+                        &state.backpack,
+                        state.tick(),
+                        &context,
+                        graphics,
+                        glyphs,
+                        layout.backpack,
+                    );
+
+                    draw_energy(
+                        state.energy,
+                        theme.text(),
+                        layout.status,
                         &context,
                         graphics,
                         glyphs,
-                        starting_text_y,
                     );
 
-                    //backpack
-                    draw_text(
+                    draw_score(
+                        state.score,
+                        theme.text(),
+                        layout.status,
                         &context,
                         graphics,
                         glyphs,
-                        [1.0; 4],
-                        [starting_text_x, 30 + starting_text_y + 25 * 5],
-                        current_tuple_information.4.as_str(),
                     );
 
-                    draw_energy(
-                        current_tuple_information.5,
+                    draw_environment(
+                        &state.environment,
+                        theme.text(),
+                        layout.status,
                         &context,
                         graphics,
                         glyphs,
                     );
 
-                    draw_score(
-                        current_tuple_information.6,
+                    draw_run_progress(
+                        run_ticks,
+                        tick_limit,
+                        &control.status(),
+                        theme.text(),
+                        layout.status,
                         &context,
                         graphics,
                         glyphs,
                     );
 
-                    glyphs.factory.encoder.flush(device);
+                    //one card per robot when several robots are running
+                    if robot_slots.len() > 1 {
+                        draw_robot_cards(
+                            &robot_slots,
+                            &context,
+                            graphics,
+                            glyphs,
+                            layout.robot_cards,
+                        );
+                    }
+
+                    if should_draw_charts {
+                        draw_history(
+                            &state.history,
+                            &layout,
+                            &context,
+                            graphics,
+                            glyphs,
+                        );
+                    }
+
+                    if should_draw_metrics {
+                        draw_metrics_panel(
+                            &state.metrics,
+                            &context,
+                            graphics,
+                            glyphs,
+                            layout.metrics,
+                        );
+                    }
+
+                    if should_draw_event_log {
+                        draw_event_log(
+                            &state.event_log,
+                            &event_filter,
+                            event_log_scroll,
+                            &context,
+                            graphics,
+                            glyphs,
+                            layout.event_log,
+                        );
+                    }
+                }
+
+            }
+
+            //end-of-run summary, over the grid
+            if let Some(ref mut glyphs) = glyphs {
+                match &run_phase {
+                    RunPhase::Finished => draw_summary_panel(
+                        "RUN FINISHED - press Esc to close",
+                        [0.4, 1.0, 0.4, 1.0],
+                        &RunSummary::from_state(&state, run_ticks).to_lines(),
+                        &context,
+                        graphics,
+                        glyphs,
+                        layout.summary,
+                    ),
+                    RunPhase::Failed(e) => draw_summary_panel(
+                        "RUN FAILED - press Esc to close",
+                        [1.0, 0.3, 0.3, 1.0],
+                        &[e.clone()],
+                        &context,
+                        graphics,
+                        glyphs,
+                        layout.summary,
+                    ),
+                    RunPhase::Running | RunPhase::Exporting => {}
                 }
+            }
 
+            //text is drawn also by the robot view, so the glyphs are flushed even if the info text is hidden
+            if let Some(ref mut glyphs) = glyphs {
+                glyphs.factory.encoder.flush(device);
             }
         });
     }
+
+    //the window is closed: stop the robot, export what was done and wait for the threads
+    info!(target: "window", "window closed, shutting down");
+    drop(control);
+    lifecycle.shutdown();
+}
+
+/// Plays two recorded runs side by side, synchronized by tick. Space pauses, `.` steps,
+/// `[` and `]` change the speed, Home restarts, X toggles the diff overlay; the arrows
+/// and +/- move the camera of both runs.
+fn run_comparison(mut comparison: ReplayComparison, config: &VisualizerConfig) {
+    let window_size = Size::from((config.window_width, config.window_height));
+    let mut window: PistonWindow = WindowSettings::new("comparison", window_size)
+        .exit_on_esc(true)
+        .resizable(false)
+        .graphics_api(OpenGL::V3_2)
+        .build()
+        .unwrap();
+    let mut glyphs = match window.load_font(&config.font_path) {
+        Ok(_glyphs) => Some(_glyphs),
+        Err(e) => {
+            error!(target: "window", "Couldnt load glyphs: {}", e);
+            None
+        }
+    };
+
+    let theme = config.theme;
+    let map_size = comparison.map_size();
+    let layout = HudLayout::new(config.window_width as f64, config.window_height as f64);
+    let rect_size = layout.grid_pixels / map_size as f64;
+    let mut scroll_offset = [0.0, 0.0];
+    let mut zoom_factor = 1.0;
+    //without a tick delay the replays are played at 10 ticks per second
+    let mut status = ControlStatus::from_tick_delay(Duration::from_millis(config.tick_delay_ms.max(100)));
+    let mut pending_ticks = 0.0;
+    let mut should_draw_diff = true;
+    let mut diff_layer: Option<ColorMatrix> = None;
+    let mut diff_lines: Option<Vec<String>> = None;
+
+    while let Some(event) = window.next() {
+        if let Some(Button::Keyboard(key)) = event.press_args() {
+            match key {
+                Key::Up => scroll_offset[1] -= SCROLL_AMOUNT * 4.0,
+                Key::Down => scroll_offset[1] += SCROLL_AMOUNT * 4.0,
+                Key::Left => scroll_offset[0] -= SCROLL_AMOUNT * 4.0,
+                Key::Right => scroll_offset[0] += SCROLL_AMOUNT * 4.0,
+                Key::Equals | Key::Plus => zoom_factor += ZOOM_AMOUNT,
+                Key::Minus => zoom_factor = (zoom_factor - ZOOM_AMOUNT).max(0.1),
+                Key::Space => {
+                    status.apply(ControlCommand::TogglePause);
+                }
+                Key::Period => {
+                    status.apply(ControlCommand::Step);
+                    comparison.seek(comparison.tick() + 1);
+                    diff_layer = None;
+                    diff_lines = None;
+                }
+                Key::RightBracket => {
+                    status.apply(ControlCommand::Faster);
+                }
+                Key::LeftBracket => {
+                    status.apply(ControlCommand::Slower);
+                }
+                Key::Home => {
+                    comparison.seek(0);
+                    diff_layer = None;
+                    diff_lines = None;
+                }
+                Key::X => {
+                    should_draw_diff = !should_draw_diff;
+                    diff_layer = None;
+                }
+                _ => {}
+            }
+        }
+
+        //both replays move forward together, at the selected speed
+        event.update(|args| {
+            if status.paused || comparison.tick() >= comparison.last_tick() {
+                return;
+            }
+            let ticks = if status.ticks_per_second > 0.0 {
+                pending_ticks += args.dt * status.ticks_per_second;
+                let ticks = pending_ticks.floor();
+                pending_ticks -= ticks;
+                ticks as usize
+            } else {
+                1
+            };
+            if ticks > 0 {
+                comparison.seek(comparison.tick() + ticks);
+                diff_layer = None;
+                diff_lines = None;
+            }
+        });
+
+        let (left, right) = (comparison.left(), comparison.right());
+        if should_draw_diff && diff_layer.is_none() {
+            diff_layer = Some(diff_overlay(left.tile_colors, right.tile_colors));
+        }
+        if diff_lines.is_none() {
+            diff_lines = Some(ExplorationDiff::new(left.tile_colors, right.tile_colors).to_lines());
+        }
+
+        window.draw_2d(&event, |context, graphics, device| {
+            clear(theme.background(), graphics);
+            draw_split_view(
+                [(&left, ROBOT_COLORS[0]), (&right, ROBOT_COLORS[1])],
+                diff_layer.as_ref().filter(|_| should_draw_diff),
+                context,
+                graphics,
+                glyphs.as_mut(),
+                (map_size, map_size),
+                rect_size,
+                scroll_offset,
+                zoom_factor,
+                layout.grid_pixels,
+            );
+
+            if let Some(ref mut glyphs) = glyphs {
+                let mut lines = vec![
+                    format!("tick {}/{}", comparison.tick(), comparison.last_tick()),
+                    status.to_text(),
+                    String::new(),
+                ];
+                lines.extend(left.to_lines());
+                lines.push(String::new());
+                lines.extend(right.to_lines());
+                lines.push(String::new());
+                lines.extend(diff_lines.iter().flatten().cloned());
+                draw_summary_panel(
+                    "COMPARISON",
+                    [1.0; 4],
+                    &lines,
+                    &context,
+                    graphics,
+                    glyphs,
+                    layout.comparison,
+                );
+                glyphs.factory.encoder.flush(device);
+            }
+        });
+    }
+}
+
+/// Draws the states received from the sender thread in the terminal until the run is over
+/// or the sender stops, and returns the last state drawn. Only the first robot is drawn.
+fn run_terminal(receiver: mpsc::Receiver<Vec<VisualizerState>>, lifecycle: &LifecycleHandle) -> Option<VisualizerState> {
+    let renderer = TerminalRenderer::from_env();
+    let mut stdout = std::io::stdout();
+    if let Err(e) = renderer.start(&mut stdout) {
+        error!(target: "terminal", "Couldnt prepare the terminal: {}", e);
+        return None;
+    }
+    let mut last_state = None;
+    while let Ok(mut states) = receiver.recv() {
+        //only the latest state is drawn
+        while let Ok(newer_states) = receiver.try_recv() {
+            states = newer_states;
+        }
+        if states.is_empty() {
+            continue;
+        }
+        let state = states.swap_remove(0);
+        if let Err(e) = renderer.draw(&state, &mut stdout) {
+            error!(target: "terminal", "Couldnt draw in the terminal: {}", e);
+            break;
+        }
+        last_state = Some(state);
+        //the state received after the end of the run is the final one
+        if lifecycle.phase().is_over() {
+            break;
+        }
+        thread::sleep(TERMINAL_FRAME_INTERVAL);
+    }
+    let _ = renderer.stop(&mut stdout);
+    last_state
 }
 
 fn draw_score(
     score: f32,
+    color: [f32; 4],
+    origin: [f64; 2],
     context: &piston_window::Context,
     graphics: &mut G2d,
     glyphs: &mut Glyphs,
@@ -508,119 +1295,95 @@ fn draw_score(
         context,
         graphics,
         glyphs,
-        [1.0; 4],
-        [770, 20],
+        color,
+        [origin[0] as u32, origin[1] as u32],
         "SCORE:",
     );
     draw_text(
         context,
         graphics,
         glyphs,
-        [1.0; 4],
-        [840, 20],
+        color,
+        [origin[0] as u32 + 70, origin[1] as u32],
         score.floor().to_string().as_str(),
     );
 }
 
-fn draw_energy(
-    energy: usize,
+fn draw_environment(
+    environment: &EnvironmentInfo,
+    color: [f32; 4],
+    origin: [f64; 2],
     context: &piston_window::Context,
     graphics: &mut G2d,
     glyphs: &mut Glyphs,
 ) {
-    //ENERGY:
     draw_text(
         context,
         graphics,
         glyphs,
-        [1.0; 4],
-        [770, 55],
-        "ENERGY:",
+        color,
+        [origin[0] as u32, origin[1] as u32 + 75],
+        environment.to_text().as_str(),
     );
-    //actual energy value
-    draw_text(
+}
+
+fn draw_history(
+    history: &RunHistory,
+    layout: &HudLayout,
+    context: &piston_window::Context,
+    graphics: &mut G2d,
+    glyphs: &mut Glyphs,
+) {
+    let energy: Vec<f64> = history.energy().iter().map(|energy| *energy as f64).collect();
+    let score: Vec<f64> = history.score().iter().map(|score| *score as f64).collect();
+    draw_line_chart(
+        &energy,
+        [1.0, 0.9, 0.3, 1.0],
+        "ENERGY",
         context,
         graphics,
         glyphs,
-        [1.0; 4],
-        [845, 55],
-        energy.to_string().as_str(),
+        layout.energy_chart,
     );
-    //the rectangle
-    draw_energy_level(
-        energy,
+    draw_line_chart(
+        &score,
+        [0.4, 1.0, 0.4, 1.0],
+        "SCORE",
         context,
         graphics,
-        770.0,
-        60.0
-    )
+        glyphs,
+        layout.score_chart,
+    );
 }
 
-fn draw_texts(
-    vec1: &Option<&Vec<Option<Tile>>>,
-    vec2: &Option<&Vec<Option<Tile>>>,
-    vec3: &Option<&Vec<Option<Tile>>>,
+fn draw_energy(
+    energy: usize,
+    color: [f32; 4],
+    origin: [f64; 2],
     context: &piston_window::Context,
     graphics: &mut G2d,
     glyphs: &mut Glyphs,
-    starting_text_y: u32,
 ) {
-    let start_x: u32 = 50;
-    let start_y: u32 = starting_text_y + 35;
-    let offset: u32 = 25;
-    draw_text(
-        context,
-        graphics,
-        glyphs,
-        [1.0; 4],
-        [start_x, start_y],
-        "Robot view :",
-    );
-
-    if let Some(maybe_vec) = vec1 {
-        draw_text(
-            context,
-            graphics,
-            glyphs,
-            [1.0; 4],
-            [start_x, start_y + offset],
-            create_text_view(maybe_vec).as_str(),
-        );
-    }
-
-    if let Some(maybe_vec) = vec2 {
-        draw_text(
-            context,
-            graphics,
-            glyphs,
-            [1.0; 4],
-            [start_x, start_y + offset * 2],
-            create_text_view(maybe_vec).as_str(),
-        );
-    }
-
-    if let Some(maybe_vec) = vec3 {
-        draw_text(
-            context,
-            graphics,
-            glyphs,
-            [1.0; 4],
-            [start_x, start_y + offset * 3],
-            create_text_view(maybe_vec).as_str(),
-        );
-    }
+    ProgressBar::new(DEFAULT_MAX_ENERGY as f64, [origin[0], origin[1] + 25.0, 110.0, 12.0])
+        .with_ramp(ColorRamp::energy())
+        .with_text_color(color)
+        .with_label("ENERGY")
+        .draw(energy as f64, context, graphics, Some(glyphs));
 }
 
-fn create_text_view(vec: &Vec<Option<Tile>>) -> String {
-    let mut result = String::new();
-    for maybe_tile in vec {
-        let content_str = match maybe_tile {
-            Some(tile) => {
-                format!("{:?} ", tile.content)
-            } // Use Display formatting
-            None => "[x]".to_string(), // Placeholder for None
-        };
-        result += &format!("{:<10} ", content_str); // Adjusted for uniform spacing
-    }
-    result
+fn draw_run_progress(
+    tick: usize,
+    tick_limit: usize,
+    status: &ControlStatus,
+    color: [f32; 4],
+    origin: [f64; 2],
+    context: &piston_window::Context,
+    graphics: &mut G2d,
+    glyphs: &mut Glyphs,
+) {
+    ProgressBar::new(tick_limit as f64, [origin[0], origin[1] + 115.0, 110.0, 6.0])
+        .with_ramp(ColorRamp::solid([0.6, 0.6, 1.0, 1.0]))
+        .with_text_color(color)
+        .with_label(&format!("TICKS  {}", status.to_text()))
+        .draw(tick as f64, context, graphics, Some(glyphs));
 }
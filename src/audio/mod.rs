@@ -0,0 +1,305 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{unbounded, Sender};
+use log::{debug, error, warn};
+use robotics_lib::event::events::Event;
+use rodio::source::Source;
+use rodio::{Decoder, OutputStream, OutputStreamHandle};
+use serde::{Deserialize, Serialize};
+
+use crate::util::DEFAULT_SOUNDS_PATH;
+
+/// The robot events a sound can be bound to, one per `Event` variant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SoundEvent {
+    Ready,
+    Terminated,
+    TimeChanged,
+    DayChanged,
+    EnergyRecharged,
+    EnergyConsumed,
+    Moved,
+    TileContentUpdated,
+    AddedToBackpack,
+    RemovedFromBackpack,
+}
+
+impl SoundEvent {
+    pub fn from_event(event: &Event) -> Self {
+        match event {
+            Event::Ready => SoundEvent::Ready,
+            Event::Terminated => SoundEvent::Terminated,
+            Event::TimeChanged(_) => SoundEvent::TimeChanged,
+            Event::DayChanged(_) => SoundEvent::DayChanged,
+            Event::EnergyRecharged(_) => SoundEvent::EnergyRecharged,
+            Event::EnergyConsumed(_) => SoundEvent::EnergyConsumed,
+            Event::Moved(_, _) => SoundEvent::Moved,
+            Event::TileContentUpdated(_, _) => SoundEvent::TileContentUpdated,
+            Event::AddedToBackpack(_, _) => SoundEvent::AddedToBackpack,
+            Event::RemovedFromBackpack(_, _) => SoundEvent::RemovedFromBackpack,
+        }
+    }
+}
+
+/// The sound played for an event.
+///
+/// Attributes:
+/// - `file`: The sound file, relative to `AudioConfig::sounds_dir`.
+/// - `volume`: The amplification of the sound, 1.0 is the original volume.
+/// - `min_interval_ms`: The sound is not played again before this many milliseconds,
+///   so that frequent events don't pile up.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SoundMapping {
+    pub file: String,
+    pub volume: f32,
+    #[serde(default)]
+    pub min_interval_ms: u64,
+}
+
+impl SoundMapping {
+    pub fn new(file: &str, volume: f32, min_interval_ms: u64) -> Self {
+        Self {
+            file: file.to_string(),
+            volume,
+            min_interval_ms,
+        }
+    }
+}
+
+/// Which sound is played for which event.
+///
+/// Attributes:
+/// - `sounds_dir`: The directory of the sound files.
+/// - `sounds`: The sound of each event; events missing from the map are silent.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioConfig {
+    pub sounds_dir: PathBuf,
+    pub sounds: BTreeMap<SoundEvent, SoundMapping>,
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self {
+            sounds_dir: PathBuf::from(DEFAULT_SOUNDS_PATH),
+            sounds: BTreeMap::from([
+                (SoundEvent::TimeChanged, SoundMapping::new("prova.ogg", 0.5, 1000)),
+                (SoundEvent::AddedToBackpack, SoundMapping::new("AddedToBackpack.ogg", 0.5, 100)),
+                (SoundEvent::RemovedFromBackpack, SoundMapping::new("RemovedFromBackpack.ogg", 0.5, 100)),
+            ]),
+        }
+    }
+}
+
+/// Where the sounds end up. It is built and used only by the audio thread, so it doesn't
+/// need to be `Send` (the rodio output stream isn't).
+pub trait AudioSink {
+    /// Starts playing the sound without waiting for it to end.
+    fn play(&mut self, path: &Path, volume: f32) -> Result<(), String>;
+}
+
+/// Plays the sounds on the default output device. All the sounds are mixed on the same
+/// stream, and the files are read only the first time they are played.
+pub struct RodioSink {
+    _stream: OutputStream,
+    handle: OutputStreamHandle,
+    files: HashMap<PathBuf, Arc<[u8]>>,
+}
+
+impl RodioSink {
+    pub fn new() -> Result<Self, String> {
+        let (stream, handle) =
+            OutputStream::try_default().map_err(|e| format!("Error obtaining output stream: {}", e))?;
+        Ok(Self {
+            _stream: stream,
+            handle,
+            files: HashMap::new(),
+        })
+    }
+
+    fn read(&mut self, path: &Path) -> Result<Arc<[u8]>, String> {
+        if let Some(bytes) = self.files.get(path) {
+            return Ok(bytes.clone());
+        }
+        let bytes: Arc<[u8]> = fs::read(path)
+            .map_err(|e| format!("Error opening file '{}': {}", path.display(), e))?
+            .into();
+        self.files.insert(path.to_path_buf(), bytes.clone());
+        Ok(bytes)
+    }
+}
+
+impl AudioSink for RodioSink {
+    fn play(&mut self, path: &Path, volume: f32) -> Result<(), String> {
+        let bytes = self.read(path)?;
+        let source = Decoder::new(Cursor::new(bytes))
+            .map_err(|e| format!("Error decoding '{}': {}", path.display(), e))?
+            .amplify(volume);
+        self.handle
+            .play_raw(source.convert_samples())
+            .map_err(|e| format!("Error playing sound: {}", e))
+    }
+}
+
+/// Plays nothing, used when there is no audio device and by the tests.
+/// Every request is recorded in `played`.
+#[derive(Clone, Default)]
+pub struct NullSink {
+    pub played: Arc<Mutex<Vec<(PathBuf, f32)>>>,
+}
+
+impl AudioSink for NullSink {
+    fn play(&mut self, path: &Path, volume: f32) -> Result<(), String> {
+        match self.played.lock() {
+            Ok(mut played) => played.push((path.to_path_buf(), volume)),
+            Err(e) => return Err(format!("Couldnt lock PLAYED: {}", e)),
+        }
+        Ok(())
+    }
+}
+
+/// Plays the sound bound to each robot event on a dedicated audio thread, so that
+/// `handle_event` never waits for the audio device.
+///
+/// Attributes:
+/// - `config`: The sound of each event.
+/// - `sender`: Sends the sounds to the audio thread.
+/// - `worker`: The audio thread, joined when the manager is dropped.
+/// - `last_played`: When each event last played its sound, for the rate limiting.
+pub struct AudioManager {
+    config: AudioConfig,
+    sender: Option<Sender<(PathBuf, f32)>>,
+    worker: Option<JoinHandle<()>>,
+    last_played: HashMap<SoundEvent, Instant>,
+}
+
+impl AudioManager {
+    /// Plays on the default output device, or on a `NullSink` if there is none.
+    pub fn new(config: AudioConfig) -> Self {
+        Self::with_sink(config, || match RodioSink::new() {
+            Ok(sink) => Box::new(sink) as Box<dyn AudioSink>,
+            Err(e) => {
+                warn!("{}, sounds are disabled", e);
+                Box::new(NullSink::default())
+            }
+        })
+    }
+
+    /// # Arguments
+    /// * `config` - The sound of each event.
+    /// * `create_sink` - Builds the sink, called on the audio thread.
+    pub fn with_sink<F>(config: AudioConfig, create_sink: F) -> Self
+    where
+        F: FnOnce() -> Box<dyn AudioSink> + Send + 'static,
+    {
+        let (sender, receiver) = unbounded::<(PathBuf, f32)>();
+        let worker = thread::Builder::new().name(String::from("audio")).spawn(move || {
+            let mut sink = create_sink();
+            for (path, volume) in receiver {
+                if let Err(e) = sink.play(&path, volume) {
+                    error!("error playing '{}': {}", path.display(), e)
+                }
+            }
+        });
+        let worker = match worker {
+            Ok(worker) => Some(worker),
+            Err(e) => {
+                error!("Couldnt spawn the audio thread: {}", e);
+                None
+            }
+        };
+        Self {
+            config,
+            sender: Some(sender),
+            worker,
+            last_played: HashMap::new(),
+        }
+    }
+
+    /// Plays the sound of the event, unless it has none or it was played too recently.
+    ///
+    /// # Returns
+    /// If the sound was sent to the audio thread.
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        self.play_at(SoundEvent::from_event(event), Instant::now())
+    }
+
+    /// Same as `handle_event`, with the event already converted and the current time given.
+    pub fn play_at(&mut self, event: SoundEvent, now: Instant) -> bool {
+        let Some(mapping) = self.config.sounds.get(&event) else {
+            return false;
+        };
+        if let Some(last_played) = self.last_played.get(&event) {
+            if now.saturating_duration_since(*last_played) < Duration::from_millis(mapping.min_interval_ms) {
+                debug!("sound of {:?} skipped, played too recently", event);
+                return false;
+            }
+        }
+        let Some(sender) = &self.sender else {
+            return false;
+        };
+        let path = self.config.sounds_dir.join(&mapping.file);
+        if sender.send((path, mapping.volume)).is_err() {
+            error!("the audio thread stopped, sounds are disabled");
+            self.sender = None;
+            return false;
+        }
+        self.last_played.insert(event, now);
+        true
+    }
+}
+
+impl Drop for AudioManager {
+    fn drop(&mut self) {
+        // closing the channel stops the audio thread
+        self.sender = None;
+        if let Some(worker) = self.worker.take() {
+            if worker.join().is_err() {
+                error!("the audio thread panicked");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager_with_null_sink(config: AudioConfig) -> (AudioManager, NullSink) {
+        let sink = NullSink::default();
+        let thread_sink = sink.clone();
+        (AudioManager::with_sink(config, move || Box::new(thread_sink)), sink)
+    }
+
+    #[test]
+    fn mapped_events_play_their_file_and_volume() {
+        let (mut manager, sink) = manager_with_null_sink(AudioConfig::default());
+        assert!(manager.play_at(SoundEvent::AddedToBackpack, Instant::now()));
+        assert!(!manager.play_at(SoundEvent::Moved, Instant::now()));
+        drop(manager);
+
+        let played = sink.played.lock().unwrap();
+        assert_eq!(
+            *played,
+            vec![(Path::new(DEFAULT_SOUNDS_PATH).join("AddedToBackpack.ogg"), 0.5)]
+        );
+    }
+
+    #[test]
+    fn frequent_events_are_rate_limited() {
+        let (mut manager, sink) = manager_with_null_sink(AudioConfig::default());
+        let start = Instant::now();
+        assert!(manager.play_at(SoundEvent::TimeChanged, start));
+        assert!(!manager.play_at(SoundEvent::TimeChanged, start + Duration::from_millis(500)));
+        assert!(manager.play_at(SoundEvent::TimeChanged, start + Duration::from_millis(1000)));
+        drop(manager);
+        assert_eq!(sink.played.lock().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,206 @@
+use std::fs;
+
+use log::debug;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use robotics_lib::interface::{destroy, go, put, Direction};
+use robotics_lib::runner::Runnable;
+use robotics_lib::world::World;
+
+/// The scripted behaviors of `ExampleRobot`, used to exercise the visualizer
+/// deterministically.
+///
+/// - `Alternate`: Down and Right alternated, a diagonal walk.
+/// - `Serpentine`: Scans the map row by row, `width` steps right, one down, `width` steps left, one down.
+/// - `RandomWalk`: A random direction every tick, reproducible with the same seed.
+/// - `CollectAndDrop`: Destroys the contents around the robot while walking diagonally for
+///   `collect_ticks` ticks, then puts the backpack contents back around it, one per tick.
+/// - `Script`: Follows a list of moves, e.g. loaded with `Behavior::load_script`.
+#[derive(Clone, Debug, Default)]
+pub enum Behavior {
+    #[default]
+    Alternate,
+    Serpentine { width: usize },
+    RandomWalk { rng: StdRng },
+    CollectAndDrop { collect_ticks: usize },
+    Script { moves: Vec<Direction>, repeat: bool },
+}
+
+/// The names accepted by `Behavior::from_name`.
+pub const BEHAVIOR_NAMES: [&str; 5] = ["alternate", "serpentine", "random_walk", "collect_and_drop", "script"];
+
+const DIRECTIONS: [Direction; 4] = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+
+impl Behavior {
+    pub fn random_walk(seed: u64) -> Self {
+        Behavior::RandomWalk {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Reads a script: one direction per line (`up`, `down`, `left`, `right`), optionally
+    /// followed by how many times it is repeated, e.g. `right 5`. Empty lines and lines
+    /// starting with `#` are ignored.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the script.
+    /// * `repeat` - If the script starts again once the last move is done.
+    pub fn load_script(path: &str, repeat: bool) -> Result<Self, String> {
+        let script = fs::read_to_string(path).map_err(|e| format!("Error reading '{}': {}", path, e))?;
+        let moves = parse_script(&script).map_err(|e| format!("Error parsing '{}': {}", path, e))?;
+        Ok(Behavior::Script { moves, repeat })
+    }
+
+    /// Builds a behavior from its name (see `BEHAVIOR_NAMES`).
+    ///
+    /// # Arguments
+    /// * `name` - The name of the behavior.
+    /// * `map_size` - The size of the world, used by `serpentine`.
+    /// * `seed` - The seed of `random_walk`.
+    /// * `script_path` - The script of `script`.
+    pub fn from_name(name: &str, map_size: usize, seed: u64, script_path: Option<&str>) -> Result<Self, String> {
+        match name {
+            "alternate" => Ok(Behavior::Alternate),
+            "serpentine" => Ok(Behavior::Serpentine {
+                width: map_size.saturating_sub(1).max(1),
+            }),
+            "random_walk" => Ok(Behavior::random_walk(seed)),
+            "collect_and_drop" => Ok(Behavior::CollectAndDrop { collect_ticks: 300 }),
+            "script" => match script_path {
+                Some(path) => Behavior::load_script(path, true),
+                None => Err(String::from("the script behavior needs a script file")),
+            },
+            _ => Err(format!("unknown behavior '{}', expected one of {}", name, BEHAVIOR_NAMES.join(", "))),
+        }
+    }
+
+    /// Performs the action of the given tick.
+    ///
+    /// # Arguments
+    /// * `robot` - The robot acting.
+    /// * `world` - The world the robot is in.
+    /// * `tick` - The current tick, starting from 0.
+    pub fn act(&mut self, robot: &mut impl Runnable, world: &mut World, tick: usize) {
+        match self {
+            Behavior::Alternate => {
+                let _ = go(robot, world, alternate_direction(tick));
+            }
+            Behavior::Serpentine { width } => {
+                let _ = go(robot, world, serpentine_direction(tick, *width));
+            }
+            Behavior::RandomWalk { rng } => {
+                let direction = DIRECTIONS[rng.gen_range(0..DIRECTIONS.len())].clone();
+                let _ = go(robot, world, direction);
+            }
+            Behavior::CollectAndDrop { collect_ticks } => {
+                if tick < *collect_ticks {
+                    for direction in DIRECTIONS {
+                        let _ = destroy(robot, world, direction);
+                    }
+                    let _ = go(robot, world, alternate_direction(tick));
+                } else {
+                    drop_one_content(robot, world);
+                }
+            }
+            Behavior::Script { moves, repeat } => {
+                if moves.is_empty() || (!*repeat && tick >= moves.len()) {
+                    return;
+                }
+                let direction = moves[tick % moves.len()].clone();
+                let _ = go(robot, world, direction);
+            }
+        }
+    }
+}
+
+fn alternate_direction(tick: usize) -> Direction {
+    if tick % 2 == 0 {
+        Direction::Down
+    } else {
+        Direction::Right
+    }
+}
+
+fn serpentine_direction(tick: usize, width: usize) -> Direction {
+    let step = tick % (2 * width + 2);
+    if step < width {
+        Direction::Right
+    } else if step == width || step == 2 * width + 1 {
+        Direction::Down
+    } else {
+        Direction::Left
+    }
+}
+
+/// Puts one unit of the first content of the backpack on the first adjacent tile accepting it.
+fn drop_one_content(robot: &mut impl Runnable, world: &mut World) {
+    let content = robot
+        .get_backpack()
+        .get_contents()
+        .iter()
+        .find(|(_, quantity)| **quantity > 0)
+        .map(|(content, _)| content.clone());
+    let Some(content) = content else {
+        return;
+    };
+    for direction in DIRECTIONS {
+        if put(robot, world, content.clone(), 1, direction).is_ok() {
+            return;
+        }
+    }
+    debug!("no tile around the robot accepts {}", content);
+}
+
+fn parse_direction(word: &str) -> Option<Direction> {
+    match word.to_lowercase().as_str() {
+        "up" | "u" => Some(Direction::Up),
+        "down" | "d" => Some(Direction::Down),
+        "left" | "l" => Some(Direction::Left),
+        "right" | "r" => Some(Direction::Right),
+        _ => None,
+    }
+}
+
+/// Parses the moves of a script (see `Behavior::load_script`).
+pub fn parse_script(script: &str) -> Result<Vec<Direction>, String> {
+    let mut moves = Vec::new();
+    for (number, line) in script.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        let direction = words
+            .next()
+            .and_then(parse_direction)
+            .ok_or_else(|| format!("line {}: expected a direction, found '{}'", number + 1, line))?;
+        let count = match words.next() {
+            Some(count) => count
+                .parse::<usize>()
+                .map_err(|_| format!("line {}: '{}' is not a number", number + 1, count))?,
+            None => 1,
+        };
+        moves.extend(std::iter::repeat(direction).take(count));
+    }
+    Ok(moves)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripts_expand_repeated_moves() {
+        let moves = parse_script("# square\nright 2\n\ndown\nL 2\nup").unwrap();
+        let names: Vec<String> = moves.iter().map(|direction| format!("{:?}", direction)).collect();
+        assert_eq!(names, ["Right", "Right", "Down", "Left", "Left", "Up"]);
+        assert!(parse_script("jump").is_err());
+        assert!(parse_script("up two").is_err());
+    }
+
+    #[test]
+    fn serpentine_turns_at_the_end_of_each_row() {
+        let names: Vec<String> = (0..8).map(|tick| format!("{:?}", serpentine_direction(tick, 3))).collect();
+        assert_eq!(names, ["Right", "Right", "Right", "Down", "Left", "Left", "Left", "Down"]);
+    }
+}
@@ -0,0 +1,207 @@
+use crate::replay::{Replay, ReplayPlayer};
+use crate::state::ColorMatrix;
+
+///# Constants
+///* `ONLY_LEFT_COLOR`, `ONLY_RIGHT_COLOR`: The diff overlay color of the tiles explored by one run only.
+pub const ONLY_LEFT_COLOR: [f32; 4] = [0.2, 0.6, 1.0, 0.55];
+pub const ONLY_RIGHT_COLOR: [f32; 4] = [1.0, 0.5, 0.1, 0.55];
+
+/// How many tiles were explored by each of the two runs.
+///
+/// Attributes:
+/// - `only_left`, `only_right`: Tiles explored by a single run.
+/// - `both`: Tiles explored by both runs.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ExplorationDiff {
+    pub only_left: usize,
+    pub only_right: usize,
+    pub both: usize,
+}
+
+impl ExplorationDiff {
+    /// # Arguments
+    /// * `left`, `right` - The maps of the two runs, a tile is explored if it is not transparent.
+    pub fn new(left: &ColorMatrix, right: &ColorMatrix) -> Self {
+        let mut diff = Self::default();
+        for (left_row, right_row) in left.iter().zip(right) {
+            for (left_color, right_color) in left_row.iter().zip(right_row) {
+                match (left_color[3] > 0.0, right_color[3] > 0.0) {
+                    (true, true) => diff.both += 1,
+                    (true, false) => diff.only_left += 1,
+                    (false, true) => diff.only_right += 1,
+                    (false, false) => {}
+                }
+            }
+        }
+        diff
+    }
+
+    pub fn to_lines(&self) -> Vec<String> {
+        vec![
+            format!("only left: {}", self.only_left),
+            format!("only right: {}", self.only_right),
+            format!("both: {}", self.both),
+        ]
+    }
+}
+
+/// The overlay drawn over both halves of the split screen: the tiles explored by only
+/// one of the two runs, in the color of that run; the other tiles are transparent.
+pub fn diff_overlay(left: &ColorMatrix, right: &ColorMatrix) -> ColorMatrix {
+    left.iter()
+        .zip(right)
+        .map(|(left_row, right_row)| {
+            left_row
+                .iter()
+                .zip(right_row)
+                .map(|(left_color, right_color)| match (left_color[3] > 0.0, right_color[3] > 0.0) {
+                    (true, false) => ONLY_LEFT_COLOR,
+                    (false, true) => ONLY_RIGHT_COLOR,
+                    _ => [0.0; 4],
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// What the split screen shows of a run at the current tick.
+///
+/// Attributes:
+/// - `label`: The name of the run.
+/// - `tile_colors`: The map discovered by the robot.
+/// - `coordinates`: The position of the robot as (row, col).
+/// - `energy`, `score`: Energy and score of the robot.
+pub struct RunView<'a> {
+    pub label: &'a str,
+    pub tile_colors: &'a ColorMatrix,
+    pub coordinates: (usize, usize),
+    pub energy: usize,
+    pub score: f32,
+}
+
+impl RunView<'_> {
+    pub fn to_lines(&self) -> Vec<String> {
+        vec![
+            self.label.to_string(),
+            format!("energy: {}", self.energy),
+            format!("score: {}", self.score),
+            format!("robot at ({},{})", self.coordinates.1, self.coordinates.0),
+        ]
+    }
+}
+
+/// Two recorded runs played together, synchronized by tick.
+///
+/// Attributes:
+/// - `left`, `right`: The runs, with their label.
+/// - `tick`: The tick shown by both runs.
+pub struct ReplayComparison {
+    left: (String, ReplayPlayer),
+    right: (String, ReplayPlayer),
+    tick: usize,
+}
+
+impl ReplayComparison {
+    /// # Arguments
+    /// * `left`, `right` - The label and the replay of each run; the maps must have the same size.
+    pub fn new(left: (String, Replay), right: (String, Replay)) -> Result<Self, String> {
+        if left.1.map_size != right.1.map_size {
+            return Err(format!(
+                "the runs are on worlds of different size: {} and {}",
+                left.1.map_size, right.1.map_size
+            ));
+        }
+        let mut comparison = Self {
+            left: (left.0, ReplayPlayer::new(left.1)),
+            right: (right.0, ReplayPlayer::new(right.1)),
+            tick: 0,
+        };
+        // before the first step the maps are not initialized yet
+        let first_tick = comparison.left.1.first_tick().min(comparison.right.1.first_tick());
+        comparison.seek(first_tick);
+        Ok(comparison)
+    }
+
+    /// Loads two replays saved by the headless mode, labelled with their file name.
+    pub fn load(left_path: &str, right_path: &str) -> Result<Self, String> {
+        Self::new(
+            (left_path.to_string(), Replay::load(left_path)?),
+            (right_path.to_string(), Replay::load(right_path)?),
+        )
+    }
+
+    pub fn map_size(&self) -> usize {
+        self.left.1.tile_colors().len()
+    }
+
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    /// The last tick of the longest run.
+    pub fn last_tick(&self) -> usize {
+        self.left.1.last_tick().max(self.right.1.last_tick())
+    }
+
+    /// Moves both runs to the given tick, limited to the last tick; a run that ended
+    /// earlier stays on its last step.
+    pub fn seek(&mut self, tick: usize) {
+        self.tick = tick.min(self.last_tick());
+        self.left.1.seek(self.tick);
+        self.right.1.seek(self.tick);
+    }
+
+    pub fn left(&self) -> RunView<'_> {
+        Self::view(&self.left)
+    }
+
+    pub fn right(&self) -> RunView<'_> {
+        Self::view(&self.right)
+    }
+
+    fn view((label, player): &(String, ReplayPlayer)) -> RunView<'_> {
+        let step = player.current_step();
+        RunView {
+            label,
+            tile_colors: player.tile_colors(),
+            coordinates: step.map(|step| step.coordinates).unwrap_or((0, 0)),
+            energy: step.map(|step| step.energy).unwrap_or(0),
+            score: step.map(|step| step.score).unwrap_or(0.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_counts_and_colors_the_tiles_explored_by_one_run() {
+        let explored = [1.0, 1.0, 1.0, 1.0];
+        let hidden = [0.0; 4];
+        let left = vec![vec![explored, explored], vec![hidden, hidden]];
+        let right = vec![vec![explored, hidden], vec![explored, hidden]];
+
+        let diff = ExplorationDiff::new(&left, &right);
+        assert_eq!(
+            diff,
+            ExplorationDiff {
+                only_left: 1,
+                only_right: 1,
+                both: 1
+            }
+        );
+        let overlay = diff_overlay(&left, &right);
+        assert_eq!(overlay[0], vec![hidden, ONLY_LEFT_COLOR]);
+        assert_eq!(overlay[1], vec![ONLY_RIGHT_COLOR, hidden]);
+    }
+
+    #[test]
+    fn runs_of_different_size_cannot_be_compared() {
+        let replay = |map_size| Replay {
+            map_size,
+            steps: Vec::new(),
+        };
+        assert!(ReplayComparison::new((String::from("a"), replay(2)), (String::from("b"), replay(3))).is_err());
+    }
+}
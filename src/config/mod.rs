@@ -0,0 +1,209 @@
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::audio::AudioConfig;
+use crate::fog::FogConfig;
+use crate::grid::{MAP_SIZE, WINDOW_SIZE};
+use crate::registry::{DEFAULT_BEHAVIOR, DEFAULT_ROBOT};
+use crate::util::DEFAULT_HISTORY_PATH;
+use crate::worldgen::{ContentDensities, WorldPreset};
+
+///# Constants
+///* `DEFAULT_FONT_PATH`: The font used by the window.
+///* `DEFAULT_TICK_LIMIT`: The simulation stops after this amount of ticks.
+///* `DEFAULT_SEND_INTERVAL_MS`: How often the state is sent to the window.
+pub const DEFAULT_FONT_PATH: &str = "../font/font.otf";
+pub const DEFAULT_TICK_LIMIT: usize = 2000;
+pub const DEFAULT_SEND_INTERVAL_MS: u64 = 10;
+
+/// The colors of the window background and of the HUD texts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl Theme {
+    pub fn background(&self) -> [f32; 4] {
+        match self {
+            Theme::Dark => [0.0, 0.0, 0.0, 1.0],
+            Theme::Light => [0.93, 0.93, 0.9, 1.0],
+        }
+    }
+
+    pub fn text(&self) -> [f32; 4] {
+        match self {
+            Theme::Dark => [1.0; 4],
+            Theme::Light => [0.1, 0.1, 0.1, 1.0],
+        }
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "dark" => Ok(Theme::Dark),
+            "light" => Ok(Theme::Light),
+            _ => Err(format!("unknown theme '{}', expected 'dark' or 'light'", s)),
+        }
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Theme::Dark => write!(f, "dark"),
+            Theme::Light => write!(f, "light"),
+        }
+    }
+}
+
+/// Every setting of the visualizer binary. It can be read from a JSON file, where
+/// missing keys keep their default value, and each value can be overridden from the command line.
+///
+/// Attributes:
+/// - `robot`: The name of the robot, as registered in the `RobotRegistry`.
+/// - `behavior`: The scripted behavior of the `example` robot.
+/// - `robots`: Several robots run on copies of the same world, as `name` or `name:behavior`;
+///   when empty only `robot` runs.
+/// - `script_path`: The moves followed by the `script` behavior.
+/// - `world`: The kind of world generated.
+/// - `world_size`: The size of the generated world.
+/// - `seed`: The seed of the world generator, random if missing.
+/// - `densities`: The probabilities of the contents placed by the generator.
+/// - `world_file`: A world saved with `export_world`; when set, it is used instead of the generator.
+/// - `world_image`: An image painted with the tile palette; when set, it is used instead of the generator.
+/// - `content_image`: The contents painted over `world_image`.
+/// - `ticks`: The amount of ticks after which the simulation stops.
+/// - `tick_delay_ms`: Pause between two ticks, to slow the robot down.
+/// - `send_interval_ms`: How often the state is sent to the window.
+/// - `view_radius`: Tiles shown on each side of the robot in the robot view.
+/// - `record_frames`: If a frame is saved on every move.
+/// - `gif`: If the frames are converted to a gif at the end of the run (needs ffmpeg).
+/// - `trace_path`: The JSON-lines file the events are written to, if any.
+/// - `history_path`: The csv file the energy and score history is exported to.
+/// - `output_dir`: The directory of the headless outputs.
+/// - `sound`: If sounds are played.
+/// - `audio`: The sound files and volumes of the events.
+/// - `theme`: The colors of the window.
+/// - `fog`: How the undiscovered, out of view and recently discovered tiles are drawn.
+/// - `ground_truth`: If the real world is published to the window, to be drawn under the discovered map.
+/// - `window_width`, `window_height`: The size of the window.
+/// - `font_path`: The font used by the window.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VisualizerConfig {
+    pub robot: String,
+    pub behavior: String,
+    pub robots: Vec<String>,
+    pub script_path: Option<PathBuf>,
+    pub world: WorldPreset,
+    pub world_size: usize,
+    pub seed: Option<u64>,
+    pub densities: ContentDensities,
+    pub world_file: Option<PathBuf>,
+    pub world_image: Option<PathBuf>,
+    pub content_image: Option<PathBuf>,
+    pub ticks: usize,
+    pub tick_delay_ms: u64,
+    pub send_interval_ms: u64,
+    pub view_radius: usize,
+    pub record_frames: bool,
+    pub gif: bool,
+    pub trace_path: Option<PathBuf>,
+    pub history_path: PathBuf,
+    pub output_dir: PathBuf,
+    pub sound: bool,
+    pub audio: AudioConfig,
+    pub theme: Theme,
+    pub fog: FogConfig,
+    pub ground_truth: bool,
+    pub window_width: u32,
+    pub window_height: u32,
+    pub font_path: PathBuf,
+}
+
+impl Default for VisualizerConfig {
+    fn default() -> Self {
+        Self {
+            robot: DEFAULT_ROBOT.to_string(),
+            behavior: DEFAULT_BEHAVIOR.to_string(),
+            robots: Vec::new(),
+            script_path: None,
+            world: WorldPreset::default(),
+            world_size: MAP_SIZE,
+            seed: None,
+            densities: ContentDensities::default(),
+            world_file: None,
+            world_image: None,
+            content_image: None,
+            ticks: DEFAULT_TICK_LIMIT,
+            tick_delay_ms: 0,
+            send_interval_ms: DEFAULT_SEND_INTERVAL_MS,
+            view_radius: 1,
+            record_frames: true,
+            gif: true,
+            trace_path: None,
+            history_path: PathBuf::from(DEFAULT_HISTORY_PATH),
+            output_dir: PathBuf::from("."),
+            sound: false,
+            audio: AudioConfig::default(),
+            theme: Theme::default(),
+            fog: FogConfig::default(),
+            ground_truth: false,
+            window_width: WINDOW_SIZE.0 as u32,
+            window_height: WINDOW_SIZE.1 as u32,
+            font_path: PathBuf::from(DEFAULT_FONT_PATH),
+        }
+    }
+}
+
+impl VisualizerConfig {
+    /// Reads the configuration from a JSON file.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the JSON file.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let json = fs::read_to_string(path).map_err(|e| format!("Error reading '{}': {}", path, e))?;
+        serde_json::from_str(&json).map_err(|e| format!("Error parsing '{}': {}", path, e))
+    }
+
+    /// Writes the configuration as JSON, e.g. to create a starting config file.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the JSON file.
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| format!("Error writing '{}': {}", path, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_keys_keep_the_default_value() {
+        let config: VisualizerConfig = serde_json::from_str(r#"{ "ticks": 50, "theme": "light" }"#).unwrap();
+        assert_eq!(config.ticks, 50);
+        assert_eq!(config.theme, Theme::Light);
+        assert_eq!(config.world_size, MAP_SIZE);
+        assert_eq!(config.send_interval_ms, DEFAULT_SEND_INTERVAL_MS);
+        assert!(config.seed.is_none());
+    }
+
+    #[test]
+    fn theme_is_parsed_from_its_name() {
+        assert_eq!("Dark".parse::<Theme>(), Ok(Theme::Dark));
+        assert_eq!(Theme::Light.to_string().parse::<Theme>(), Ok(Theme::Light));
+        assert!("blue".parse::<Theme>().is_err());
+    }
+}
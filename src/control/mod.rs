@@ -0,0 +1,257 @@
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{unbounded, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use log::{error, info};
+
+///# Constants
+///* `SPEEDS`: The ticks per second selectable with `Faster`/`Slower`; `0.0` means as fast as possible.
+///* `PAUSE_POLL_INTERVAL`: How long the paused runner waits for a command before checking again.
+pub const SPEEDS: [f64; 9] = [0.5, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 0.0];
+pub const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The commands the window sends to the runner thread.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ControlCommand {
+    TogglePause,
+    /// Runs a single tick, then pauses.
+    Step,
+    Faster,
+    Slower,
+}
+
+/// The state of the simulation, shown in the HUD.
+///
+/// Attributes:
+/// - `paused`: If the runner is waiting for a `TogglePause` or a `Step`.
+/// - `ticks_per_second`: The target speed, `0.0` if the runner is not slowed down.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ControlStatus {
+    pub paused: bool,
+    pub ticks_per_second: f64,
+}
+
+impl ControlStatus {
+    /// Converts the delay between two ticks (the `tick_delay_ms` setting) to a status.
+    pub fn from_tick_delay(delay: Duration) -> Self {
+        Self {
+            paused: false,
+            ticks_per_second: if delay.is_zero() { 0.0 } else { 1.0 / delay.as_secs_f64() },
+        }
+    }
+
+    /// The minimum time between the start of two ticks.
+    pub fn tick_interval(&self) -> Duration {
+        if self.ticks_per_second > 0.0 {
+            Duration::from_secs_f64(1.0 / self.ticks_per_second)
+        } else {
+            Duration::ZERO
+        }
+    }
+
+    /// Text shown in the HUD, e.g. `PAUSED 5 t/s` or `max speed`.
+    pub fn to_text(&self) -> String {
+        let speed = if self.ticks_per_second > 0.0 {
+            format!("{} t/s", self.ticks_per_second)
+        } else {
+            String::from("max speed")
+        };
+        if self.paused {
+            format!("PAUSED {}", speed)
+        } else {
+            speed
+        }
+    }
+
+    /// Applies a command, returning how many ticks must be run while paused (1 for `Step`).
+    pub fn apply(&mut self, command: ControlCommand) -> usize {
+        match command {
+            ControlCommand::TogglePause => self.paused = !self.paused,
+            ControlCommand::Step => {
+                self.paused = true;
+                return 1;
+            }
+            ControlCommand::Faster => self.ticks_per_second = self.faster_speed(),
+            ControlCommand::Slower => self.ticks_per_second = self.slower_speed(),
+        }
+        0
+    }
+
+    // the current speed may not be in `SPEEDS` if it comes from the tick delay,
+    // so the closest one in the requested direction is used
+
+    fn faster_speed(&self) -> f64 {
+        if self.ticks_per_second == 0.0 {
+            return 0.0;
+        }
+        SPEEDS
+            .iter()
+            .copied()
+            .find(|speed| *speed > self.ticks_per_second)
+            .unwrap_or(0.0)
+    }
+
+    fn slower_speed(&self) -> f64 {
+        SPEEDS
+            .iter()
+            .copied()
+            .filter(|speed| *speed > 0.0)
+            .filter(|speed| self.ticks_per_second == 0.0 || *speed < self.ticks_per_second)
+            .last()
+            .unwrap_or(SPEEDS[0])
+    }
+}
+
+/// The window side of the control channel: sends the commands and reads the status.
+#[derive(Clone)]
+pub struct ControlHandle {
+    sender: Sender<ControlCommand>,
+    status: Arc<Mutex<ControlStatus>>,
+}
+
+impl ControlHandle {
+    pub fn send(&self, command: ControlCommand) {
+        if let Err(e) = self.sender.send(command) {
+            error!("Couldnt send {:?} to the runner: {}", command, e)
+        }
+    }
+
+    /// The status after the last command handled by the runner.
+    pub fn status(&self) -> ControlStatus {
+        match self.status.lock() {
+            Ok(status) => *status,
+            Err(e) => {
+                error!("Couldnt lock CONTROL_STATUS: {}", e);
+                ControlStatus::from_tick_delay(Duration::ZERO)
+            }
+        }
+    }
+}
+
+/// The runner side of the control channel: decides when the next tick can run.
+pub struct SimulationClock {
+    receiver: Receiver<ControlCommand>,
+    status: Arc<Mutex<ControlStatus>>,
+    current: ControlStatus,
+    pending_steps: usize,
+    last_tick: Option<Instant>,
+}
+
+impl SimulationClock {
+    /// Blocks until the next tick can run: while paused it waits for a command, otherwise
+    /// it waits for the tick interval of the current speed. If the window is gone the
+    /// simulation is resumed, since nobody can unpause it anymore.
+    pub fn wait_for_tick(&mut self) {
+        loop {
+            self.handle_commands();
+            if !self.current.paused {
+                break;
+            }
+            if self.pending_steps > 0 {
+                self.pending_steps -= 1;
+                break;
+            }
+            match self.receiver.recv_timeout(PAUSE_POLL_INTERVAL) {
+                Ok(command) => self.apply(command),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => self.resume_without_window(),
+            }
+        }
+
+        let interval = self.current.tick_interval();
+        if let Some(last_tick) = self.last_tick {
+            let elapsed = last_tick.elapsed();
+            if elapsed < interval {
+                thread::sleep(interval - elapsed);
+            }
+        }
+        self.last_tick = Some(Instant::now());
+    }
+
+    fn handle_commands(&mut self) {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(command) => self.apply(command),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.resume_without_window();
+                    break;
+                }
+            }
+        }
+    }
+
+    fn apply(&mut self, command: ControlCommand) {
+        self.pending_steps += self.current.apply(command);
+        self.publish();
+    }
+
+    fn resume_without_window(&mut self) {
+        if self.current.paused {
+            info!("the window is closed, resuming the simulation");
+            self.current.paused = false;
+            self.publish();
+        }
+    }
+
+    fn publish(&self) {
+        match self.status.lock() {
+            Ok(mut status) => *status = self.current,
+            Err(e) => error!("Couldnt lock CONTROL_STATUS: {}", e),
+        }
+    }
+}
+
+/// Creates the two ends of the control channel.
+///
+/// # Arguments
+/// * `initial` - The status the simulation starts with.
+pub fn control_channel(initial: ControlStatus) -> (ControlHandle, SimulationClock) {
+    let (sender, receiver) = unbounded();
+    let status = Arc::new(Mutex::new(initial));
+    (
+        ControlHandle {
+            sender,
+            status: status.clone(),
+        },
+        SimulationClock {
+            receiver,
+            status,
+            current: initial,
+            pending_steps: 0,
+            last_tick: None,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn step_pauses_and_runs_one_tick() {
+        let mut status = ControlStatus::from_tick_delay(Duration::ZERO);
+        assert_eq!(status.apply(ControlCommand::Step), 1);
+        assert!(status.paused);
+        assert_eq!(status.apply(ControlCommand::TogglePause), 0);
+        assert!(!status.paused);
+    }
+
+    #[test]
+    fn speed_moves_through_the_list() {
+        let mut status = ControlStatus::from_tick_delay(Duration::ZERO);
+        status.apply(ControlCommand::Faster);
+        assert_eq!(status.ticks_per_second, 0.0);
+        status.apply(ControlCommand::Slower);
+        assert_eq!(status.ticks_per_second, 100.0);
+
+        // 3 t/s from the tick delay is between 2 and 5
+        let mut status = ControlStatus::from_tick_delay(Duration::from_millis(333));
+        status.apply(ControlCommand::Slower);
+        assert_eq!(status.ticks_per_second, 2.0);
+        status.apply(ControlCommand::Faster);
+        status.apply(ControlCommand::Faster);
+        assert_eq!(status.ticks_per_second, 10.0);
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::{HashSet, VecDeque};
+
+use robotics_lib::event::events::Event;
+use serde::Serialize;
+
+///# Constants
+///* `DEFAULT_EVENT_LOG_CAPACITY`: How many entries the event log keeps before dropping the oldest ones.
+pub const DEFAULT_EVENT_LOG_CAPACITY: usize = 200;
+
+/// The categories the robot events are grouped in, used to filter the event log.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Moved,
+    AddedToBackpack,
+    RemovedFromBackpack,
+    TileContentUpdated,
+    Energy,
+    Time,
+    Lifecycle,
+}
+
+impl EventKind {
+    /// Every kind, in the order used by the filter keys (1 to 7) of the window.
+    pub const ALL: [EventKind; 7] = [
+        EventKind::Moved,
+        EventKind::AddedToBackpack,
+        EventKind::RemovedFromBackpack,
+        EventKind::TileContentUpdated,
+        EventKind::Energy,
+        EventKind::Time,
+        EventKind::Lifecycle,
+    ];
+
+    pub fn from_event(event: &Event) -> Self {
+        match event {
+            Event::Moved(_, _) => EventKind::Moved,
+            Event::AddedToBackpack(_, _) => EventKind::AddedToBackpack,
+            Event::RemovedFromBackpack(_, _) => EventKind::RemovedFromBackpack,
+            Event::TileContentUpdated(_, _) => EventKind::TileContentUpdated,
+            Event::EnergyConsumed(_) | Event::EnergyRecharged(_) => EventKind::Energy,
+            Event::TimeChanged(_) | Event::DayChanged(_) => EventKind::Time,
+            Event::Ready | Event::Terminated => EventKind::Lifecycle,
+        }
+    }
+
+    /// Short name shown in the filter bar of the event log panel.
+    pub fn label(&self) -> &'static str {
+        match self {
+            EventKind::Moved => "move",
+            EventKind::AddedToBackpack => "add",
+            EventKind::RemovedFromBackpack => "remove",
+            EventKind::TileContentUpdated => "tile",
+            EventKind::Energy => "energy",
+            EventKind::Time => "time",
+            EventKind::Lifecycle => "run",
+        }
+    }
+
+    /// Color used to draw the entries of this kind.
+    pub fn color(&self) -> [f32; 4] {
+        match self {
+            EventKind::Moved => [1.0, 1.0, 1.0, 1.0],
+            EventKind::AddedToBackpack => [0.4, 1.0, 0.4, 1.0],
+            EventKind::RemovedFromBackpack => [1.0, 0.5, 0.3, 1.0],
+            EventKind::TileContentUpdated => [0.6, 0.8, 1.0, 1.0],
+            EventKind::Energy => [1.0, 0.9, 0.3, 1.0],
+            EventKind::Time => [0.75, 0.6, 1.0, 1.0],
+            EventKind::Lifecycle => [0.7, 0.7, 0.7, 1.0],
+        }
+    }
+}
+
+/// Returns a short, human readable description of a robot event.
+///
+/// # Arguments
+/// * `event` - The event to describe.
+pub fn describe_event(event: &Event) -> String {
+    match event {
+        Event::Ready => "ready".to_string(),
+        Event::Terminated => "terminated".to_string(),
+        Event::TimeChanged(conditions) => format!("time {}", conditions.get_time_of_day_string()),
+        Event::DayChanged(conditions) => format!(
+            "new day {}, {:?}",
+            conditions.get_time_of_day_string(),
+            conditions.get_weather_condition()
+        ),
+        Event::EnergyRecharged(amount) => format!("energy +{}", amount),
+        Event::EnergyConsumed(amount) => format!("energy -{}", amount),
+        Event::Moved(tile, (row, col)) => format!("moved to ({},{}) {:?}", col, row, tile.tile_type),
+        Event::TileContentUpdated(tile, (row, col)) => format!("({},{}) now {}", col, row, tile.content),
+        Event::AddedToBackpack(content, amount) => format!("+{} {}", amount, content),
+        Event::RemovedFromBackpack(content, amount) => format!("-{} {}", amount, content),
+    }
+}
+
+/// A single entry of the event log.
+///
+/// Attributes:
+/// - `tick`: The tick in which the event happened.
+/// - `kind`: The category of the event.
+/// - `message`: The description of the event.
+#[derive(Clone, Debug)]
+pub struct LogEntry {
+    pub tick: usize,
+    pub kind: EventKind,
+    pub message: String,
+}
+
+impl LogEntry {
+    /// Text shown in the event log panel, e.g. `#42 moved to (3,4) Grass`.
+    pub fn to_text(&self) -> String {
+        format!("#{} {}", self.tick, self.message)
+    }
+}
+
+/// Bounded history of the events received by `handle_event`, newest last.
+#[derive(Clone, Debug)]
+pub struct EventLog {
+    entries: VecDeque<LogEntry>,
+    capacity: usize,
+}
+
+impl EventLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Records an event, dropping the oldest entry if the log is full.
+    ///
+    /// # Arguments
+    /// * `tick` - The tick in which the event happened.
+    /// * `event` - The event to record.
+    pub fn push(&mut self, tick: usize, event: &Event) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(LogEntry {
+            tick,
+            kind: EventKind::from_event(event),
+            message: describe_event(event),
+        });
+    }
+
+    pub fn entries(&self) -> &VecDeque<LogEntry> {
+        &self.entries
+    }
+
+    /// Returns the entries accepted by the filter, newest first.
+    pub fn filtered<'a>(&'a self, filter: &'a EventFilter) -> impl Iterator<Item = &'a LogEntry> + 'a {
+        self.entries.iter().rev().filter(move |entry| filter.is_visible(entry.kind))
+    }
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_LOG_CAPACITY)
+    }
+}
+
+/// The set of event kinds hidden from the event log panel.
+#[derive(Clone, Debug, Default)]
+pub struct EventFilter {
+    hidden: HashSet<EventKind>,
+}
+
+impl EventFilter {
+    /// Shows the kind if it was hidden, hides it otherwise.
+    pub fn toggle(&mut self, kind: EventKind) {
+        if !self.hidden.remove(&kind) {
+            self.hidden.insert(kind);
+        }
+    }
+
+    pub fn is_visible(&self, kind: EventKind) -> bool {
+        !self.hidden.contains(&kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use robotics_lib::world::environmental_conditions::{EnvironmentalConditions, WeatherType};
+
+    use super::*;
+
+    fn ticks<'a>(entries: impl Iterator<Item = &'a LogEntry>) -> Vec<usize> {
+        entries.map(|entry| entry.tick).collect()
+    }
+
+    #[test]
+    fn full_log_drops_the_oldest_entries() {
+        let mut log = EventLog::new(3);
+        for tick in 0..5 {
+            log.push(tick, &Event::EnergyConsumed(tick));
+        }
+        assert_eq!(ticks(log.entries().iter()), vec![2, 3, 4]);
+        assert_eq!(log.entries()[0].message, "energy -2");
+
+        let mut disabled = EventLog::new(0);
+        disabled.push(0, &Event::Ready);
+        assert!(disabled.entries().is_empty());
+    }
+
+    #[test]
+    fn filtered_entries_are_newest_first_and_follow_the_toggles() {
+        let mut log = EventLog::default();
+        log.push(0, &Event::Ready);
+        log.push(1, &Event::EnergyConsumed(5));
+        log.push(2, &Event::EnergyRecharged(3));
+        log.push(3, &Event::Terminated);
+
+        let mut filter = EventFilter::default();
+        assert_eq!(ticks(log.filtered(&filter)), vec![3, 2, 1, 0]);
+
+        filter.toggle(EventKind::Energy);
+        assert!(!filter.is_visible(EventKind::Energy));
+        assert_eq!(ticks(log.filtered(&filter)), vec![3, 0]);
+
+        filter.toggle(EventKind::Energy);
+        filter.toggle(EventKind::Lifecycle);
+        assert!(filter.is_visible(EventKind::Energy));
+        assert_eq!(ticks(log.filtered(&filter)), vec![2, 1]);
+    }
+
+    #[test]
+    fn new_day_shows_the_time_and_the_weather() {
+        let conditions = EnvironmentalConditions::new(&[WeatherType::Sunny], 15, 12).unwrap();
+        let time = conditions.get_time_of_day_string();
+        assert_eq!(describe_event(&Event::DayChanged(conditions)), format!("new day {}, Sunny", time));
+    }
+}
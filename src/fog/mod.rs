@@ -0,0 +1,247 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::state::ColorMatrix;
+
+///# Constants
+///* `UNKNOWN_GRAY`: The color of the undiscovered tiles with `UnknownStyle::Gray`.
+///* `UNKNOWN_HATCH_BASE`, `UNKNOWN_HATCH_LINE`: The colors of the undiscovered tiles with `UnknownStyle::Hatched`.
+///* `OUT_OF_VIEW_DIM`: Drawn over the discovered tiles that no robot is seeing.
+///* `RECENT_HIGHLIGHT`: Drawn over the tiles discovered in the last ticks, fading with the age of the tile.
+///* `DEFAULT_HIGHLIGHT_TICKS`: For how many ticks a discovered tile stays highlighted.
+pub const UNKNOWN_GRAY: [f32; 4] = [0.3, 0.3, 0.32, 1.0];
+pub const UNKNOWN_HATCH_BASE: [f32; 4] = [0.14, 0.14, 0.16, 1.0];
+pub const UNKNOWN_HATCH_LINE: [f32; 4] = [0.35, 0.35, 0.38, 1.0];
+pub const OUT_OF_VIEW_DIM: [f32; 4] = [0.0, 0.0, 0.0, 0.45];
+pub const RECENT_HIGHLIGHT: [f32; 4] = [1.0, 1.0, 0.4, 0.6];
+pub const DEFAULT_HIGHLIGHT_TICKS: usize = 20;
+
+/// How the tiles the robot hasn't discovered yet are drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownStyle {
+    /// Not drawn, the background shows through (the same as a `Street` on the dark theme).
+    Hidden,
+    Gray,
+    #[default]
+    Hatched,
+}
+
+impl UnknownStyle {
+    /// The style used after this one, used by the key cycling the styles.
+    pub fn next(&self) -> Self {
+        match self {
+            UnknownStyle::Hidden => UnknownStyle::Gray,
+            UnknownStyle::Gray => UnknownStyle::Hatched,
+            UnknownStyle::Hatched => UnknownStyle::Hidden,
+        }
+    }
+}
+
+impl FromStr for UnknownStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "hidden" => Ok(UnknownStyle::Hidden),
+            "gray" => Ok(UnknownStyle::Gray),
+            "hatched" => Ok(UnknownStyle::Hatched),
+            _ => Err(format!("unknown fog style '{}', expected 'hidden', 'gray' or 'hatched'", s)),
+        }
+    }
+}
+
+impl fmt::Display for UnknownStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnknownStyle::Hidden => write!(f, "hidden"),
+            UnknownStyle::Gray => write!(f, "gray"),
+            UnknownStyle::Hatched => write!(f, "hatched"),
+        }
+    }
+}
+
+/// The fog of war drawn over the discovered map.
+///
+/// Attributes:
+/// - `unknown`: How the undiscovered tiles are drawn.
+/// - `dim_out_of_view`: If the discovered tiles outside the view of every robot are darkened.
+/// - `highlight_recent`: If the tiles discovered in the last `highlight_ticks` ticks are highlighted.
+/// - `highlight_ticks`: For how many ticks a discovered tile stays highlighted.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FogConfig {
+    pub unknown: UnknownStyle,
+    pub dim_out_of_view: bool,
+    pub highlight_recent: bool,
+    pub highlight_ticks: usize,
+}
+
+impl Default for FogConfig {
+    fn default() -> Self {
+        Self {
+            unknown: UnknownStyle::default(),
+            dim_out_of_view: false,
+            highlight_recent: true,
+            highlight_ticks: DEFAULT_HIGHLIGHT_TICKS,
+        }
+    }
+}
+
+/// Remembers the tick each tile was discovered at, by comparing the maps received by the window.
+///
+/// Attributes:
+/// - `discovered_at`: The tick of the discovery of each tile, indexed `[row][col]`; `None` if undiscovered.
+#[derive(Clone, Debug, Default)]
+pub struct DiscoveryTracker {
+    discovered_at: Vec<Vec<Option<usize>>>,
+}
+
+impl DiscoveryTracker {
+    pub fn new(map_size: usize) -> Self {
+        Self {
+            discovered_at: vec![vec![None; map_size]; map_size],
+        }
+    }
+
+    /// Marks the tiles of the map that are discovered for the first time.
+    ///
+    /// # Arguments
+    /// * `tile_colors` - The discovered map, a tile is discovered if it is not transparent.
+    /// * `tick` - The tick of the map.
+    pub fn update(&mut self, tile_colors: &ColorMatrix, tick: usize) {
+        for (tracked_row, row) in self.discovered_at.iter_mut().zip(tile_colors) {
+            for (discovered_at, color) in tracked_row.iter_mut().zip(row) {
+                if discovered_at.is_none() && color[3] > 0.0 {
+                    *discovered_at = Some(tick);
+                }
+            }
+        }
+    }
+
+    /// The tick the tile was discovered at, if it was.
+    pub fn discovered_at(&self, row: usize, col: usize) -> Option<usize> {
+        self.discovered_at.get(row).and_then(|r| r.get(col)).copied().flatten()
+    }
+}
+
+/// The area a robot is seeing: its position as (row, col) and the tiles on each side of it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewArea {
+    pub center: (usize, usize),
+    pub radius: usize,
+}
+
+impl ViewArea {
+    /// # Arguments
+    /// * `center` - The position of the robot as (row, col).
+    /// * `view_side` - The side of the robot view, i.e. `2 * radius + 1`.
+    pub fn new(center: (usize, usize), view_side: usize) -> Self {
+        Self {
+            center,
+            radius: view_side / 2,
+        }
+    }
+
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        row.abs_diff(self.center.0) <= self.radius && col.abs_diff(self.center.1) <= self.radius
+    }
+}
+
+/// Builds the layer drawn over the discovered map: the undiscovered tiles in the color of
+/// their style, the recently discovered tiles highlighted and, if enabled, the tiles out of
+/// view darkened. The other tiles are transparent.
+///
+/// # Arguments
+/// * `tile_colors` - The discovered map.
+/// * `tracker` - When each tile was discovered.
+/// * `tick` - The current tick.
+/// * `views` - What each robot is seeing.
+/// * `config` - The fog settings.
+pub fn fog_overlay(
+    tile_colors: &ColorMatrix,
+    tracker: &DiscoveryTracker,
+    tick: usize,
+    views: &[ViewArea],
+    config: &FogConfig,
+) -> ColorMatrix {
+    let unknown_color = match config.unknown {
+        UnknownStyle::Hidden => [0.0; 4],
+        UnknownStyle::Gray => UNKNOWN_GRAY,
+        UnknownStyle::Hatched => UNKNOWN_HATCH_BASE,
+    };
+    tile_colors
+        .iter()
+        .enumerate()
+        .map(|(row, colors)| {
+            colors
+                .iter()
+                .enumerate()
+                .map(|(col, color)| {
+                    if color[3] == 0.0 {
+                        return unknown_color;
+                    }
+                    if config.highlight_recent && config.highlight_ticks > 0 {
+                        if let Some(age) = tracker.discovered_at(row, col).map(|at| tick.saturating_sub(at)) {
+                            if age < config.highlight_ticks {
+                                let fade = 1.0 - age as f32 / config.highlight_ticks as f32;
+                                let mut highlight = RECENT_HIGHLIGHT;
+                                highlight[3] *= fade;
+                                return highlight;
+                            }
+                        }
+                    }
+                    if config.dim_out_of_view && !views.iter().any(|view| view.contains(row, col)) {
+                        return OUT_OF_VIEW_DIM;
+                    }
+                    [0.0; 4]
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FOUND: [f32; 4] = [0.5, 0.5, 0.5, 1.0];
+    const HIDDEN: [f32; 4] = [0.0; 4];
+
+    #[test]
+    fn tiles_keep_the_tick_of_their_first_discovery() {
+        let mut tracker = DiscoveryTracker::new(2);
+        tracker.update(&vec![vec![FOUND, HIDDEN], vec![HIDDEN, HIDDEN]], 3);
+        tracker.update(&vec![vec![FOUND, FOUND], vec![HIDDEN, HIDDEN]], 7);
+        assert_eq!(tracker.discovered_at(0, 0), Some(3));
+        assert_eq!(tracker.discovered_at(0, 1), Some(7));
+        assert_eq!(tracker.discovered_at(1, 0), None);
+    }
+
+    #[test]
+    fn overlay_marks_unknown_recent_and_out_of_view_tiles() {
+        let tile_colors = vec![vec![FOUND, FOUND, FOUND], vec![HIDDEN, HIDDEN, HIDDEN]];
+        let mut tracker = DiscoveryTracker::new(3);
+        tracker.update(&vec![vec![FOUND, HIDDEN, HIDDEN]], 0);
+        tracker.update(&tile_colors, 5);
+        let config = FogConfig {
+            unknown: UnknownStyle::Gray,
+            dim_out_of_view: true,
+            highlight_recent: true,
+            highlight_ticks: 10,
+        };
+        let views = [ViewArea::new((0, 0), 1)];
+
+        let overlay = fog_overlay(&tile_colors, &tracker, 10, &views, &config);
+        assert_eq!(overlay[1], vec![UNKNOWN_GRAY; 3]);
+        // discovered long ago but in view
+        assert_eq!(overlay[0][0], HIDDEN);
+        // discovered 5 ticks ago, half faded
+        assert_eq!(overlay[0][1][3], RECENT_HIGHLIGHT[3] * 0.5);
+
+        let overlay = fog_overlay(&tile_colors, &tracker, 20, &views, &config);
+        assert_eq!(overlay[0][2], OUT_OF_VIEW_DIM);
+    }
+}
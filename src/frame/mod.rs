@@ -1,11 +1,14 @@
 use image::{DynamicImage, ImageError, Rgba};
+use std::fs;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::thread;
 use std::thread::sleep;
 use std::time::Duration;
 
-use crate::util::{gif_creator, id_to_path_string, match_color_to_type};
+use log::{debug, error, info, warn};
+
+use crate::util::{apply_environment_tint, DEFAULT_PNGS_PATH, DEFAULT_VIDEO_PATH, EnvironmentInfo, gif_creator, id_to_path_string, match_color_to_type};
 use robotics_lib::world::tile::{Tile};
 
 const MAX_FAILURE_TOLERANCE: usize = 5;
@@ -15,11 +18,17 @@ const N_OF_BYTES_PER_PIXEL: usize = 4;
 ///received_frames: it counts the amount of requests to process a Frame that have been received.
 /// processed_frames: it counts the amount of frames that are successfully saved.
 /// fails: Count the amount of frames that could not be saved due to errors
+/// environment_log: the environmental conditions of each received frame, exported next to the pngs.
+/// directory: where the pngs are saved, `DEFAULT_PNGS_PATH` unless changed with `set_output`.
+/// output_video: the video made from the pngs, `DEFAULT_VIDEO_PATH` unless changed with `set_output`.
 /// the type Arc<AtomicUsize> is due to the interaction of multiple threads with the parameters of the struct.
 pub struct Frames {
     received_frames: usize,
     saved_frames: Arc<AtomicUsize>,
     fails: Arc<AtomicUsize>,
+    environment_log: Vec<String>,
+    directory: String,
+    output_video: String,
 }
 
 impl Frames {
@@ -29,25 +38,49 @@ impl Frames {
             received_frames: 0,
             saved_frames: Arc::new(AtomicUsize::new(0)),
             fails: Arc::new(AtomicUsize::new(0)),
+            environment_log: Vec::new(),
+            directory: DEFAULT_PNGS_PATH.to_string(),
+            output_video: DEFAULT_VIDEO_PATH.to_string(),
         }
     }
-    pub fn add_frame(&mut self, robot_map: &Option<Vec<Vec<Option<Tile>>>>) {
+
+    /// Sets where the pngs and the video are written, to be called before the first frame.
+    pub fn set_output(&mut self, directory: &str, output_video: &str) {
+        self.directory = directory.to_string();
+        self.output_video = output_video.to_string();
+    }
+
+    pub fn directory(&self) -> &str {
+        &self.directory
+    }
+
+    /// Takes the frames received so far, leaving no frames with the same output.
+    pub fn take(&mut self) -> Frames {
+        let mut empty = Frames::new();
+        empty.set_output(&self.directory, &self.output_video);
+        std::mem::replace(self, empty)
+    }
+    pub fn add_frame(&mut self, robot_map: &Option<Vec<Vec<Option<Tile>>>>, environment: &EnvironmentInfo) {
         if robot_map.is_some() {
-            println!(
-                "VALUES: {} {}",
+            debug!(
+                "received frames: {}, saved frames: {}",
                 self.received_frames,
                 self.saved_frames.load(Ordering::SeqCst)
             );
-            let frame = Frame::new_from_robot_map(robot_map, self.received_frames);
+            let frame = Frame::new_from_robot_map(robot_map, self.received_frames, environment);
+            self.environment_log.push(format!(
+                "{},{},{},{:?}",
+                self.received_frames, environment.day, environment.time, environment.weather
+            ));
             self.received_frames += 1;
 
             let arc_processed_frames = self.saved_frames.clone();
             let arc_fails = self.fails.clone();
+            let directory = self.directory.clone();
 
             thread::spawn(move || {
-                match frame.save_frame() {
+                match frame.save_frame(&directory) {
                     Ok(_) => {
-                        println!("frame {} saved", frame.id);
                         //Ordering::Relaxed is enough for my aim -> Ordering::SeqCst has too much constrains that i dont need in this case.
                         //I just need to ensure that all the threads increments the counter but i dont care about the increasing order.
                         arc_processed_frames.fetch_add(1, Ordering::Relaxed);
@@ -68,14 +101,13 @@ impl Frames {
                                 arc_fails.fetch_add(1, Ordering::Relaxed);
                             }
                             //in the other case it make sense to re-try (just 1 time but it can be decided)
-                            _ => match frame.save_frame() {
+                            _ => match frame.save_frame(&directory) {
                                 Ok(_) => {
-                                    println!("frame {} saved", frame.id);
                                     arc_processed_frames.fetch_add(1, Ordering::Relaxed);
                                 }
                                 Err(_) => {
-                                    eprintln!("frame {} cant be saved", frame.id);
-                                    arc_fails.fetch_sub(1, Ordering::Relaxed);
+                                    error!("frame {} cant be saved", frame.id);
+                                    arc_fails.fetch_add(1, Ordering::Relaxed);
                                 }
                             },
                         }
@@ -85,28 +117,55 @@ impl Frames {
         }
     }
 
+    /// Waits for the frames being saved, then converts them to a video. Gives up if no frame
+    /// was saved or failed for `MAX_WAITING_CYCLES` polls, or if too many frames failed.
     pub fn convert_frames_to_gif(&self) -> Result<(), String> {
         let mut waiting_counter: usize = 0;
         let received_frames = self.received_frames;
-        let mut atomic_processed_frames = self.saved_frames.clone().load(Ordering::SeqCst);
         let polling_interval = Duration::from_millis(100);
-        let atomic_fails = self.fails.clone().load(Ordering::SeqCst);
+        let mut done_frames = 0;
 
-        while received_frames != atomic_processed_frames {
-            atomic_processed_frames = self.saved_frames.clone().load(Ordering::SeqCst);
-            println!(
+        loop {
+            let atomic_processed_frames = self.saved_frames.load(Ordering::SeqCst);
+            let atomic_fails = self.fails.load(Ordering::SeqCst);
+            if atomic_fails >= MAX_FAILURE_TOLERANCE {
+                return Err(String::from("too many failure"));
+            }
+            //a failed frame is not waited for
+            if atomic_processed_frames + atomic_fails >= received_frames {
+                break;
+            }
+            if atomic_processed_frames + atomic_fails == done_frames {
+                waiting_counter += 1;
+                if waiting_counter == MAX_WAITING_CYCLES {
+                    return Err(format!(
+                        "timed out waiting for the frames. Received={} saved={} failed={}",
+                        received_frames, atomic_processed_frames, atomic_fails
+                    ));
+                }
+            } else {
+                done_frames = atomic_processed_frames + atomic_fails;
+                waiting_counter = 0;
+            }
+            info!(
                 "from_frames_to_gif method is waiting. Received={} saved={}",
                 received_frames, atomic_processed_frames
             );
             sleep(polling_interval);
-            waiting_counter += 1;
-            if waiting_counter == MAX_WAITING_CYCLES {
-                if atomic_fails >= MAX_FAILURE_TOLERANCE {
-                    return Err(String::from("too many failure"));
-                }
-            }
         }
-        gif_creator()
+        self.save_environment_log()?;
+        gif_creator(&self.directory, &self.output_video)
+    }
+
+    /// Writes the environmental conditions of every frame as a csv file (`frame,day,time,weather`)
+    /// in the PNG directory, so that the exported video can be matched with the weather and time.
+    fn save_environment_log(&self) -> Result<(), String> {
+        let mut content = String::from("frame,day,time,weather\n");
+        for line in &self.environment_log {
+            content += line;
+            content += "\n";
+        }
+        fs::write(format!("{}/environment.csv", self.directory), content).map_err(|e| e.to_string())
     }
 }
 
@@ -126,18 +185,21 @@ impl Frame {
     /// Arguments:
     /// - `robot_map`: The robot's current discovered map to be visualized.
     /// - `id`: The unique identifier for the frame.
-    pub fn new_from_robot_map(robot_map: &Option<Vec<Vec<Option<Tile>>>>, id: usize) -> Self {
+    /// - `environment`: The environmental conditions used to tint the frame (night, rain).
+    pub fn new_from_robot_map(robot_map: &Option<Vec<Vec<Option<Tile>>>>, id: usize, environment: &EnvironmentInfo) -> Self {
         Self {
-            image: Self::robot_map_to_dynamic_image(robot_map),
+            image: Self::robot_map_to_dynamic_image(robot_map, environment),
             id,
         }
     }
 
-    /// Creates a `DynamicImage` from a given robot map
-    fn robot_map_to_dynamic_image(map: &Option<Vec<Vec<Option<Tile>>>>) -> DynamicImage {
-        let dim = map.clone().unwrap_or(vec![]).len();
+    /// Creates a `DynamicImage` from a given robot map, tinted according to the environment
+    fn robot_map_to_dynamic_image(map: &Option<Vec<Vec<Option<Tile>>>>, environment: &EnvironmentInfo) -> DynamicImage {
+        //the map is indexed [row][col]: rows are the height of the image, columns its width
+        let height = map.as_ref().map(|rows| rows.len()).unwrap_or(0);
+        let width = map.as_ref().and_then(|rows| rows.first()).map(|row| row.len()).unwrap_or(0);
 
-        let mut pixel_data: Vec<u8> = Vec::with_capacity(dim * dim * N_OF_BYTES_PER_PIXEL);
+        let mut pixel_data: Vec<u8> = Vec::with_capacity(width * height * N_OF_BYTES_PER_PIXEL);
         //total number of pixels in the image, 4 bytes per pixel (R,G,B and A=transparency)
 
         let mut color_rgba: (u8, u8, u8, u8);
@@ -146,7 +208,7 @@ impl Frame {
                 for _tile in tile {
                     match &_tile.clone() {
                         Some(_) => {
-                            color_rgba = match_color_to_type(&_tile.clone().unwrap().tile_type)
+                            color_rgba = tint_rgba(match_color_to_type(&_tile.clone().unwrap().tile_type), environment)
                         }
                         None => color_rgba = (0, 0, 0, 0), //transparent
                     }
@@ -159,30 +221,90 @@ impl Frame {
         }
 
         let image_buffer =
-            image::ImageBuffer::<Rgba<u8>, _>::from_vec(dim as u32, dim as u32, pixel_data)
+            image::ImageBuffer::<Rgba<u8>, _>::from_vec(width as u32, height as u32, pixel_data)
                 .expect("Failed to create ImageBuffer");
         DynamicImage::ImageRgba8(image_buffer)
     }
 
     /// Saves the frame to disk.
     ///
-    /// This method attempts to save the frame's image to a predetermined location, using the frame's
+    /// This method attempts to save the frame's image in the given directory, using the frame's
     /// ID to generate a unique file name.
     ///
     /// Returns:
     /// - `Ok(())` if the image is successfully saved.
     /// - `Err(ImageError)` containing details of any error encountered during saving.
-    pub fn save_frame(&self) -> Result<(), ImageError> {
-        match self.image.save(id_to_path_string(self.id)) {
+    pub fn save_frame(&self, directory: &str) -> Result<(), ImageError> {
+        match self.image.save(id_to_path_string(directory, self.id)) {
             Ok(_) => {
-                println!("Frame n.{} saved", self.id);
+                debug!("Frame n.{} saved", self.id);
                 Ok(())
             }
             Err(e) => {
-                eprintln!("Failed to save PNG image: {}", e);
+                warn!("Failed to save PNG image: {}", e);
                 Err(e)
             }
         }
     }
 }
 
+/// Applies `apply_environment_tint` to a 0-255 RGBA color.
+fn tint_rgba(color: (u8, u8, u8, u8), environment: &EnvironmentInfo) -> (u8, u8, u8, u8) {
+    let tinted = apply_environment_tint(
+        [
+            color.0 as f32 / 255.0,
+            color.1 as f32 / 255.0,
+            color.2 as f32 / 255.0,
+            color.3 as f32 / 255.0,
+        ],
+        environment,
+    );
+    (
+        (tinted[0] * 255.0).round() as u8,
+        (tinted[1] * 255.0).round() as u8,
+        (tinted[2] * 255.0).round() as u8,
+        color.3,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use robotics_lib::world::tile::{Content, TileType};
+
+    fn tile(tile_type: TileType) -> Option<Tile> {
+        Some(Tile {
+            tile_type,
+            content: Content::None,
+            elevation: 0,
+        })
+    }
+
+    #[test]
+    fn robot_map_to_dynamic_image_uses_rows_as_height() {
+        // 2 rows x 3 columns
+        let map = Some(vec![
+            vec![tile(TileType::Grass), tile(TileType::Sand), None],
+            vec![tile(TileType::Lava), tile(TileType::Snow), tile(TileType::Street)],
+        ]);
+        let image = Frame::robot_map_to_dynamic_image(&map, &EnvironmentInfo::default()).to_rgba8();
+
+        assert_eq!(image.dimensions(), (3, 2));
+        assert_eq!(image.get_pixel(1, 0).0, [255, 255, 0, 255]);
+        assert_eq!(image.get_pixel(2, 0).0, [0, 0, 0, 0]);
+        assert_eq!(image.get_pixel(0, 1).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(2, 1).0, [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn taken_frames_keep_the_output() {
+        let mut frames = Frames::new();
+        frames.set_output("out/frames", "out/run.mp4");
+        let taken = frames.take();
+        assert_eq!(taken.directory(), "out/frames");
+        assert_eq!(frames.directory(), "out/frames");
+        assert_eq!(id_to_path_string(frames.directory(), 42), "out/frames/00042.png");
+        // nothing to wait for
+        assert_eq!(frames.received_frames, 0);
+    }
+}
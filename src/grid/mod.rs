@@ -1,14 +1,25 @@
 extern crate piston_window;
 
+use std::time::Instant;
+
 use piston_window::*;
 use piston_window::{Context, G2d, rectangle};
 use piston_window::types::{Color};
 
+use crate::compare::RunView;
+use crate::events::{EventFilter, EventKind, EventLog};
+use crate::metrics::MetricsSample;
+use crate::multi::RobotSlots;
+use crate::teleport::{JumpAnimation, TeleportLinks, ACTIVE_TELEPORT_COLOR, INACTIVE_TELEPORT_COLOR, TELEPORT_LINK_COLOR};
+use crate::util::{BackpackInfo, EnvironmentInfo, match_content_color_to_type_piston, RAIN_TINT, same_content_kind};
+
 type ColorMatrix = Vec<Vec<[f32; 4]>>;
 
 pub const MAP_SIZE: usize = 700;
 pub const GRID_SIZE: (usize, usize) = (MAP_SIZE, MAP_SIZE);
-pub const RECT_SIZE: f64 = 750.0 / MAP_SIZE as f64;
+//side, in pixels, of the area the map is drawn in
+pub const GRID_PIXELS: f64 = 750.0;
+pub const RECT_SIZE: f64 = GRID_PIXELS / MAP_SIZE as f64;
 pub const WINDOW_SIZE: (usize, usize) = (
     950,
     950,
@@ -19,6 +30,78 @@ pub const SCROLL_AMOUNT: f64 = 5.0;
 
 pub const ROBOT_COLOR: [f32; 4] = [191.0 / 255.0, 139.0 / 255.0, 255.0 / 255.0, 1.0];
 
+//maximum energy level of a robot in robotics_lib
+pub const DEFAULT_MAX_ENERGY: usize = 1000;
+
+//for how many ticks the last added/removed content stays highlighted
+pub const BACKPACK_HIGHLIGHT_TICKS: usize = 10;
+//space left to the HUD on the right of the grid and below it
+pub const HUD_MARGIN: f64 = 200.0;
+pub const MIN_GRID_PIXELS: f64 = 200.0;
+pub const ROBOT_CARD_SIZE: [f64; 2] = [146.0, 46.0];
+pub const EVENT_LOG_FONT_SIZE: u32 = 11;
+pub const EVENT_LOG_LINE_HEIGHT: f64 = 14.0;
+
+/// Where the grid and the HUD panels are drawn, derived from the size of the window: the
+/// grid is a square leaving `HUD_MARGIN` pixels on its right and below it, the status and
+/// the panels are in the column on the right, the robot view, the charts and the robot
+/// cards in the strip below. With the default 950x950 window the grid is 750 pixels wide.
+///
+/// Attributes:
+/// - `window_size`: The size of the window, the tiles outside it are not drawn.
+/// - `grid_pixels`: The side of the area the map is drawn in.
+/// - `status`: The top left corner of the score, energy, environment and ticks rows.
+/// - `coordinates_text`: Where the coordinates of the robot are written.
+/// - the other attributes: The `[x, y, width, height]` of each panel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HudLayout {
+    pub window_size: [f64; 2],
+    pub grid_pixels: f64,
+    pub status: [f64; 2],
+    pub coordinates_text: [u32; 2],
+    pub backpack: [f64; 4],
+    pub metrics: [f64; 4],
+    pub event_log: [f64; 4],
+    pub comparison: [f64; 4],
+    pub robot_view: [f64; 4],
+    pub energy_chart: [f64; 4],
+    pub score_chart: [f64; 4],
+    pub robot_cards: [f64; 4],
+    pub summary: [f64; 4],
+}
+
+impl HudLayout {
+    /// # Arguments
+    /// * `width`, `height` - The size of the window.
+    pub fn new(width: f64, height: f64) -> Self {
+        let grid_pixels = (width - HUD_MARGIN).min(height - HUD_MARGIN).max(MIN_GRID_PIXELS);
+        let side_x = grid_pixels + 10.0;
+        let side_width = (width - side_x - 5.0).max(0.0);
+        let strip_y = grid_pixels + 15.0;
+        Self {
+            window_size: [width, height],
+            grid_pixels,
+            status: [side_x + 10.0, 20.0],
+            coordinates_text: [50, (strip_y + 20.0) as u32],
+            backpack: [side_x, 150.0, side_width, 215.0],
+            metrics: [side_x, 375.0, side_width, 110.0],
+            event_log: [side_x, 495.0, side_width, (strip_y - 25.0 - 495.0).max(0.0)],
+            comparison: [side_x, 20.0, side_width, 400.0],
+            robot_view: [width - 450.0, strip_y, 180.0, 180.0],
+            energy_chart: [width - 250.0, strip_y + 5.0, 240.0, 75.0],
+            score_chart: [width - 250.0, strip_y + 95.0, 240.0, 75.0],
+            robot_cards: [50.0, strip_y + 35.0, (width - 510.0).max(0.0), (height - strip_y - 45.0).max(0.0)],
+            summary: [grid_pixels / 2.0 - 225.0, grid_pixels / 2.0 - 95.0, 450.0, 170.0],
+        }
+    }
+}
+
+impl Default for HudLayout {
+    fn default() -> Self {
+        Self::new(WINDOW_SIZE.0 as f64, WINDOW_SIZE.1 as f64)
+    }
+}
+
 /// Draws a grid based on a given color matrix, with support for zoom and scroll.
 ///
 /// This function iterates over a matrix of colors to draw a grid of rectangles. It optimizes
@@ -26,15 +109,16 @@ pub const ROBOT_COLOR: [f32; 4] = [191.0 / 255.0, 139.0 / 255.0, 255.0 / 255.0,
 /// Then, the function displays the robot's position on the produced grid
 ///
 /// # Arguments
-/// * `matrix` - A reference to the primary color matrix for drawing rectangles.
+/// * `matrix` - A reference to the primary color matrix for drawing rectangles, indexed `[row][col]`.
 /// * `context` - The Piston window context for drawing.
 /// * `graphics` - The graphics backend for rendering shapes.
-/// * `grid_size` - The dimensions of the grid (in cells).
+/// * `grid_size` - The dimensions of the grid (in cells) as (columns, rows).
 /// * `rect_size` - The size of each cell in the grid.
 /// * `scroll_offset` - The current scroll offset for the view.
 /// * `zoom_factor` - The current zoom level for the view.
 /// * `coord_x` - The x-coordinate of the robot's position.
 /// * `coord_y` - The y-coordinate of the robot's position.
+/// * `view_size` - The size of the window (see `HudLayout::window_size`), the cells outside it are not drawn.
 pub fn draw_optimized_grid(
     matrix: &ColorMatrix,
     context: Context,
@@ -45,54 +129,25 @@ pub fn draw_optimized_grid(
     zoom_factor: f64,
     coord_x: f64,
     coord_y: f64,
+    view_size: [f64; 2],
 ) {
-    // Calculate visible area considering zoom and scroll
-    let visible_start_col = ((scroll_offset[0] / zoom_factor) / rect_size).max(0.0) as usize;
-    let visible_start_row = ((scroll_offset[1] / zoom_factor) / rect_size).max(0.0) as usize;
-    let visible_end_col = (((scroll_offset[0] + WINDOW_SIZE.0 as f64) / zoom_factor) / rect_size)
-        .min(grid_size.0 as f64) as usize;
-    let visible_end_row = (((scroll_offset[1] + WINDOW_SIZE.1 as f64) / zoom_factor) / rect_size)
-        .min(grid_size.1 as f64) as usize;
+    draw_color_layer(matrix, context, graphics, grid_size, rect_size, scroll_offset, zoom_factor, view_size);
 
     let transform = context
         .transform
         .trans(-scroll_offset[0], -scroll_offset[1])
         .zoom(zoom_factor);
 
-    for j in visible_start_row..visible_end_row {
-        let mut i = visible_start_col;
-        while i < visible_end_col {
-            let color = matrix[i][j];
-            let mut end_col = i + 1;
-            while end_col < visible_end_col && matrix[end_col][j] == color {
-                end_col += 1;
-            }
-
-            let rect_x = i as f64 * rect_size * zoom_factor - scroll_offset[0];
-            let rect_y = j as f64 * rect_size * zoom_factor - scroll_offset[1];
-            let rect_width = (end_col - i) as f64 * rect_size * zoom_factor;
-
-            rectangle(
-                color,
-                [rect_x, rect_y, rect_width, rect_size * zoom_factor],
-                transform,
-                graphics,
-            );
-
-            //robot's position
-            let robot_x = coord_x * rect_size * zoom_factor - scroll_offset[0];
-            let robot_y = coord_y * rect_size * zoom_factor - scroll_offset[1];
-            let robot_rect_width = rect_size * zoom_factor;
-            rectangle(
-                ROBOT_COLOR,
-                [robot_x, robot_y, robot_rect_width, rect_size * zoom_factor],
-                transform,
-                graphics,
-            );
-
-            i = end_col;
-        }
-    }
+    //robot's position
+    let robot_x = coord_x * rect_size * zoom_factor - scroll_offset[0];
+    let robot_y = coord_y * rect_size * zoom_factor - scroll_offset[1];
+    let robot_rect_width = rect_size * zoom_factor;
+    rectangle(
+        ROBOT_COLOR,
+        [robot_x, robot_y, robot_rect_width, rect_size * zoom_factor],
+        transform,
+        graphics,
+    );
 
     let white = [1.0, 1.0, 1.0, 1.0]; // RGBA color for white
     // Draw a white rectangle to the right of the last column
@@ -128,37 +183,492 @@ pub fn draw_optimized_grid(
     );
 }
 
-/// Draws a 3x3 grid representing the robot's immediate surroundings.
+/// Draws the cells of a color matrix, merging contiguous cells of the same color into
+/// single rectangles; transparent cells are skipped. Used by `draw_optimized_grid` and for
+/// the layers drawn over the grid.
+///
+/// # Arguments
+/// * `matrix` - The colors of the cells, indexed `[row][col]`.
+/// * `context` - The Piston window context for drawing.
+/// * `graphics` - The graphics backend for rendering shapes.
+/// * `grid_size` - The dimensions of the grid (in cells) as (columns, rows).
+/// * `rect_size` - The size of each cell in the grid.
+/// * `scroll_offset` - The current scroll offset for the view.
+/// * `zoom_factor` - The current zoom level for the view.
+/// * `view_size` - The size of the window, the cells outside it are not drawn.
+pub fn draw_color_layer(
+    matrix: &ColorMatrix,
+    context: Context,
+    graphics: &mut G2d,
+    grid_size: (usize, usize),
+    rect_size: f64,
+    scroll_offset: [f64; 2],
+    zoom_factor: f64,
+    view_size: [f64; 2],
+) {
+    // Calculate visible area considering zoom and scroll
+    let visible_start_col = ((scroll_offset[0] / zoom_factor) / rect_size).max(0.0) as usize;
+    let visible_start_row = ((scroll_offset[1] / zoom_factor) / rect_size).max(0.0) as usize;
+    let visible_end_col = (((scroll_offset[0] + view_size[0]) / zoom_factor) / rect_size)
+        .min(grid_size.0 as f64) as usize;
+    let visible_end_row = (((scroll_offset[1] + view_size[1]) / zoom_factor) / rect_size)
+        .min(grid_size.1 as f64) as usize;
+
+    let transform = context
+        .transform
+        .trans(-scroll_offset[0], -scroll_offset[1])
+        .zoom(zoom_factor);
+
+    // the matrix is indexed [row][col], it can be smaller than the grid
+    let visible_end_row = visible_end_row.min(matrix.len());
+    for row in visible_start_row..visible_end_row {
+        let matrix_row = &matrix[row];
+        let row_end_col = visible_end_col.min(matrix_row.len());
+        let mut col = visible_start_col;
+        while col < row_end_col {
+            let color = matrix_row[col];
+            let mut end_col = col + 1;
+            while end_col < row_end_col && matrix_row[end_col] == color {
+                end_col += 1;
+            }
+
+            let rect_x = col as f64 * rect_size * zoom_factor - scroll_offset[0];
+            let rect_y = row as f64 * rect_size * zoom_factor - scroll_offset[1];
+            let rect_width = (end_col - col) as f64 * rect_size * zoom_factor;
+
+            if color[3] > 0.0 {
+                rectangle(
+                    color,
+                    [rect_x, rect_y, rect_width, rect_size * zoom_factor],
+                    transform,
+                    graphics,
+                );
+            }
+
+            col = end_col;
+        }
+    }
+
+}
+
+/// Draws a diagonal line over every visible undiscovered tile of the map, so that the
+/// unknown tiles can't be mistaken for black tiles. Nothing is drawn when the tiles are
+/// too small for the lines to be seen.
+///
+/// # Arguments
+/// * `tile_colors` - The discovered map, a tile is undiscovered if it is transparent.
+/// * `color` - The color of the lines.
+/// * `context` - The Piston window context.
+/// * `graphics` - The graphics backend.
+/// * `grid_size` - The size of the grid as (columns, rows).
+/// * `rect_size` - The size of a tile before zooming.
+/// * `scroll_offset` - The scroll of the grid.
+/// * `zoom_factor` - The zoom of the grid.
+/// * `view_size` - The size of the window, the tiles outside it are not drawn.
+pub fn draw_hatching(
+    tile_colors: &ColorMatrix,
+    color: Color,
+    context: Context,
+    graphics: &mut G2d,
+    grid_size: (usize, usize),
+    rect_size: f64,
+    scroll_offset: [f64; 2],
+    zoom_factor: f64,
+    view_size: [f64; 2],
+) {
+    let cell = rect_size * zoom_factor;
+    if cell < 4.0 {
+        return;
+    }
+    let visible_start_col = ((scroll_offset[0] / zoom_factor) / rect_size).max(0.0) as usize;
+    let visible_start_row = ((scroll_offset[1] / zoom_factor) / rect_size).max(0.0) as usize;
+    let visible_end_col = (((scroll_offset[0] + view_size[0]) / zoom_factor) / rect_size)
+        .min(grid_size.0 as f64) as usize;
+    let visible_end_row = (((scroll_offset[1] + view_size[1]) / zoom_factor) / rect_size)
+        .min(grid_size.1 as f64) as usize;
+
+    let transform = context
+        .transform
+        .trans(-scroll_offset[0], -scroll_offset[1])
+        .zoom(zoom_factor);
+
+    for row in visible_start_row..visible_end_row.min(tile_colors.len()) {
+        let matrix_row = &tile_colors[row];
+        for col in visible_start_col..visible_end_col.min(matrix_row.len()) {
+            if matrix_row[col][3] > 0.0 {
+                continue;
+            }
+            let x = col as f64 * cell - scroll_offset[0];
+            let y = row as f64 * cell - scroll_offset[1];
+            line(color, 0.5, [x, y + cell, x + cell, y], transform, graphics);
+        }
+    }
+}
+
+/// The contexts of the two halves of the split screen: each one only draws in its half of
+/// the grid area, and the right one is moved so that both show the same part of the map.
+///
+/// # Arguments
+/// * `context` - The Piston window context.
+/// * `width`, `height` - The size of the area shared by the two halves.
+pub fn split_view_contexts(context: Context, width: f64, height: f64) -> [Context; 2] {
+    let half = width / 2.0;
+    let left = Context {
+        draw_state: context.draw_state.scissor([0, 0, half as u32, height as u32]),
+        ..context
+    };
+    let right = Context {
+        draw_state: context.draw_state.scissor([half as u32, 0, half as u32, height as u32]),
+        transform: context.transform.trans(half, 0.0),
+        ..context
+    };
+    [left, right]
+}
+
+/// Draws two runs side by side with linked cameras: both halves of the grid area show the
+/// same part of the map, with the same scroll offset and zoom factor.
+///
+/// # Arguments
+/// * `sides` - The left and the right run, with the color of their robot.
+/// * `diff` - The overlay of the tiles explored by a single run (see `diff_overlay`), if shown.
+/// * `context` - The Piston window context for drawing.
+/// * `graphics` - The graphics backend for rendering shapes.
+/// * `glyphs` - The font glyphs, used for the name of the runs.
+/// * `grid_size` - The dimensions of the grid (in cells) as (columns, rows).
+/// * `rect_size` - The size of each cell in the grid.
+/// * `scroll_offset` - The current scroll offset for the view.
+/// * `zoom_factor` - The current zoom level for the view.
+/// * `grid_pixels` - The side of the grid area shared by the two halves.
+pub fn draw_split_view(
+    sides: [(&RunView, Color); 2],
+    diff: Option<&ColorMatrix>,
+    context: Context,
+    graphics: &mut G2d,
+    mut glyphs: Option<&mut Glyphs>,
+    grid_size: (usize, usize),
+    rect_size: f64,
+    scroll_offset: [f64; 2],
+    zoom_factor: f64,
+    grid_pixels: f64,
+) {
+    let contexts = split_view_contexts(context, grid_pixels, grid_pixels);
+    let view_size = [grid_pixels, grid_pixels];
+    for ((run, color), side_context) in sides.iter().zip(contexts) {
+        draw_color_layer(run.tile_colors, side_context, graphics, grid_size, rect_size, scroll_offset, zoom_factor, view_size);
+        if let Some(diff) = diff {
+            draw_color_layer(diff, side_context, graphics, grid_size, rect_size, scroll_offset, zoom_factor, view_size);
+        }
+        draw_robot_trail(&[run.coordinates], *color, side_context, graphics, rect_size, scroll_offset, zoom_factor);
+        if let Some(glyphs) = glyphs.as_deref_mut() {
+            rectangle([0.1, 0.1, 0.1, 0.8], [0.0, 0.0, grid_pixels / 2.0, 20.0], side_context.transform, graphics);
+            rectangle(*color, [4.0, 5.0, 10.0, 10.0], side_context.transform, graphics);
+            let text = format!("{}  energy {}  score {:.0}", run.label, run.energy, run.score);
+            draw_small_text(&side_context, graphics, glyphs, [1.0; 4], [18.0, 14.0], &text);
+        }
+    }
+    rectangle([1.0; 4], [grid_pixels / 2.0 - 1.0, 0.0, 2.0, grid_pixels], context.transform, graphics);
+}
+
+/// Draws a robot and its trail over the grid: the trail is a line of small squares fading
+/// out towards the oldest position.
+///
+/// Uses the same placement as `draw_optimized_grid`, so it must be drawn with the same
+/// rect size, scroll offset and zoom factor.
+///
+/// # Arguments
+/// * `trail` - The last positions of the robot as (row, col), oldest first; the last one is the robot.
+/// * `color` - The color of the robot.
+/// * `context` - The Piston window context for drawing.
+/// * `graphics` - The graphics backend for rendering shapes.
+/// * `rect_size` - The size of each cell in the grid.
+/// * `scroll_offset` - The current scroll offset for the view.
+/// * `zoom_factor` - The current zoom level for the view.
+pub fn draw_robot_trail(
+    trail: &[(usize, usize)],
+    color: Color,
+    context: Context,
+    graphics: &mut G2d,
+    rect_size: f64,
+    scroll_offset: [f64; 2],
+    zoom_factor: f64,
+) {
+    let transform = context
+        .transform
+        .trans(-scroll_offset[0], -scroll_offset[1])
+        .zoom(zoom_factor);
+    let cell = rect_size * zoom_factor;
+    for (index, (row, col)) in trail.iter().enumerate() {
+        let x = *col as f64 * cell - scroll_offset[0];
+        let y = *row as f64 * cell - scroll_offset[1];
+        if index + 1 == trail.len() {
+            rectangle(color, [x, y, cell, cell], transform, graphics);
+        } else {
+            let alpha = 0.15 + 0.5 * (index + 1) as f32 / trail.len() as f32;
+            let dot = cell / 2.0;
+            rectangle(
+                [color[0], color[1], color[2], alpha],
+                [x + dot / 2.0, y + dot / 2.0, dot, dot],
+                transform,
+                graphics,
+            );
+        }
+    }
+}
+
+/// Draws a border around every discovered teleport, in the color of its activation state,
+/// with its number; if `show_connections` is set, the active teleports are also connected
+/// to each other, since the robot can jump between any two of them.
+///
+/// # Arguments
+/// * `links` - The discovered teleports.
+/// * `show_connections` - If the lines between the active teleports are drawn.
+/// * `context` - The Piston window context.
+/// * `graphics` - The graphics backend.
+/// * `glyphs` - The font glyphs, the numbers are not drawn without them.
+/// * `rect_size` - The size of a tile before zooming.
+/// * `scroll_offset` - The scroll of the grid.
+/// * `zoom_factor` - The zoom of the grid.
+pub fn draw_teleport_links(
+    links: &TeleportLinks,
+    show_connections: bool,
+    context: Context,
+    graphics: &mut G2d,
+    glyphs: Option<&mut Glyphs>,
+    rect_size: f64,
+    scroll_offset: [f64; 2],
+    zoom_factor: f64,
+) {
+    let transform = context
+        .transform
+        .trans(-scroll_offset[0], -scroll_offset[1])
+        .zoom(zoom_factor);
+    let cell = rect_size * zoom_factor;
+    let corner = |(row, col): (usize, usize)| [col as f64 * cell - scroll_offset[0], row as f64 * cell - scroll_offset[1]];
+
+    if show_connections {
+        let active: Vec<[f64; 2]> = links.links().iter().filter(|link| link.active).map(|link| corner(link.coordinates)).collect();
+        for (index, from) in active.iter().enumerate() {
+            for to in &active[index + 1..] {
+                line(
+                    TELEPORT_LINK_COLOR,
+                    0.5,
+                    [from[0] + cell / 2.0, from[1] + cell / 2.0, to[0] + cell / 2.0, to[1] + cell / 2.0],
+                    transform,
+                    graphics,
+                );
+            }
+        }
+    }
+
+    for link in links.links() {
+        let [x, y] = corner(link.coordinates);
+        let color = if link.active { ACTIVE_TELEPORT_COLOR } else { INACTIVE_TELEPORT_COLOR };
+        Rectangle::new_border(color, 1.0).draw([x, y, cell, cell], &context.draw_state, transform, graphics);
+    }
+
+    //the labels are drawn on the screen, so that they keep the same size at any zoom
+    if let Some(glyphs) = glyphs {
+        for link in links.links() {
+            let [x, y] = corner(link.coordinates);
+            let screen = [x * zoom_factor - scroll_offset[0], y * zoom_factor - scroll_offset[1]];
+            let color = if link.active { ACTIVE_TELEPORT_COLOR } else { INACTIVE_TELEPORT_COLOR };
+            draw_small_text(
+                &context,
+                graphics,
+                glyphs,
+                color,
+                [screen[0] + cell * zoom_factor + 1.0, screen[1]],
+                link.number.to_string().as_str(),
+            );
+        }
+    }
+}
+
+/// Draws the robot flying between two teleports: a line between them that fades as the
+/// jump goes on, and the robot at its position along the line.
+///
+/// # Arguments
+/// * `jump` - The jump.
+/// * `color` - The color of the robot.
+/// * `now` - The current time.
+/// * `context` - The Piston window context.
+/// * `graphics` - The graphics backend.
+/// * `rect_size` - The size of a tile before zooming.
+/// * `scroll_offset` - The scroll of the grid.
+/// * `zoom_factor` - The zoom of the grid.
+pub fn draw_teleport_jump(
+    jump: &JumpAnimation,
+    color: Color,
+    now: Instant,
+    context: Context,
+    graphics: &mut G2d,
+    rect_size: f64,
+    scroll_offset: [f64; 2],
+    zoom_factor: f64,
+) {
+    let transform = context
+        .transform
+        .trans(-scroll_offset[0], -scroll_offset[1])
+        .zoom(zoom_factor);
+    let cell = rect_size * zoom_factor;
+    let center = |(row, col): (f64, f64)| [col * cell - scroll_offset[0] + cell / 2.0, row * cell - scroll_offset[1] + cell / 2.0];
+
+    let from = center((jump.from.0 as f64, jump.from.1 as f64));
+    let to = center((jump.to.0 as f64, jump.to.1 as f64));
+    let mut line_color = ACTIVE_TELEPORT_COLOR;
+    line_color[3] = 1.0 - jump.progress(now) as f32;
+    line(line_color, 1.0, [from[0], from[1], to[0], to[1]], transform, graphics);
+
+    let [x, y] = center(jump.position(now));
+    rectangle(color, [x - cell / 2.0, y - cell / 2.0, cell, cell], transform, graphics);
+}
+
+/// Draws one card per robot in the bottom strip of the window: its color, name, energy,
+/// score and discovered tiles. The focused robot has a white border, robots whose map is
+/// hidden are dimmed.
 ///
-/// This function visualizes the robot's local view by drawing a 3x3 grid of
+/// # Arguments
+/// * `robots` - The robots of the run.
+/// * `ctx` - The Piston window context.
+/// * `graphics` - The graphics backend.
+/// * `glyphs` - The font glyphs.
+/// * `area` - The area of the cards `[x, y, width, height]`, filled row by row.
+pub fn draw_robot_cards(
+    robots: &RobotSlots,
+    ctx: &Context,
+    graphics: &mut G2d,
+    glyphs: &mut Glyphs,
+    area: [f64; 4],
+) {
+    let [x, y, width, _] = area;
+    let cards_per_row = ((width / ROBOT_CARD_SIZE[0]) as usize).max(1);
+    for (index, slot) in robots.slots().iter().enumerate() {
+        let card_x = x + (index % cards_per_row) as f64 * ROBOT_CARD_SIZE[0];
+        let card_y = y + (index / cards_per_row) as f64 * ROBOT_CARD_SIZE[1];
+        let card = [card_x, card_y, ROBOT_CARD_SIZE[0] - 5.0, ROBOT_CARD_SIZE[1] - 5.0];
+        if index == robots.focused() {
+            rectangle([1.0; 4], [card[0] - 1.0, card[1] - 1.0, card[2] + 2.0, card[3] + 2.0], ctx.transform, graphics);
+        }
+        rectangle([0.1, 0.1, 0.1, 0.9], card, ctx.transform, graphics);
+
+        let text_color = if slot.show_map { [1.0; 4] } else { [0.5, 0.5, 0.5, 1.0] };
+        rectangle(slot.color, [card[0] + 4.0, card[1] + 4.0, 10.0, 10.0], ctx.transform, graphics);
+        let lines = [
+            format!("F{} {}", index + 1, slot.label),
+            format!("energy {}  score {:.0}", slot.state.energy, slot.state.score),
+            format!("discovered {}", slot.discovered_tiles()),
+        ];
+        for (line_index, line) in lines.iter().enumerate() {
+            let text_x = if line_index == 0 { card[0] + 18.0 } else { card[0] + 4.0 };
+            let text_y = card[1] + 13.0 + line_index as f64 * EVENT_LOG_LINE_HEIGHT;
+            draw_small_text(ctx, graphics, glyphs, text_color, [text_x, text_y], line);
+        }
+    }
+}
+
+/// Draws the environmental conditions over the map: a dark layer whose opacity follows
+/// the time of day and, while it is raining, a blue layer with rain streaks.
+///
+/// The overlay covers the same area as `draw_optimized_grid`, so it must be drawn
+/// right after it, with the same scroll offset and zoom factor.
+///
+/// # Arguments
+/// * `environment` - The current environmental conditions.
+/// * `context` - The Piston window context for drawing.
+/// * `graphics` - The graphics backend for rendering shapes.
+/// * `grid_size` - The dimensions of the grid (in cells).
+/// * `rect_size` - The size of each cell in the grid.
+/// * `scroll_offset` - The current scroll offset for the view.
+/// * `zoom_factor` - The current zoom level for the view.
+pub fn draw_environment_overlay(
+    environment: &EnvironmentInfo,
+    context: Context,
+    graphics: &mut G2d,
+    grid_size: (usize, usize),
+    rect_size: f64,
+    scroll_offset: [f64; 2],
+    zoom_factor: f64,
+) {
+    let transform = context
+        .transform
+        .trans(-scroll_offset[0], -scroll_offset[1])
+        .zoom(zoom_factor);
+
+    let area = [
+        -scroll_offset[0],
+        -scroll_offset[1],
+        grid_size.0 as f64 * rect_size * zoom_factor,
+        grid_size.1 as f64 * rect_size * zoom_factor,
+    ];
+
+    if environment.is_raining() {
+        rectangle(RAIN_TINT, area, transform, graphics);
+
+        // rain streaks, shifted every in-game minute so that the rain looks animated
+        let streak_color = [0.7, 0.8, 1.0, 0.35];
+        let spacing = 40.0;
+        let shift = (environment.minute as f64 * 7.0) % spacing;
+        let mut y = area[1] + shift;
+        while y < area[1] + area[3] {
+            let mut x = area[0] + (y / spacing).floor() % 2.0 * spacing / 2.0;
+            while x < area[0] + area[2] {
+                line(streak_color, 0.5, [x, y, x - 4.0, y + 12.0], transform, graphics);
+                x += spacing;
+            }
+            y += spacing / 2.0;
+        }
+    }
+
+    let darkness = environment.darkness();
+    if darkness > 0.0 {
+        rectangle([0.0, 0.0, 0.05, darkness], area, transform, graphics);
+    }
+}
+
+/// Draws a grid representing the robot's surroundings, with the robot in the center.
+///
+/// This function visualizes the robot's local view by drawing a grid of
 /// rectangles(representing the TileType) and circles(representing the Content),
 /// where each cell's color is determined by the corresponding entry in the provided color matrices.
+/// The view can have any size: the cells are scaled to fit the given area, the robot's cell
+/// (the central one) is outlined and, if glyphs are given, the quantity of each content is written in its cell.
 ///
 /// # Arguments
-/// * `rect_matrix` - Color matrix for the rectangles of the robot's view.
-/// * `circle_matrix` - Color matrix for the circles within the robot's view.
+/// * `rect_matrix` - Color matrix for the rectangles of the robot's view, indexed `[row][col]`.
+/// * `circle_matrix` - Color matrix for the circles within the robot's view, indexed `[row][col]`.
+/// * `quantity_matrix` - Quantity of the content of each cell, if it has one, indexed `[row][col]`.
 /// * `context` - The Piston window context.
 /// * `graphics` - The graphics backend.
-/// * `rect_size` - The size of each rectangle and circle in the grid.
+/// * `glyphs` - The font glyphs, if missing the quantities are not drawn.
+/// * `area` - The position and size `[x, y, width, height]` the view has to fit in.
 pub fn draw_robot_view(
     rect_matrix: &Vec<Vec<[f32; 4]>>,
     circle_matrix: &Vec<Vec<[f32; 4]>>,
+    quantity_matrix: &Vec<Vec<Option<usize>>>,
     context: Context,
     graphics: &mut G2d,
-    rect_size: f64,
+    mut glyphs: Option<&mut Glyphs>,
+    area: [f64; 4],
 ) {
-    let grid_start_x = 500.0;
-    let grid_start_y = 770.0;
+    let rows = rect_matrix.len();
+    let cols = rect_matrix.iter().map(|row| row.len()).max().unwrap_or(0);
+    if rows == 0 || cols == 0 {
+        return;
+    }
+
+    let rect_size = (area[2] / cols as f64).min(area[3] / rows as f64);
+    let grid_start_x = area[0] + (area[2] - rect_size * cols as f64) / 2.0;
+    let grid_start_y = area[1] + (area[3] - rect_size * rows as f64) / 2.0;
+    let font_size = (rect_size / 3.0).clamp(8.0, 16.0) as u32;
 
-    // Iterate over the 3x3 matrix for rectangles
-    for (i, row) in rect_matrix.iter().enumerate() {
-        for (j, _) in row.iter().enumerate() {
+    for row in 0..rows {
+        for col in 0..cols {
             // Calculate the position for each rectangle
-            let x = grid_start_x + (j as f64 * rect_size);
-            let y = grid_start_y + (i as f64 * rect_size);
+            let x = grid_start_x + (col as f64 * rect_size);
+            let y = grid_start_y + (row as f64 * rect_size);
 
             // Draw the rectangle
-            if let Some(&rect_color) = rect_matrix.get(j).and_then(|r| r.get(i)) {
+            if let Some(&rect_color) = rect_matrix.get(row).and_then(|r| r.get(col)) {
                 rectangle(
                     rect_color,
                     [x, y, rect_size, rect_size],
@@ -167,11 +677,10 @@ pub fn draw_robot_view(
                 );
             }
 
-
             let circle_radius = rect_size / 4.0;
             let circle_x = x + rect_size / 2.0 - circle_radius;
             let circle_y = y + rect_size / 2.0 - circle_radius;
-            if let Some(&circle_color) = circle_matrix.get(j).and_then(|r| r.get(i)) {
+            if let Some(&circle_color) = circle_matrix.get(row).and_then(|r| r.get(col)) {
                 ellipse(
                     circle_color,
                     [circle_x, circle_y, circle_radius * 2.0, circle_radius * 2.0],
@@ -179,63 +688,190 @@ pub fn draw_robot_view(
                     graphics,
                 );
             }
+
+            // the quantity is written only if it is readable
+            if let (Some(glyphs), Some(Some(quantity))) = (
+                glyphs.as_deref_mut(),
+                quantity_matrix.get(row).and_then(|r| r.get(col)),
+            ) {
+                if rect_size >= 18.0 {
+                    draw_sized_text(
+                        &context,
+                        graphics,
+                        glyphs,
+                        [1.0; 4],
+                        font_size,
+                        [x + 2.0, y + rect_size - 2.0],
+                        quantity.to_string().as_str(),
+                    );
+                }
+            }
+        }
+    }
+
+    // the robot is in the center of the view
+    Rectangle::new_border(ROBOT_COLOR, (rect_size / 20.0).max(1.0)).draw(
+        [
+            grid_start_x + (cols / 2) as f64 * rect_size,
+            grid_start_y + (rows / 2) as f64 * rect_size,
+            rect_size,
+            rect_size,
+        ],
+        &context.draw_state,
+        context.transform,
+        graphics,
+    );
+}
+
+/// A continuous color gradient, defined by colors placed at fractions between 0.0 and 1.0.
+///
+/// The color of a fraction between two stops is interpolated linearly, so that the
+/// color changes smoothly when the value changes.
+#[derive(Clone, Debug)]
+pub struct ColorRamp {
+    stops: Vec<(f64, Color)>,
+}
+
+impl ColorRamp {
+    /// Creates a ramp from its stops, which are sorted by fraction.
+    ///
+    /// # Arguments
+    /// * `stops` - `(fraction, color)` pairs, the fractions should be in 0.0..=1.0.
+    pub fn new(mut stops: Vec<(f64, Color)>) -> Self {
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { stops }
+    }
+
+    /// The ramp used for the energy: red when empty, then orange, yellow and green when full.
+    pub fn energy() -> Self {
+        Self::new(vec![
+            (0.0, [1.0, 0.0, 0.0, 1.0]),
+            (0.3, [1.0, 0.55, 0.0, 1.0]),
+            (0.6, [1.0, 0.9, 0.0, 1.0]),
+            (1.0, [0.0, 0.8, 0.0, 1.0]),
+        ])
+    }
+
+    /// A single color ramp, for bars whose color doesn't depend on the value.
+    pub fn solid(color: Color) -> Self {
+        Self::new(vec![(0.0, color), (1.0, color)])
+    }
+
+    /// Returns the color of the given fraction, clamped to the first and last stop.
+    pub fn color_at(&self, fraction: f64) -> Color {
+        let (first, last) = match (self.stops.first(), self.stops.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return [1.0; 4],
+        };
+        if fraction <= first.0 {
+            return first.1;
+        }
+        if fraction >= last.0 {
+            return last.1;
+        }
+        for pair in self.stops.windows(2) {
+            let ((start, start_color), (end, end_color)) = (pair[0], pair[1]);
+            if fraction <= end {
+                let factor = if end > start { ((fraction - start) / (end - start)) as f32 } else { 1.0 };
+                let mut color = start_color;
+                for (channel, (start, end)) in color.iter_mut().zip(start_color.iter().zip(end_color)) {
+                    *channel += (end - start) * factor;
+                }
+                return color;
+            }
         }
+        last.1
     }
 }
 
-/// Draws a rectangle representing the robot's current energy level.
+/// A horizontal gauge for any bounded metric (energy, backpack usage, run progress...).
 ///
-/// The color and length of the rectangle vary based on the robot's current energy, providing
-/// a visual indicator of its status.
+/// The filled part is proportional to `value / max` and colored by the `ColorRamp`,
+/// the bar has a border, an optional label above it and the `value / max` text on its right.
 ///
-/// # Arguments
-/// * `energy_level` - The current energy level of the robot.
-/// * `context` - The Piston window context.
-/// * `graphics` - The graphics backend.
-/// * `start_x` - The starting x-coordinate for the energy level rectangle.
-/// * `start_y` - The starting y-coordinate for the energy level rectangle.
-pub fn draw_energy_level(
-    energy_level: usize,
-    context: &Context,
-    graphics: &mut G2d,
-    start_x: f64, // Starting X position for the rectangle
-    start_y: f64, // Starting Y position for the rectangle
-) {
-    let length = (energy_level as f64 / 1000.0) * 100.0; //pixels -> if the energy is at the maximum value(1000) the rect will be 100pixel long
-
-    let color:[f32;4] = match energy_level {
-        801..=1000 => { // 80% to 100%
-            // Full green to lighter green
-            let factor = (energy_level as f64 - 800.0) / 200.0;
-            [0.0, ((1.0 - factor) * 139.0/255.0 + factor) as f32, 0.0, 1.0]
-        },
-        601..=800 => { // 60% to 80%
-            // Lighter green to orange
-            let factor = (energy_level as f64 - 600.0) / 200.0;
-            [((factor) * 255.0/255.0) as f32, ((1.0 - factor) * 255.0/255.0) as f32, 0.0, 1.0]
-        },
-        401..=600 => { // 40% to 60%
-            // Orange to red
-            [255.0/255.0, ((1.0 - ((energy_level as f64 - 400.0) / 200.0)) as f32) * 165.0/255.0, 0.0, 1.0]
-        },
-        201..=400 => { // 20% to 40%
-            // Red to light red
-            let factor = (energy_level as f64 - 200.0) / 200.0;
-            [1.0, (factor * 69.0/255.0) as f32, (factor * 69.0/255.0) as f32, 1.0]
-        },
-        _ => { // Below 20%
-            // Light red
-            [1.0, 0.69, 0.69, 1.0]
-        },
-    };
+/// Attributes:
+/// - `max`: The value that fills the bar.
+/// - `ramp`: The color of the filled part, depending on the filled fraction.
+/// - `label`: The text drawn above the bar.
+/// - `text_color`: The color of the label and of the value, drawn on the window background.
+/// - `area`: The bar position and size `[x, y, width, height]`.
+#[derive(Clone, Debug)]
+pub struct ProgressBar {
+    pub max: f64,
+    pub ramp: ColorRamp,
+    pub label: Option<String>,
+    pub text_color: Color,
+    pub area: [f64; 4],
+}
 
-    // Draw the energy level rectangle
-    rectangle(
-         color, // Color based on energy level
-        [start_x, start_y, length, 10.0], // x, y, width, height
-        context.transform,
-        graphics,
-    );
+impl ProgressBar {
+    pub fn new(max: f64, area: [f64; 4]) -> Self {
+        Self {
+            max,
+            ramp: ColorRamp::solid([1.0; 4]),
+            label: None,
+            text_color: [1.0; 4],
+            area,
+        }
+    }
+
+    pub fn with_ramp(mut self, ramp: ColorRamp) -> Self {
+        self.ramp = ramp;
+        self
+    }
+
+    pub fn with_text_color(mut self, text_color: Color) -> Self {
+        self.text_color = text_color;
+        self
+    }
+
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    /// Returns the filled fraction for the given value, in 0.0..=1.0.
+    pub fn fraction(&self, value: f64) -> f64 {
+        if self.max <= 0.0 {
+            return 0.0;
+        }
+        (value / self.max).clamp(0.0, 1.0)
+    }
+
+    /// Returns the width of the filled part of the bar for the given value.
+    pub fn fill_width(&self, value: f64) -> f64 {
+        self.area[2] * self.fraction(value)
+    }
+
+    /// Draws the bar filled up to `value`.
+    ///
+    /// # Arguments
+    /// * `value` - The current value of the metric.
+    /// * `ctx` - The Piston window context.
+    /// * `graphics` - The graphics backend.
+    /// * `glyphs` - The font glyphs, if missing the label and the value are not drawn.
+    pub fn draw(&self, value: f64, ctx: &Context, graphics: &mut G2d, glyphs: Option<&mut Glyphs>) {
+        let fraction = self.fraction(value);
+        let [x, y, width, height] = self.area;
+
+        rectangle([0.15, 0.15, 0.15, 1.0], self.area, ctx.transform, graphics);
+        rectangle(self.ramp.color_at(fraction), [x, y, self.fill_width(value), height], ctx.transform, graphics);
+        Rectangle::new_border([0.8, 0.8, 0.8, 1.0], 0.5).draw(self.area, &ctx.draw_state, ctx.transform, graphics);
+
+        if let Some(glyphs) = glyphs {
+            if let Some(label) = &self.label {
+                draw_small_text(ctx, graphics, glyphs, self.text_color, [x, y - 3.0], label.as_str());
+            }
+            draw_small_text(
+                ctx,
+                graphics,
+                glyphs,
+                self.text_color,
+                [x + width + 5.0, y + height],
+                format!("{}/{}", value.floor(), self.max.floor()).as_str(),
+            );
+        }
+    }
 }
 
 /// Draws textual information at a specified position on the screen.
@@ -265,3 +901,370 @@ pub fn draw_text(
         )
         .unwrap();
 }
+
+/// Draws the event log panel: a filter bar listing every event kind (dimmed if hidden)
+/// followed by the visible entries, newest first.
+///
+/// # Arguments
+/// * `event_log` - The log to draw.
+/// * `filter` - The event kinds currently hidden.
+/// * `scroll` - How many of the newest visible entries to skip.
+/// * `ctx` - The Piston window context.
+/// * `graphics` - The graphics backend.
+/// * `glyphs` - The font glyphs.
+/// * `area` - The panel position and size `[x, y, width, height]`.
+pub fn draw_event_log(
+    event_log: &EventLog,
+    filter: &EventFilter,
+    scroll: usize,
+    ctx: &Context,
+    graphics: &mut G2d,
+    glyphs: &mut Glyphs,
+    area: [f64; 4],
+) {
+    rectangle([0.1, 0.1, 0.1, 0.8], area, ctx.transform, graphics);
+
+    // filter bar: "1 move  2 add ..."
+    let mut y = area[1] + EVENT_LOG_LINE_HEIGHT;
+    for (index, kind) in EventKind::ALL.iter().enumerate() {
+        let column = (index % 2) as f64;
+        let color = if filter.is_visible(*kind) {
+            kind.color()
+        } else {
+            [0.35, 0.35, 0.35, 1.0]
+        };
+        draw_small_text(
+            ctx,
+            graphics,
+            glyphs,
+            color,
+            [area[0] + 4.0 + column * area[2] / 2.0, y],
+            format!("{} {}", index + 1, kind.label()).as_str(),
+        );
+        if column == 1.0 {
+            y += EVENT_LOG_LINE_HEIGHT;
+        }
+    }
+    y += EVENT_LOG_LINE_HEIGHT * 1.5;
+
+    for entry in event_log.filtered(filter).skip(scroll) {
+        if y > area[1] + area[3] {
+            break;
+        }
+        draw_small_text(
+            ctx,
+            graphics,
+            glyphs,
+            entry.kind.color(),
+            [area[0] + 4.0, y],
+            entry.to_text().as_str(),
+        );
+        y += EVENT_LOG_LINE_HEIGHT;
+    }
+}
+
+/// Draws a line chart of a time series, scaled to fit the given area.
+///
+/// When there are more values than horizontal pixels, only one value per pixel is drawn.
+/// The label and the last value are written in the top-left corner of the chart.
+///
+/// # Arguments
+/// * `values` - The series to draw, one value per tick.
+/// * `color` - The color of the line.
+/// * `label` - The name of the series.
+/// * `ctx` - The Piston window context.
+/// * `graphics` - The graphics backend.
+/// * `glyphs` - The font glyphs.
+/// * `area` - The chart position and size `[x, y, width, height]`.
+pub fn draw_line_chart(
+    values: &[f64],
+    color: Color,
+    label: &str,
+    ctx: &Context,
+    graphics: &mut G2d,
+    glyphs: &mut Glyphs,
+    area: [f64; 4],
+) {
+    rectangle([0.1, 0.1, 0.1, 0.8], area, ctx.transform, graphics);
+
+    let last = values.last().copied().unwrap_or(0.0);
+    draw_small_text(
+        ctx,
+        graphics,
+        glyphs,
+        [1.0; 4],
+        [area[0] + 4.0, area[1] + EVENT_LOG_LINE_HEIGHT],
+        format!("{}: {}", label, last.floor()).as_str(),
+    );
+
+    if values.len() < 2 {
+        return;
+    }
+
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min).min(0.0);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = if max - min > 0.0 { max - min } else { 1.0 };
+
+    // the label takes the top of the area
+    let plot = [area[0] + 2.0, area[1] + EVENT_LOG_LINE_HEIGHT + 4.0, area[2] - 4.0, area[3] - EVENT_LOG_LINE_HEIGHT - 6.0];
+    let samples = (plot[2] as usize).min(values.len());
+    let step = values.len() as f64 / samples as f64;
+    let point = |index: usize| {
+        let value = values[((index as f64 * step) as usize).min(values.len() - 1)];
+        [
+            plot[0] + index as f64 * plot[2] / (samples - 1).max(1) as f64,
+            plot[1] + plot[3] - (value - min) / range * plot[3],
+        ]
+    };
+
+    let mut previous = point(0);
+    for index in 1..samples {
+        let current = point(index);
+        line(color, 0.7, [previous[0], previous[1], current[0], current[1]], ctx.transform, graphics);
+        previous = current;
+    }
+}
+
+/// Draws the backpack panel: the capacity usage bar followed by one row per content
+/// with its color swatch and quantity. The content changed in the last
+/// `BACKPACK_HIGHLIGHT_TICKS` ticks is highlighted in green (added) or red (removed).
+///
+/// # Arguments
+/// * `backpack` - The backpack to draw.
+/// * `current_tick` - The current tick, used to fade the highlight out.
+/// * `ctx` - The Piston window context.
+/// * `graphics` - The graphics backend.
+/// * `glyphs` - The font glyphs.
+/// * `area` - The panel position and size `[x, y, width, height]`.
+pub fn draw_backpack_panel(
+    backpack: &BackpackInfo,
+    current_tick: usize,
+    ctx: &Context,
+    graphics: &mut G2d,
+    glyphs: &mut Glyphs,
+    area: [f64; 4],
+) {
+    rectangle([0.1, 0.1, 0.1, 0.8], area, ctx.transform, graphics);
+
+    let [x, y, width, height] = area;
+    ProgressBar::new(backpack.size as f64, [x + 4.0, y + EVENT_LOG_LINE_HEIGHT + 2.0, width - 70.0, 8.0])
+        .with_ramp(ColorRamp::new(vec![
+            (0.0, [0.3, 0.7, 1.0, 1.0]),
+            (0.8, [1.0, 0.9, 0.0, 1.0]),
+            (1.0, [1.0, 0.2, 0.2, 1.0]),
+        ]))
+        .with_label("BACKPACK")
+        .draw(backpack.used() as f64, ctx, graphics, Some(&mut *glyphs));
+
+    // content changed recently, with the highlight color
+    let highlight = backpack.last_change.as_ref().and_then(|change| {
+        let age = current_tick.saturating_sub(change.tick);
+        if age >= BACKPACK_HIGHLIGHT_TICKS {
+            return None;
+        }
+        let alpha = 0.6 * (1.0 - age as f32 / BACKPACK_HIGHLIGHT_TICKS as f32);
+        let color = if change.amount >= 0 {
+            [0.2, 0.9, 0.2, alpha]
+        } else {
+            [0.9, 0.2, 0.2, alpha]
+        };
+        Some((&change.content, color))
+    });
+
+    let mut row_y = y + EVENT_LOG_LINE_HEIGHT * 2.0 + 8.0;
+    if backpack.contents.is_empty() {
+        draw_small_text(ctx, graphics, glyphs, [0.6, 0.6, 0.6, 1.0], [x + 4.0, row_y + EVENT_LOG_LINE_HEIGHT - 3.0], "empty");
+    }
+    for (content, quantity) in &backpack.contents {
+        if row_y + EVENT_LOG_LINE_HEIGHT > y + height {
+            break;
+        }
+        if let Some((changed, color)) = highlight {
+            if same_content_kind(changed, content) {
+                rectangle(color, [x + 2.0, row_y, width - 4.0, EVENT_LOG_LINE_HEIGHT], ctx.transform, graphics);
+            }
+        }
+        rectangle(
+            match_content_color_to_type_piston(content),
+            [x + 4.0, row_y + 2.0, 10.0, 10.0],
+            ctx.transform,
+            graphics,
+        );
+        draw_small_text(ctx, graphics, glyphs, [1.0; 4], [x + 20.0, row_y + EVENT_LOG_LINE_HEIGHT - 3.0], content.to_string().as_str());
+        draw_small_text(ctx, graphics, glyphs, [1.0; 4], [x + width - 35.0, row_y + EVENT_LOG_LINE_HEIGHT - 3.0], quantity.to_string().as_str());
+        row_y += EVENT_LOG_LINE_HEIGHT;
+    }
+
+    // a removed content is not listed anymore, show it at the bottom while it is highlighted
+    if let Some((changed, color)) = highlight {
+        if backpack.quantity_of(changed) == 0 && row_y + EVENT_LOG_LINE_HEIGHT <= y + height {
+            rectangle(color, [x + 2.0, row_y, width - 4.0, EVENT_LOG_LINE_HEIGHT], ctx.transform, graphics);
+            draw_small_text(ctx, graphics, glyphs, [0.7, 0.7, 0.7, 1.0], [x + 20.0, row_y + EVENT_LOG_LINE_HEIGHT - 3.0], changed.to_string().as_str());
+            draw_small_text(ctx, graphics, glyphs, [0.7, 0.7, 0.7, 1.0], [x + width - 35.0, row_y + EVENT_LOG_LINE_HEIGHT - 3.0], "0");
+        }
+    }
+}
+
+/// Draws the exploration metrics of the last tick, one value per row, with a bar for the
+/// percentage of discovered tiles.
+///
+/// # Arguments
+/// * `metrics` - The metrics of the last tick.
+/// * `ctx` - The Piston window context.
+/// * `graphics` - The graphics backend.
+/// * `glyphs` - The font glyphs.
+/// * `area` - The panel position and size `[x, y, width, height]`.
+pub fn draw_metrics_panel(
+    metrics: &MetricsSample,
+    ctx: &Context,
+    graphics: &mut G2d,
+    glyphs: &mut Glyphs,
+    area: [f64; 4],
+) {
+    rectangle([0.1, 0.1, 0.1, 0.8], area, ctx.transform, graphics);
+
+    let [x, y, width, _] = area;
+    draw_small_text(ctx, graphics, glyphs, [1.0; 4], [x + 4.0, y + EVENT_LOG_LINE_HEIGHT], "EXPLORATION");
+    let bar_width = (width - 8.0) * (metrics.discovered_percent as f64 / 100.0).clamp(0.0, 1.0);
+    rectangle([0.3, 0.3, 0.3, 1.0], [x + 4.0, y + 19.0, width - 8.0, 4.0], ctx.transform, graphics);
+    rectangle([0.4, 1.0, 0.4, 1.0], [x + 4.0, y + 19.0, bar_width, 4.0], ctx.transform, graphics);
+
+    let mut row_y = y + 23.0 + EVENT_LOG_LINE_HEIGHT;
+    for line in metrics.to_lines() {
+        draw_small_text(ctx, graphics, glyphs, [0.85, 0.85, 0.85, 1.0], [x + 4.0, row_y], line.as_str());
+        row_y += EVENT_LOG_LINE_HEIGHT;
+    }
+}
+
+/// Draws the panel shown over the grid at the end of the run: a title followed by one
+/// line of text per row.
+///
+/// # Arguments
+/// * `title` - The first line, drawn bigger.
+/// * `title_color` - The color of the title.
+/// * `lines` - The rows of the panel.
+/// * `ctx` - The Piston window context.
+/// * `graphics` - The graphics backend.
+/// * `glyphs` - The font glyphs.
+/// * `area` - The panel position and size `[x, y, width, height]`.
+pub fn draw_summary_panel(
+    title: &str,
+    title_color: Color,
+    lines: &[String],
+    ctx: &Context,
+    graphics: &mut G2d,
+    glyphs: &mut Glyphs,
+    area: [f64; 4],
+) {
+    rectangle([0.1, 0.1, 0.1, 0.9], area, ctx.transform, graphics);
+
+    let [x, y, _, height] = area;
+    draw_sized_text(ctx, graphics, glyphs, title_color, 20, [x + 10.0, y + 28.0], title);
+    let mut row_y = y + 28.0 + EVENT_LOG_LINE_HEIGHT * 1.5;
+    for line in lines {
+        if row_y > y + height {
+            break;
+        }
+        draw_sized_text(ctx, graphics, glyphs, [1.0; 4], 13, [x + 10.0, row_y], line);
+        row_y += EVENT_LOG_LINE_HEIGHT + 3.0;
+    }
+}
+
+/// Same as `draw_text` but with the smaller font used by the panels.
+fn draw_small_text(
+    ctx: &Context,
+    graphics: &mut G2d,
+    glyphs: &mut Glyphs,
+    color: Color,
+    pos: [f64; 2],
+    text: &str,
+) {
+    draw_sized_text(ctx, graphics, glyphs, color, EVENT_LOG_FONT_SIZE, pos, text)
+}
+
+/// Same as `draw_text` but with the given font size.
+fn draw_sized_text(
+    ctx: &Context,
+    graphics: &mut G2d,
+    glyphs: &mut Glyphs,
+    color: Color,
+    font_size: u32,
+    pos: [f64; 2],
+    text: &str,
+) {
+    Text::new_color(color, font_size)
+        .draw(
+            text,
+            glyphs,
+            &ctx.draw_state,
+            ctx.transform.trans(pos[0], pos[1]),
+            graphics,
+        )
+        .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_layout_fits_the_default_window() {
+        let layout = HudLayout::default();
+        assert_eq!(layout.grid_pixels, GRID_PIXELS);
+        assert_eq!(layout.backpack, [760.0, 150.0, 185.0, 215.0]);
+        assert_eq!(layout.event_log, [760.0, 495.0, 185.0, 245.0]);
+        assert_eq!(layout.robot_view, [500.0, 765.0, 180.0, 180.0]);
+        assert_eq!(layout.score_chart, [700.0, 860.0, 240.0, 75.0]);
+    }
+
+    #[test]
+    fn layout_follows_the_window_size() {
+        let layout = HudLayout::new(1400.0, 1100.0);
+        assert_eq!(layout.grid_pixels, 900.0);
+        assert_eq!(layout.status, [920.0, 20.0]);
+        assert_eq!(layout.backpack[2], 1400.0 - 910.0 - 5.0);
+        assert!(layout.robot_view[1] >= layout.grid_pixels);
+        assert!(layout.score_chart[1] + layout.score_chart[3] <= 1100.0);
+    }
+
+    fn assert_color_eq(actual: Color, expected: Color) {
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-6, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn ramp_is_clamped_to_its_endpoints() {
+        let ramp = ColorRamp::energy();
+        assert_color_eq(ramp.color_at(0.0), [1.0, 0.0, 0.0, 1.0]);
+        assert_color_eq(ramp.color_at(1.0), [0.0, 0.8, 0.0, 1.0]);
+        assert_color_eq(ramp.color_at(-0.5), [1.0, 0.0, 0.0, 1.0]);
+        assert_color_eq(ramp.color_at(3.0), [0.0, 0.8, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn ramp_interpolates_between_stops_without_jumps() {
+        let ramp = ColorRamp::energy();
+        // exactly on the inner stops
+        assert_color_eq(ramp.color_at(0.3), [1.0, 0.55, 0.0, 1.0]);
+        assert_color_eq(ramp.color_at(0.6), [1.0, 0.9, 0.0, 1.0]);
+        // halfway between two stops
+        assert_color_eq(ramp.color_at(0.8), [0.5, 0.85, 0.0, 1.0]);
+        // both sides of a stop are close to its color
+        for stop in [0.3, 0.6] {
+            let (before, after) = (ramp.color_at(stop - 1e-9), ramp.color_at(stop + 1e-9));
+            for (b, a) in before.iter().zip(after) {
+                assert!((b - a).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn bar_fill_is_proportional_to_its_max() {
+        let bar = ProgressBar::new(250.0, [0.0, 0.0, 100.0, 10.0]);
+        assert_eq!(bar.fill_width(125.0), 50.0);
+        assert_eq!(bar.fill_width(500.0), 100.0);
+        assert_eq!(bar.fill_width(-10.0), 0.0);
+        assert_eq!(ProgressBar::new(0.0, bar.area).fill_width(10.0), 0.0);
+    }
+}
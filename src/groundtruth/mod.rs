@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+use robotics_lib::world::environmental_conditions::EnvironmentalConditions;
+use robotics_lib::world::tile::{Content, Tile};
+use robotics_lib::world::world_generator::Generator;
+
+use crate::state::ColorMatrix;
+use crate::util::{match_color_to_type_piston, match_content_color_to_type_piston};
+
+///# Constants
+///* `GROUND_TRUTH_OPACITY`: The opacity of the real world drawn under the discovered map.
+pub const GROUND_TRUTH_OPACITY: f32 = 0.35;
+
+/// A generator handing to the `Runner` the world of the wrapped one, keeping a copy of the
+/// tiles it returned: the ground truth is built from the exact world the robot runs in,
+/// without generating it again.
+///
+/// Attributes:
+/// - `generator`: The generator of the world.
+/// - `tiles`: The tiles returned by the last call of `gen`, until they are taken.
+pub struct CapturingGenerator<G: Generator> {
+    generator: G,
+    tiles: Option<Vec<Vec<Tile>>>,
+}
+
+impl<G: Generator> CapturingGenerator<G> {
+    pub fn new(generator: G) -> Self {
+        Self { generator, tiles: None }
+    }
+
+    /// The tiles of the generated world, `None` if the world wasn't generated yet.
+    pub fn take_tiles(&mut self) -> Option<Vec<Vec<Tile>>> {
+        self.tiles.take()
+    }
+}
+
+impl<G: Generator> Generator for CapturingGenerator<G> {
+    fn gen(
+        &mut self,
+    ) -> (
+        Vec<Vec<Tile>>,
+        (usize, usize),
+        EnvironmentalConditions,
+        f32,
+        Option<HashMap<Content, f32>>,
+    ) {
+        let world = self.generator.gen();
+        self.tiles = Some(world.0.clone());
+        world
+    }
+}
+
+/// The tiles changed during the run, with their `(row, col)` coordinates, in the order of the
+/// `TileContentUpdated` events: filled by the robot and taken by the runner thread.
+pub type TileUpdates = Vec<((usize, usize), Tile)>;
+
+/// The tiles of the real world, with their contents, drawn under the discovered map to
+/// debug what the robot doesn't know yet.
+///
+/// The tiles are the ones the `Runner` received from its generator (see `CapturingGenerator`),
+/// then the tiles changed during the run (the `TileContentUpdated` events of the robot, see
+/// `TileUpdates`) are applied with `update_tile`, and the truth is published again.
+///
+/// Attributes:
+/// - `tile_colors`: The tile type colors of the world, indexed `[row][col]`.
+/// - `content_colors`: The content colors of the world, transparent where there is no content.
+#[derive(Clone, Debug)]
+pub struct GroundTruth {
+    tile_colors: ColorMatrix,
+    content_colors: ColorMatrix,
+}
+
+impl GroundTruth {
+    /// # Arguments
+    /// * `tiles` - The tiles of the world, indexed `[row][col]`.
+    pub fn new(tiles: &[Vec<Tile>]) -> Self {
+        Self {
+            tile_colors: tiles
+                .iter()
+                .map(|row| row.iter().map(|tile| match_color_to_type_piston(&tile.tile_type)).collect())
+                .collect(),
+            content_colors: tiles
+                .iter()
+                .map(|row| row.iter().map(|tile| match_content_color_to_type_piston(&tile.content)).collect())
+                .collect(),
+        }
+    }
+
+    /// Replaces the colors of a tile changed during the run, the coordinates outside the
+    /// world are ignored.
+    ///
+    /// # Arguments
+    /// * `coordinates` - The `(row, col)` of the tile.
+    /// * `tile` - The tile after the change.
+    pub fn update_tile(&mut self, (row, col): (usize, usize), tile: &Tile) {
+        if let Some(color) = self.tile_colors.get_mut(row).and_then(|colors| colors.get_mut(col)) {
+            *color = match_color_to_type_piston(&tile.tile_type);
+        }
+        if let Some(color) = self.content_colors.get_mut(row).and_then(|colors| colors.get_mut(col)) {
+            *color = match_content_color_to_type_piston(&tile.content);
+        }
+    }
+
+    pub fn map_size(&self) -> usize {
+        self.tile_colors.len()
+    }
+
+    pub fn tile_colors(&self) -> &ColorMatrix {
+        &self.tile_colors
+    }
+
+    pub fn content_colors(&self) -> &ColorMatrix {
+        &self.content_colors
+    }
+
+    /// The colors of the world with the given opacity, to be drawn under the discovered map:
+    /// the color of the content where there is one, the color of the tile type elsewhere.
+    pub fn faded(&self, opacity: f32) -> ColorMatrix {
+        self.tile_colors
+            .iter()
+            .zip(&self.content_colors)
+            .map(|(tiles, contents)| {
+                tiles
+                    .iter()
+                    .zip(contents)
+                    .map(|(tile, content)| {
+                        let color = if content[3] > 0.0 { content } else { tile };
+                        [color[0], color[1], color[2], color[3] * opacity]
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The amount of tiles of the world missing from the discovered map.
+    ///
+    /// # Arguments
+    /// * `discovered` - The discovered map, a tile is discovered if it is not transparent.
+    pub fn unknown_tiles(&self, discovered: &ColorMatrix) -> usize {
+        self.tile_colors
+            .iter()
+            .enumerate()
+            .map(|(row, colors)| {
+                (0..colors.len())
+                    .filter(|col| {
+                        discovered
+                            .get(row)
+                            .and_then(|discovered_row| discovered_row.get(*col))
+                            .map_or(true, |color| color[3] == 0.0)
+                    })
+                    .count()
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use robotics_lib::world::environmental_conditions::WeatherType;
+    use robotics_lib::world::tile::TileType;
+
+    use super::*;
+
+    fn tile(tile_type: TileType) -> Tile {
+        Tile {
+            tile_type,
+            content: Content::None,
+            elevation: 0,
+        }
+    }
+
+    struct OnceGenerator(Option<Vec<Vec<Tile>>>);
+
+    impl Generator for OnceGenerator {
+        fn gen(
+            &mut self,
+        ) -> (
+            Vec<Vec<Tile>>,
+            (usize, usize),
+            EnvironmentalConditions,
+            f32,
+            Option<HashMap<Content, f32>>,
+        ) {
+            //a second call would panic: the world must be generated only once
+            let tiles = self.0.take().unwrap();
+            let conditions = EnvironmentalConditions::new(&[WeatherType::Sunny], 15, 12).unwrap();
+            (tiles, (0, 0), conditions, 0.0, None)
+        }
+    }
+
+    #[test]
+    fn faded_world_keeps_the_tile_colors() {
+        let truth = GroundTruth::new(&[vec![tile(TileType::Grass), tile(TileType::DeepWater)]]);
+        let faded = truth.faded(0.5);
+        assert_eq!(faded[0][1][..3], match_color_to_type_piston(&TileType::DeepWater)[..3]);
+        assert_eq!(faded[0][0][3], match_color_to_type_piston(&TileType::Grass)[3] * 0.5);
+    }
+
+    #[test]
+    fn captured_tiles_are_the_generated_ones_with_their_contents() {
+        let mut rock = tile(TileType::Grass);
+        rock.content = Content::Rock(3);
+        let world = vec![vec![rock, tile(TileType::Sand)]];
+        let mut generator = CapturingGenerator::new(OnceGenerator(Some(world.clone())));
+        assert!(generator.take_tiles().is_none());
+
+        let generated = generator.gen().0;
+        let truth = GroundTruth::new(&generator.take_tiles().unwrap());
+        assert_eq!(generated.len(), world.len());
+        assert_eq!(truth.content_colors()[0][0], match_content_color_to_type_piston(&Content::Rock(3)));
+        assert_eq!(truth.faded(1.0)[0][0], match_content_color_to_type_piston(&Content::Rock(3)));
+        assert_eq!(truth.faded(1.0)[0][1], match_color_to_type_piston(&TileType::Sand));
+    }
+
+    #[test]
+    fn updated_tiles_replace_the_generated_ones() {
+        let mut rock = tile(TileType::Grass);
+        rock.content = Content::Rock(3);
+        let mut truth = GroundTruth::new(&[vec![rock, tile(TileType::Sand)]]);
+        truth.update_tile((0, 0), &tile(TileType::Grass));
+        truth.update_tile((0, 1), &tile(TileType::Street));
+        truth.update_tile((5, 5), &tile(TileType::Lava));
+        assert_eq!(truth.faded(1.0)[0][0], match_color_to_type_piston(&TileType::Grass));
+        assert_eq!(truth.faded(1.0)[0][1], match_color_to_type_piston(&TileType::Street));
+        assert_eq!(truth.map_size(), 1);
+    }
+
+    #[test]
+    fn unknown_tiles_are_the_undiscovered_ones() {
+        let truth = GroundTruth::new(&vec![vec![tile(TileType::Sand); 2]; 2]);
+        let discovered = vec![vec![[1.0; 4], [0.0; 4]], vec![[0.0; 4], [1.0; 4]]];
+        assert_eq!(truth.unknown_tiles(&discovered), 2);
+        assert_eq!(truth.map_size(), 2);
+    }
+}
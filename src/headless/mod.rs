@@ -0,0 +1,208 @@
+use std::fs;
+use std::path::PathBuf;
+
+use log::info;
+use robotics_lib::runner::Runner;
+use robotics_lib::world::world_generator::Generator;
+use serde::Serialize;
+
+use crate::lifecycle::export_run;
+use crate::registry::VisualizedRobot;
+use crate::replay::ReplayRecorder;
+use crate::state::VisualizerState;
+
+///# Constants
+///* `HISTORY_FILE`, `REPLAY_FILE`, `SUMMARY_FILE`, `METRICS_FILE`: Names of the files written in the output directory.
+///* `FRAMES_DIR`, `VIDEO_FILE`: Where the frames and the video made from them are written in the output directory.
+pub const HISTORY_FILE: &str = "history.csv";
+pub const REPLAY_FILE: &str = "replay.json";
+pub const SUMMARY_FILE: &str = "summary.json";
+pub const METRICS_FILE: &str = "metrics.csv";
+pub const FRAMES_DIR: &str = "frames";
+pub const VIDEO_FILE: &str = "run.mp4";
+
+/// Options of a run without window.
+///
+/// Attributes:
+/// - `ticks`: The amount of ticks to run.
+/// - `output_dir`: The directory where history, replay, summary and metrics are written.
+/// - `gif`: If the saved frames are also converted to a gif (needs ffmpeg on the PATH).
+#[derive(Clone, Debug)]
+pub struct HeadlessOptions {
+    pub ticks: usize,
+    pub output_dir: PathBuf,
+    pub gif: bool,
+}
+
+/// The final values of a run, written as `summary.json`.
+///
+/// Attributes:
+/// - `ticks`: The amount of ticks that were run.
+/// - `energy`, `score`: Energy and score at the end of the run.
+/// - `coordinates`: The final coordinates of the robot as (row, col).
+/// - `discovered_tiles`, `total_tiles`: How much of the map the robot discovered.
+/// - `backpack_used`: The amount of items in the backpack at the end of the run.
+#[derive(Clone, Debug, Serialize)]
+pub struct RunSummary {
+    pub ticks: usize,
+    pub energy: usize,
+    pub score: f32,
+    pub coordinates: (usize, usize),
+    pub discovered_tiles: usize,
+    pub total_tiles: usize,
+    pub backpack_used: usize,
+}
+
+impl RunSummary {
+    /// # Arguments
+    /// * `state` - The last state of the run.
+    /// * `ticks` - The ticks counted by the runner, the history of the state is empty for the
+    ///   robots that don't record it.
+    pub fn from_state(state: &VisualizerState, ticks: usize) -> Self {
+        let tiles = state.tile_colors.iter().flatten();
+        Self {
+            ticks,
+            energy: state.energy,
+            score: state.score,
+            coordinates: state.coordinates,
+            discovered_tiles: tiles.clone().filter(|color| color[3] > 0.0).count(),
+            total_tiles: tiles.count(),
+            backpack_used: state.backpack.used(),
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        format!(
+            "{} ticks, energy {}, score {}, discovered {}/{} tiles, robot at ({},{})",
+            self.ticks,
+            self.energy,
+            self.score,
+            self.discovered_tiles,
+            self.total_tiles,
+            self.coordinates.1,
+            self.coordinates.0
+        )
+    }
+
+    /// The summary split in rows, as shown by the window at the end of the run.
+    pub fn to_lines(&self) -> Vec<String> {
+        vec![
+            format!("ticks: {}", self.ticks),
+            format!("energy: {}", self.energy),
+            format!("score: {}", self.score),
+            format!("discovered tiles: {}/{}", self.discovered_tiles, self.total_tiles),
+            format!("items in the backpack: {}", self.backpack_used),
+            format!("robot coordinates: ({},{})", self.coordinates.1, self.coordinates.0),
+        ]
+    }
+}
+
+/// Runs the robot without opening a window until `options.ticks` ticks are done,
+/// then writes the history, the replay, the summary and the metrics in `options.output_dir`.
+/// The frames saved by the robot and the video are written in `options.output_dir` too.
+///
+/// # Arguments
+/// * `robot` - The robot to run, e.g. built by the `RobotRegistry`.
+/// * `generator` - The generator of the world.
+/// * `map_size` - The size of the world.
+/// * `options` - The options of the run.
+///
+/// # Returns
+/// The summary of the run, or the first error that stopped the run or an export.
+pub fn run_headless(robot: VisualizedRobot, generator: &mut impl Generator, map_size: usize, options: &HeadlessOptions) -> Result<RunSummary, String> {
+    fs::create_dir_all(&options.output_dir)
+        .map_err(|e| format!("Error creating '{}': {}", options.output_dir.display(), e))?;
+
+    let VisualizedRobot {
+        runnable,
+        collector,
+        init_frames,
+        history,
+        metrics,
+        tile_updates,
+    } = robot;
+    let output = |name: &str| options.output_dir.join(name).display().to_string();
+    fs::create_dir_all(output(FRAMES_DIR)).map_err(|e| format!("Error creating '{}': {}", output(FRAMES_DIR), e))?;
+    init_frames
+        .lock()
+        .map_err(|e| format!("Couldnt lock INIT_FRAMES: {}", e))?
+        .set_output(&output(FRAMES_DIR), &output(VIDEO_FILE));
+    let mut recorder = ReplayRecorder::new(map_size);
+    let mut runner = Runner::new(runnable, generator).map_err(|e| format!("Error creating the runner: {:?}", e))?;
+
+    //the ticks are counted here, the history only grows if the robot records it
+    let mut state = collector.collect();
+    for tick in 1..=options.ticks {
+        runner.game_tick().map_err(|e| format!("Error at tick {}: {:?}", tick, e))?;
+        //there is no ground truth to update without the window
+        tile_updates.lock().map_err(|e| format!("Couldnt lock TILE_UPDATES: {}", e))?.clear();
+        state = collector.collect();
+        recorder.record_tick(tick, &state);
+        if tick % 100 == 0 {
+            info!("tick {}/{}", tick, options.ticks);
+        }
+    }
+
+    recorder.replay().save(&output(REPLAY_FILE))?;
+    let summary = RunSummary::from_state(&state, options.ticks);
+    let summary_json = serde_json::to_string_pretty(&summary).map_err(|e| e.to_string())?;
+    fs::write(output(SUMMARY_FILE), summary_json).map_err(|e| format!("Error writing '{}': {}", output(SUMMARY_FILE), e))?;
+    //the state only has the tail of the history, the whole one is exported like in the windowed runs
+    export_run(
+        &history,
+        &init_frames,
+        &output(HISTORY_FILE),
+        &metrics,
+        &output(METRICS_FILE),
+        options.gif,
+    )?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::registry::{RobotRegistry, RobotSettings};
+    use crate::worldgen::{ContentDensities, SeededGenerator, WorldPreset};
+
+    use super::*;
+
+    #[test]
+    fn summary_counts_the_discovered_tiles() {
+        let mut state = VisualizerState::new(2);
+        state.tile_colors = vec![vec![[1.0; 4], [0.0; 4]], vec![[0.0; 4], [0.0; 4]]];
+        state.energy = 300;
+        state.coordinates = (1, 0);
+
+        let summary = RunSummary::from_state(&state, 7);
+        assert_eq!((summary.discovered_tiles, summary.total_tiles), (1, 4));
+        let lines = summary.to_lines();
+        assert_eq!(lines[0], "ticks: 7");
+        assert_eq!(lines[1], "energy: 300");
+        assert_eq!(lines[3], "discovered tiles: 1/4");
+        assert_eq!(lines[5], "robot coordinates: (0,1)");
+    }
+
+    #[test]
+    fn headless_run_writes_every_file() {
+        let output_dir = std::env::temp_dir().join(format!("visualizer-headless-{}", std::process::id()));
+        let settings = RobotSettings {
+            record_frames: false,
+            ..RobotSettings::default()
+        };
+        let robot = RobotRegistry::with_defaults().create("example", &settings, 15).unwrap();
+        let mut generator = SeededGenerator::new(WorldPreset::Flat, 15, 1, ContentDensities::default()).unwrap();
+        let options = HeadlessOptions {
+            ticks: 3,
+            output_dir: output_dir.clone(),
+            gif: false,
+        };
+
+        let summary = run_headless(robot, &mut generator, 15, &options).unwrap();
+        assert_eq!(summary.ticks, 3);
+        for file in [HISTORY_FILE, REPLAY_FILE, SUMMARY_FILE, METRICS_FILE, FRAMES_DIR] {
+            assert!(output_dir.join(file).exists(), "{} is missing", file);
+        }
+        assert!(!output_dir.join(VIDEO_FILE).exists());
+        let _ = fs::remove_dir_all(&output_dir);
+    }
+}
@@ -0,0 +1,134 @@
+use std::fs;
+
+/// Energy and score of the robot at the end of every tick, indexed by tick.
+///
+/// Attributes:
+/// - `first_tick`: The tick of the first sample kept, 0 unless the history is a `tail`.
+/// - `energy`, `score`: The samples kept, starting from `first_tick`.
+#[derive(Clone, Debug, Default)]
+pub struct RunHistory {
+    first_tick: usize,
+    energy: Vec<usize>,
+    score: Vec<f32>,
+}
+
+impl RunHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the values of a tick. Ticks that were skipped are filled with the
+    /// previous values, so that the index of each sample is always its tick.
+    ///
+    /// # Arguments
+    /// * `tick` - The tick the values refer to.
+    /// * `energy` - The energy of the robot at the end of the tick.
+    /// * `score` - The score at the end of the tick.
+    pub fn record(&mut self, tick: usize, energy: usize, score: f32) {
+        //the ticks before the tail are gone
+        let Some(index) = tick.checked_sub(self.first_tick) else {
+            return;
+        };
+        if index < self.energy.len() {
+            self.energy[index] = energy;
+            self.score[index] = score;
+            return;
+        }
+        while self.energy.len() < index {
+            let last_energy = self.energy.last().copied().unwrap_or(energy);
+            let last_score = self.score.last().copied().unwrap_or(score);
+            self.energy.push(last_energy);
+            self.score.push(last_score);
+        }
+        self.energy.push(energy);
+        self.score.push(score);
+    }
+
+    /// The last `max_ticks` ticks of the history, sent to the renderers instead of the
+    /// whole history; `len` still counts every tick.
+    pub fn tail(&self, max_ticks: usize) -> Self {
+        let start = self.energy.len().saturating_sub(max_ticks);
+        Self {
+            first_tick: self.first_tick + start,
+            energy: self.energy[start..].to_vec(),
+            score: self.score[start..].to_vec(),
+        }
+    }
+
+    /// The energy of the ticks kept, starting from the first one (see `tail`).
+    pub fn energy(&self) -> &Vec<usize> {
+        &self.energy
+    }
+
+    /// The score of the ticks kept, starting from the first one (see `tail`).
+    pub fn score(&self) -> &Vec<f32> {
+        &self.score
+    }
+
+    /// The amount of ticks recorded, including the ones before the tail.
+    pub fn len(&self) -> usize {
+        self.first_tick + self.energy.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Converts the history to csv, one line per tick (`tick,energy,score`).
+    pub fn to_csv(&self) -> String {
+        let mut result = String::from("tick,energy,score\n");
+        for (tick, (energy, score)) in self.energy.iter().zip(self.score.iter()).enumerate() {
+            result += &format!("{},{},{}\n", self.first_tick + tick, energy, score);
+        }
+        result
+    }
+
+    /// Writes the history as csv to the given path.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the csv file.
+    ///
+    /// # Returns
+    /// A result indicating the success or failure of the export.
+    pub fn export_csv(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_csv()).map_err(|e| format!("Error writing '{}': {}", path, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skipped_ticks_are_filled_and_old_ticks_overwritten() {
+        let mut history = RunHistory::new();
+        history.record(0, 100, 0.0);
+        history.record(3, 70, 5.0);
+        assert_eq!(history.energy(), &vec![100, 100, 100, 70]);
+        assert_eq!(history.score(), &vec![0.0, 0.0, 0.0, 5.0]);
+
+        history.record(1, 90, 1.0);
+        assert_eq!(history.energy(), &vec![100, 90, 100, 70]);
+        assert_eq!(history.score(), &vec![0.0, 1.0, 0.0, 5.0]);
+        assert_eq!(history.len(), 4);
+    }
+
+    #[test]
+    fn tail_keeps_the_tick_numbers() {
+        let mut history = RunHistory::new();
+        for tick in 0..5 {
+            history.record(tick, 100 - tick, tick as f32);
+        }
+        assert_eq!(history.to_csv(), "tick,energy,score\n0,100,0\n1,99,1\n2,98,2\n3,97,3\n4,96,4\n");
+
+        let mut tail = history.tail(2);
+        assert_eq!(tail.len(), 5);
+        assert_eq!(tail.energy(), &vec![97, 96]);
+        assert_eq!(tail.to_csv(), "tick,energy,score\n3,97,3\n4,96,4\n");
+
+        tail.record(1, 0, 0.0);
+        tail.record(5, 95, 5.0);
+        assert_eq!(tail.energy(), &vec![97, 96, 95]);
+        assert_eq!(history.tail(10).energy(), history.energy());
+    }
+}
@@ -5,3 +5,43 @@ pub mod util;
 pub mod grid;
 
 pub mod robot;
+
+pub mod events;
+
+pub mod trace;
+
+pub mod history;
+
+pub mod state;
+
+pub mod terminal;
+
+pub mod replay;
+
+pub mod headless;
+
+pub mod config;
+
+pub mod worldgen;
+
+pub mod worldfile;
+
+pub mod worldimage;
+
+pub mod registry;
+
+pub mod behavior;
+
+pub mod control;
+
+pub mod lifecycle;
+
+pub mod audio;
+
+pub mod multi;
+
+pub mod compare;
+pub mod metrics;
+pub mod fog;
+pub mod groundtruth;
+pub mod teleport;
@@ -0,0 +1,225 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use log::{error, info};
+
+use crate::frame::Frames;
+use crate::history::RunHistory;
+use crate::metrics::ExplorationMetrics;
+
+/// The phases of a run, in order.
+///
+/// - `Running`: The robot is moving.
+/// - `Exporting`: The run is over and the history, the metrics (and the gif) are being written.
+/// - `Finished`: Everything is exported, the window shows the summary of the run.
+/// - `Failed`: The run stopped because of the given error.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum RunPhase {
+    #[default]
+    Running,
+    Exporting,
+    Finished,
+    Failed(String),
+}
+
+impl RunPhase {
+    /// If the run is over and nothing is left to do but showing the result.
+    pub fn is_over(&self) -> bool {
+        matches!(self, RunPhase::Finished | RunPhase::Failed(_))
+    }
+}
+
+/// What the worker threads share: the shutdown signal, the phase of the run and the
+/// amount of ticks done, counted by the runner since not every robot records its history.
+#[derive(Clone, Default)]
+pub struct LifecycleHandle {
+    shutdown: Arc<AtomicBool>,
+    phase: Arc<Mutex<RunPhase>>,
+    ticks: Arc<AtomicUsize>,
+}
+
+impl LifecycleHandle {
+    /// Asks every worker thread to stop at the next iteration.
+    pub fn request_shutdown(&self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutdown.load(Ordering::SeqCst)
+    }
+
+    pub fn phase(&self) -> RunPhase {
+        match self.phase.lock() {
+            Ok(phase) => phase.clone(),
+            Err(e) => RunPhase::Failed(format!("Couldnt lock RUN_PHASE: {}", e)),
+        }
+    }
+
+    pub fn set_phase(&self, phase: RunPhase) {
+        match self.phase.lock() {
+            Ok(mut lock) => *lock = phase,
+            Err(e) => error!("Couldnt lock RUN_PHASE: {}", e),
+        }
+    }
+
+    /// The amount of ticks the runner has done.
+    pub fn ticks(&self) -> usize {
+        self.ticks.load(Ordering::SeqCst)
+    }
+
+    pub fn set_ticks(&self, ticks: usize) {
+        self.ticks.store(ticks, Ordering::SeqCst);
+    }
+}
+
+/// Owns the worker threads of the visualizer (runner, sender, ...) so that they are all
+/// stopped and joined when the window or the terminal closes.
+///
+/// Attributes:
+/// - `handle`: The state shared with the workers.
+/// - `workers`: The name and the handle of every spawned thread.
+pub struct Lifecycle {
+    handle: LifecycleHandle,
+    workers: Vec<(String, JoinHandle<()>)>,
+}
+
+impl Lifecycle {
+    pub fn new() -> Self {
+        Self {
+            handle: LifecycleHandle::default(),
+            workers: Vec::new(),
+        }
+    }
+
+    pub fn handle(&self) -> LifecycleHandle {
+        self.handle.clone()
+    }
+
+    pub fn phase(&self) -> RunPhase {
+        self.handle.phase()
+    }
+
+    pub fn ticks(&self) -> usize {
+        self.handle.ticks()
+    }
+
+    /// Spawns a named worker thread. The worker must return once
+    /// `LifecycleHandle::is_shutting_down` is true.
+    pub fn spawn<F>(&mut self, name: &str, worker: F)
+    where
+        F: FnOnce(LifecycleHandle) + Send + 'static,
+    {
+        let handle = self.handle.clone();
+        match thread::Builder::new().name(name.to_string()).spawn(move || worker(handle)) {
+            Ok(join_handle) => self.workers.push((name.to_string(), join_handle)),
+            Err(e) => error!("Couldnt spawn the {} thread: {}", name, e),
+        }
+    }
+
+    /// Signals the shutdown and waits for every worker to return.
+    pub fn shutdown(self) {
+        self.handle.request_shutdown();
+        for (name, worker) in self.workers {
+            info!("waiting for the {} thread", name);
+            if worker.join().is_err() {
+                error!("the {} thread panicked", name);
+            }
+        }
+    }
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Writes what is kept at the end of a run: the history and the metrics as csv and, if
+/// requested, the gif.
+///
+/// The frames are taken out of `init_frames` before being converted, so the lock is not
+/// held while waiting for ffmpeg.
+///
+/// # Arguments
+/// * `history` - The history of the run.
+/// * `init_frames` - The frames saved during the run.
+/// * `history_path` - Where the history csv is written.
+/// * `metrics` - The exploration metrics of the run.
+/// * `metrics_path` - Where the metrics csv is written.
+/// * `make_gif` - If the frames are converted to a gif.
+///
+/// # Returns
+/// The first error, after trying every export.
+pub fn export_run(
+    history: &Arc<Mutex<RunHistory>>,
+    init_frames: &Arc<Mutex<Frames>>,
+    history_path: &str,
+    metrics: &Arc<Mutex<ExplorationMetrics>>,
+    metrics_path: &str,
+    make_gif: bool,
+) -> Result<(), String> {
+    let history_result = match history.lock() {
+        Ok(lock) => lock
+            .export_csv(history_path)
+            .map_err(|e| format!("error exporting the history: {}", e)),
+        Err(e) => Err(format!("Couldnt lock HISTORY implies impossible to export it: {}", e)),
+    };
+    let metrics_result = match metrics.lock() {
+        Ok(lock) => lock
+            .export_csv(metrics_path)
+            .map_err(|e| format!("error exporting the metrics: {}", e)),
+        Err(e) => Err(format!("Couldnt lock METRICS implies impossible to export them: {}", e)),
+    };
+    let history_result = history_result.and(metrics_result);
+    if !make_gif {
+        return history_result;
+    }
+
+    let frames = match init_frames.lock() {
+        Ok(mut lock) => lock.take(),
+        Err(e) => return history_result.and(Err(format!("Couldnt lock INIT_FRAMES implies impossible to create a gif: {}", e))),
+    };
+    let gif_result = frames
+        .convert_frames_to_gif()
+        .map_err(|e| format!("error creating the gif: {}", e));
+    history_result.and(gif_result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn shutdown_joins_the_workers() {
+        let mut lifecycle = Lifecycle::new();
+        let stopped = Arc::new(AtomicBool::new(false));
+        let worker_stopped = stopped.clone();
+        lifecycle.spawn("test", move |handle| {
+            while !handle.is_shutting_down() {
+                thread::sleep(Duration::from_millis(1));
+            }
+            worker_stopped.store(true, Ordering::SeqCst);
+        });
+        lifecycle.shutdown();
+        assert!(stopped.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn ticks_are_shared_with_the_workers() {
+        let mut lifecycle = Lifecycle::new();
+        lifecycle.spawn("runner", |handle| handle.set_ticks(42));
+        let handle = lifecycle.handle();
+        lifecycle.shutdown();
+        assert_eq!(handle.ticks(), 42);
+    }
+
+    #[test]
+    fn only_finished_and_failed_runs_are_over() {
+        assert!(!RunPhase::Running.is_over());
+        assert!(!RunPhase::Exporting.is_over());
+        assert!(RunPhase::Finished.is_over());
+        assert!(RunPhase::Failed(String::from("error")).is_over());
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::HashSet;
+use std::fs;
+
+use robotics_lib::event::events::Event;
+use robotics_lib::world::tile::Tile;
+use serde::Serialize;
+
+/// The exploration metrics at the end of a tick.
+///
+/// Attributes:
+/// - `tick`: The tick the values refer to.
+/// - `discovered_tiles`: Tiles of the discovered map.
+/// - `discovered_percent`: `discovered_tiles` over the tiles of the world, from 0 to 100.
+/// - `energy_spent`: Energy consumed since the start of the run.
+/// - `discoveries_per_energy`: `discovered_tiles` over `energy_spent`.
+/// - `visited_tiles`: Distinct tiles the robot stepped on.
+/// - `revisits`: Moves onto a tile that was already visited.
+/// - `content_collected`: Items added to the backpack since the start of the run.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct MetricsSample {
+    pub tick: usize,
+    pub discovered_tiles: usize,
+    pub discovered_percent: f32,
+    pub energy_spent: usize,
+    pub discoveries_per_energy: f32,
+    pub visited_tiles: usize,
+    pub revisits: usize,
+    pub content_collected: usize,
+}
+
+impl MetricsSample {
+    /// The rows of the metrics panel of the window.
+    pub fn to_lines(&self) -> Vec<String> {
+        vec![
+            format!("discovered {} ({:.1}%)", self.discovered_tiles, self.discovered_percent),
+            format!("per energy {:.2}", self.discoveries_per_energy),
+            format!("visited {}  revisits {}", self.visited_tiles, self.revisits),
+            format!("collected {}", self.content_collected),
+            format!("energy spent {}", self.energy_spent),
+        ]
+    }
+}
+
+/// Computes the exploration metrics of a run: moves, consumed energy and collected contents
+/// are counted from the events, the discovered tiles from the robot map at the end of each tick.
+///
+/// Attributes:
+/// - `total_tiles`: The tiles of the world.
+/// - `visited`: The tiles the robot stepped on, as (row, col).
+/// - `position`: The last position of the robot, a move onto the same tile is not a revisit.
+/// - `revisits`, `energy_spent`, `content_collected`: The counters since the start of the run.
+/// - `samples`: One sample per tick, indexed by tick.
+#[derive(Clone, Debug, Default)]
+pub struct ExplorationMetrics {
+    total_tiles: usize,
+    visited: HashSet<(usize, usize)>,
+    position: Option<(usize, usize)>,
+    revisits: usize,
+    energy_spent: usize,
+    content_collected: usize,
+    samples: Vec<MetricsSample>,
+}
+
+impl ExplorationMetrics {
+    pub fn new(map_size: usize) -> Self {
+        Self {
+            total_tiles: map_size * map_size,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the size of the world, used for the percentage of discovered tiles; the metrics
+    /// recorded so far are kept.
+    ///
+    /// # Arguments
+    /// * `map_size` - The size of the (square) world.
+    pub fn set_map_size(&mut self, map_size: usize) {
+        self.total_tiles = map_size * map_size;
+    }
+
+    /// Counts the moves, the consumed energy and the collected contents.
+    pub fn record_event(&mut self, event: &Event) {
+        match event {
+            Event::Moved(_, coordinates) => self.visit(*coordinates),
+            Event::EnergyConsumed(amount) => self.energy_spent += amount,
+            Event::AddedToBackpack(_, amount) => self.content_collected += amount,
+            _ => {}
+        }
+    }
+
+    /// Marks the tile the robot is on as visited.
+    ///
+    /// # Arguments
+    /// * `coordinates` - The position of the robot as (row, col).
+    pub fn visit(&mut self, coordinates: (usize, usize)) {
+        if self.position == Some(coordinates) {
+            return;
+        }
+        if !self.visited.insert(coordinates) {
+            self.revisits += 1;
+        }
+        self.position = Some(coordinates);
+    }
+
+    /// Stores the sample of a tick; a sample of the same tick is replaced.
+    ///
+    /// # Arguments
+    /// * `tick` - The tick that ended.
+    /// * `coordinates` - The position of the robot, so that the spawn tile is visited too.
+    /// * `discovered_tiles` - The tiles of the discovered map (see `count_discovered`).
+    pub fn end_tick(&mut self, tick: usize, coordinates: (usize, usize), discovered_tiles: usize) {
+        self.visit(coordinates);
+        let sample = MetricsSample {
+            tick,
+            discovered_tiles,
+            discovered_percent: if self.total_tiles == 0 {
+                0.0
+            } else {
+                100.0 * discovered_tiles as f32 / self.total_tiles as f32
+            },
+            energy_spent: self.energy_spent,
+            discoveries_per_energy: if self.energy_spent == 0 {
+                0.0
+            } else {
+                discovered_tiles as f32 / self.energy_spent as f32
+            },
+            visited_tiles: self.visited.len(),
+            revisits: self.revisits,
+            content_collected: self.content_collected,
+        };
+        match self.samples.last_mut() {
+            Some(last) if last.tick == tick => *last = sample,
+            _ => self.samples.push(sample),
+        }
+    }
+
+    /// The sample of the last tick, empty before the first tick ends.
+    pub fn current(&self) -> MetricsSample {
+        self.samples.last().cloned().unwrap_or_default()
+    }
+
+    pub fn samples(&self) -> &Vec<MetricsSample> {
+        &self.samples
+    }
+
+    /// Converts the samples to csv, one line per tick.
+    pub fn to_csv(&self) -> String {
+        let mut result = String::from(
+            "tick,discovered_tiles,discovered_percent,energy_spent,discoveries_per_energy,visited_tiles,revisits,content_collected\n",
+        );
+        for sample in &self.samples {
+            result += &format!(
+                "{},{},{},{},{},{},{},{}\n",
+                sample.tick,
+                sample.discovered_tiles,
+                sample.discovered_percent,
+                sample.energy_spent,
+                sample.discoveries_per_energy,
+                sample.visited_tiles,
+                sample.revisits,
+                sample.content_collected
+            );
+        }
+        result
+    }
+
+    /// Writes the samples as csv to the given path.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the csv file.
+    pub fn export_csv(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_csv()).map_err(|e| format!("Error writing '{}': {}", path, e))
+    }
+}
+
+/// The amount of discovered tiles of a robot map.
+pub fn count_discovered(robot_map: &Option<Vec<Vec<Option<Tile>>>>) -> usize {
+    robot_map
+        .as_ref()
+        .map(|map| map.iter().flatten().filter(|tile| tile.is_some()).count())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moving_back_to_a_tile_is_a_revisit() {
+        let mut metrics = ExplorationMetrics::new(10);
+        metrics.end_tick(0, (0, 0), 9);
+        metrics.visit((0, 1));
+        metrics.visit((0, 0));
+        metrics.end_tick(1, (0, 0), 12);
+
+        let sample = metrics.current();
+        assert_eq!(sample.visited_tiles, 2);
+        assert_eq!(sample.revisits, 1);
+        assert_eq!(sample.discovered_percent, 12.0);
+        assert_eq!(metrics.samples().len(), 2);
+    }
+
+    #[test]
+    fn discoveries_are_divided_by_the_energy_spent() {
+        let mut metrics = ExplorationMetrics::new(10);
+        metrics.record_event(&Event::EnergyConsumed(4));
+        metrics.record_event(&Event::EnergyConsumed(6));
+        metrics.end_tick(0, (0, 0), 25);
+        assert_eq!(metrics.current().discoveries_per_energy, 2.5);
+        assert!(metrics.to_csv().ends_with("0,25,25,10,2.5,1,0,0\n"));
+    }
+
+    #[test]
+    fn map_size_set_later_is_used_for_the_percentage() {
+        let mut metrics = ExplorationMetrics::default();
+        metrics.record_event(&Event::EnergyConsumed(5));
+        metrics.set_map_size(4);
+        metrics.end_tick(0, (0, 0), 4);
+        assert_eq!(metrics.current().discovered_percent, 25.0);
+        assert_eq!(metrics.current().discoveries_per_energy, 0.8);
+    }
+}
@@ -0,0 +1,259 @@
+use std::path::{Path, PathBuf};
+
+use crate::grid::ROBOT_COLOR;
+use crate::state::{ColorMatrix, VisualizerState};
+
+///# Constants
+///* `ROBOT_COLORS`: The color of each robot, the first one is the color of the single robot.
+///* `TRAIL_LENGTH`: How many of the last positions of each robot are drawn as its trail.
+pub const ROBOT_COLORS: [[f32; 4]; 8] = [
+    ROBOT_COLOR,
+    [1.0, 0.35, 0.35, 1.0],
+    [0.3, 0.85, 1.0, 1.0],
+    [1.0, 0.85, 0.2, 1.0],
+    [0.4, 1.0, 0.5, 1.0],
+    [1.0, 0.55, 0.9, 1.0],
+    [1.0, 0.6, 0.2, 1.0],
+    [0.85, 0.85, 0.85, 1.0],
+];
+pub const TRAIL_LENGTH: usize = 60;
+
+/// A robot selected with `--robots`: the name in the registry and, optionally, the
+/// behavior it uses instead of the one of the configuration (`example:serpentine`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct RobotSpec {
+    pub name: String,
+    pub behavior: Option<String>,
+}
+
+impl RobotSpec {
+    pub fn parse(spec: &str) -> Self {
+        match spec.split_once(':') {
+            Some((name, behavior)) => Self {
+                name: name.trim().to_string(),
+                behavior: Some(behavior.trim().to_string()),
+            },
+            None => Self {
+                name: spec.trim().to_string(),
+                behavior: None,
+            },
+        }
+    }
+
+    /// The name shown on the HUD card.
+    pub fn label(&self) -> String {
+        match &self.behavior {
+            Some(behavior) => format!("{}:{}", self.name, behavior),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// The path used by the robot with the given index for its own exports: the first robot
+/// uses the path as it is, the others get their index appended, e.g. `history_1.csv`.
+pub fn indexed_path(path: &Path, index: usize) -> PathBuf {
+    if index == 0 {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+    let name = match path.extension() {
+        Some(extension) => format!("{}_{}.{}", stem, index, extension.to_string_lossy()),
+        None => format!("{}_{}", stem, index),
+    };
+    path.with_file_name(name)
+}
+
+/// What the window keeps for each robot.
+///
+/// Attributes:
+/// - `label`: The name shown on the HUD card.
+/// - `color`: The color of the robot and its trail.
+/// - `state`: The last state received.
+/// - `trail`: The last positions of the robot as (row, col), oldest first.
+/// - `show_map`: If the map discovered by this robot is part of the merged map.
+pub struct RobotSlot {
+    pub label: String,
+    pub color: [f32; 4],
+    pub state: VisualizerState,
+    pub trail: Vec<(usize, usize)>,
+    pub show_map: bool,
+}
+
+impl RobotSlot {
+    fn update(&mut self, state: VisualizerState) {
+        if self.trail.last() != Some(&state.coordinates) {
+            self.trail.push(state.coordinates);
+            if self.trail.len() > TRAIL_LENGTH {
+                self.trail.remove(0);
+            }
+        }
+        self.state = state;
+    }
+
+    /// The amount of tiles discovered by the robot.
+    pub fn discovered_tiles(&self) -> usize {
+        self.state.tile_colors.iter().flatten().filter(|color| color[3] > 0.0).count()
+    }
+}
+
+/// The robots shown by the window, sharing the same map view.
+///
+/// The map shows the tiles discovered by any of the robots whose map is shown; where
+/// several robots discovered a tile, the first one wins (they all run in the same world).
+/// The HUD panels show the focused robot.
+///
+/// Attributes:
+/// - `slots`: The robots, in the order they were selected.
+/// - `focused`: The index of the robot shown by the HUD panels.
+/// - `merged`: The merged map, only computed when more than one map is shown.
+pub struct RobotSlots {
+    slots: Vec<RobotSlot>,
+    focused: usize,
+    merged: ColorMatrix,
+}
+
+impl RobotSlots {
+    /// # Arguments
+    /// * `labels` - The names of the robots, at least one.
+    /// * `map_size` - The size of the world.
+    pub fn new(labels: Vec<String>, map_size: usize) -> Self {
+        let slots = labels
+            .into_iter()
+            .enumerate()
+            .map(|(index, label)| RobotSlot {
+                label,
+                color: ROBOT_COLORS[index % ROBOT_COLORS.len()],
+                state: VisualizerState::new(map_size),
+                trail: Vec::new(),
+                show_map: true,
+            })
+            .collect();
+        let mut robots = Self {
+            slots,
+            focused: 0,
+            merged: Vec::new(),
+        };
+        robots.refresh_merged();
+        robots
+    }
+
+    pub fn slots(&self) -> &[RobotSlot] {
+        &self.slots
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Replaces the states of the robots, in the same order as the slots.
+    pub fn update(&mut self, states: Vec<VisualizerState>) {
+        for (slot, state) in self.slots.iter_mut().zip(states) {
+            slot.update(state);
+        }
+        self.refresh_merged();
+    }
+
+    pub fn focused(&self) -> usize {
+        self.focused
+    }
+
+    pub fn focused_state(&self) -> &VisualizerState {
+        &self.slots[self.focused].state
+    }
+
+    /// Focuses the next robot, going back to the first one after the last.
+    pub fn focus_next(&mut self) {
+        self.focused = (self.focused + 1) % self.slots.len();
+    }
+
+    /// Shows or hides the map discovered by a robot.
+    pub fn toggle_map(&mut self, index: usize) {
+        if let Some(slot) = self.slots.get_mut(index) {
+            slot.show_map = !slot.show_map;
+            self.refresh_merged();
+        }
+    }
+
+    /// The map drawn by the window.
+    pub fn tile_colors(&self) -> &ColorMatrix {
+        match self.single_shown_map() {
+            Some(slot) => &slot.state.tile_colors,
+            None => &self.merged,
+        }
+    }
+
+    /// The only slot whose map is shown, if there is exactly one: its map is used as it
+    /// is, without copying it into the merged map.
+    fn single_shown_map(&self) -> Option<&RobotSlot> {
+        let mut shown = self.slots.iter().filter(|slot| slot.show_map);
+        match (shown.next(), shown.next()) {
+            (Some(slot), None) => Some(slot),
+            _ => None,
+        }
+    }
+
+    fn refresh_merged(&mut self) {
+        if self.single_shown_map().is_some() {
+            return;
+        }
+        let size = self.slots.first().map(|slot| slot.state.tile_colors.len()).unwrap_or(0);
+        self.merged = vec![vec![[0.0; 4]; size]; size];
+        for slot in self.slots.iter().filter(|slot| slot.show_map) {
+            for (merged_row, row) in self.merged.iter_mut().zip(&slot.state.tile_colors) {
+                for (merged_color, color) in merged_row.iter_mut().zip(row) {
+                    if merged_color[3] == 0.0 && color[3] > 0.0 {
+                        *merged_color = *color;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn discover(slots: &mut RobotSlots, tiles: &[(usize, usize, usize)]) {
+        // nothing discovered yet
+        let mut states: Vec<VisualizerState> = (0..slots.len())
+            .map(|_| VisualizerState {
+                tile_colors: vec![vec![[0.0; 4]; 2]; 2],
+                ..VisualizerState::new(2)
+            })
+            .collect();
+        for (robot, row, col) in tiles {
+            states[*robot].tile_colors[*row][*col] = [1.0, 0.0, 0.0, 1.0];
+        }
+        slots.update(states);
+    }
+
+    #[test]
+    fn merged_map_only_uses_the_shown_maps() {
+        let mut slots = RobotSlots::new(vec![String::from("a"), String::from("b")], 2);
+        discover(&mut slots, &[(0, 0, 0), (1, 1, 1)]);
+        assert_eq!(slots.tile_colors()[0][0][3], 1.0);
+        assert_eq!(slots.tile_colors()[1][1][3], 1.0);
+
+        slots.toggle_map(0);
+        assert_eq!(slots.tile_colors()[0][0][3], 0.0);
+        assert_eq!(slots.tile_colors()[1][1][3], 1.0);
+    }
+
+    #[test]
+    fn specs_and_paths_of_the_other_robots() {
+        assert_eq!(
+            RobotSpec::parse("example:serpentine"),
+            RobotSpec {
+                name: String::from("example"),
+                behavior: Some(String::from("serpentine"))
+            }
+        );
+        assert_eq!(indexed_path(Path::new("out/history.csv"), 0), PathBuf::from("out/history.csv"));
+        assert_eq!(indexed_path(Path::new("out/history.csv"), 2), PathBuf::from("out/history_2.csv"));
+    }
+}
@@ -0,0 +1,297 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use log::{info, warn};
+use robotics_lib::runner::{Robot, Runnable};
+
+use crate::audio::{AudioConfig, AudioManager};
+use crate::behavior::Behavior;
+use crate::frame::Frames;
+use crate::groundtruth::TileUpdates;
+use crate::history::RunHistory;
+use crate::metrics::ExplorationMetrics;
+use crate::robot::{ExampleRobot, Visualizable};
+use crate::state::StateCollector;
+use crate::trace::TraceSink;
+
+///# Constants
+///* `DEFAULT_ROBOT`: The name of the robot used when none is selected.
+///* `DEFAULT_BEHAVIOR`: The behavior of `ExampleRobot` used when none is selected.
+pub const DEFAULT_ROBOT: &str = "example";
+pub const DEFAULT_BEHAVIOR: &str = "alternate";
+
+/// The options of the visualizer a factory can apply to the robot it builds.
+/// Robots that don't support an option can ignore it.
+///
+/// Attributes:
+/// - `view_radius`: Tiles shown on each side of the robot in the robot view.
+/// - `play_sounds`: If sounds are played.
+/// - `audio`: The sound of each event, used if `play_sounds` is set.
+/// - `record_frames`: If a frame is saved on every move.
+/// - `trace_path`: The JSON-lines file the events are written to, if any.
+/// - `behavior`: The name of the scripted behavior of `ExampleRobot` (see `BEHAVIOR_NAMES`).
+/// - `script_path`: The moves followed by the `script` behavior.
+/// - `seed`: The seed of the random choices of the robot.
+#[derive(Clone, Debug)]
+pub struct RobotSettings {
+    pub view_radius: usize,
+    pub play_sounds: bool,
+    pub audio: AudioConfig,
+    pub record_frames: bool,
+    pub trace_path: Option<PathBuf>,
+    pub behavior: String,
+    pub script_path: Option<PathBuf>,
+    pub seed: u64,
+}
+
+impl Default for RobotSettings {
+    fn default() -> Self {
+        Self {
+            view_radius: 1,
+            play_sounds: false,
+            audio: AudioConfig::default(),
+            record_frames: true,
+            trace_path: None,
+            behavior: DEFAULT_BEHAVIOR.to_string(),
+            script_path: None,
+            seed: 0,
+        }
+    }
+}
+
+/// A robot ready to be given to a `Runner`, together with the handles the visualizer
+/// keeps once the robot has been moved into the runner.
+///
+/// Attributes:
+/// - `runnable`: The robot.
+/// - `collector`: Builds the snapshots drawn by the window and the terminal.
+/// - `init_frames`: The frames saved during the run, converted to a gif at the end.
+/// - `history`: The energy and score history, only filled by the robots that call
+///   `update_robot_history` (the `example` robot does it every tick).
+/// - `metrics`: The exploration metrics of every tick, exported at the end of the run.
+/// - `tile_updates`: The tiles changed during the run, applied to the ground truth.
+pub struct VisualizedRobot {
+    pub runnable: Box<dyn Runnable + Send>,
+    pub collector: StateCollector,
+    pub init_frames: Arc<Mutex<Frames>>,
+    pub history: Arc<Mutex<RunHistory>>,
+    pub metrics: Arc<Mutex<ExplorationMetrics>>,
+    pub tile_updates: Arc<Mutex<TileUpdates>>,
+}
+
+impl VisualizedRobot {
+    /// # Arguments
+    /// * `robot` - Any robot implementing both `Runnable` and `Visualizable`.
+    /// * `map_size` - The size of the world, used for the color matrices.
+    pub fn new<R: Runnable + Visualizable + Send + 'static>(robot: R, map_size: usize) -> Self {
+        Self {
+            collector: StateCollector::new(&robot, map_size),
+            init_frames: robot.get_init_frames(),
+            history: robot.get_history(),
+            metrics: robot.get_metrics(),
+            tile_updates: robot.get_tile_updates(),
+            runnable: Box::new(robot),
+        }
+    }
+
+    /// The amount of ticks recorded in the history. It is the amount of ticks done only
+    /// for the robots that record their history, the runners count the ticks themselves.
+    pub fn recorded_ticks(&self) -> usize {
+        self.history.lock().map(|history| history.len()).unwrap_or(0)
+    }
+}
+
+/// Builds a robot by name. Implement it (or use `register_robot_fn`) to make an AI
+/// selectable from the command line.
+pub trait RobotFactory: Send + Sync {
+    /// The name used to select the robot, e.g. `--robot example`.
+    fn name(&self) -> &str;
+    /// One line shown by `--list-robots`.
+    fn description(&self) -> &str;
+    /// Builds a new robot.
+    ///
+    /// # Arguments
+    /// * `settings` - The options of the visualizer.
+    /// * `map_size` - The size of the world.
+    fn create(&self, settings: &RobotSettings, map_size: usize) -> Result<VisualizedRobot, String>;
+}
+
+/// A factory made of a name, a description and a closure.
+struct FnFactory<F> {
+    name: String,
+    description: String,
+    create: F,
+}
+
+impl<F> RobotFactory for FnFactory<F>
+where
+    F: Fn(&RobotSettings, usize) -> Result<VisualizedRobot, String> + Send + Sync,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn create(&self, settings: &RobotSettings, map_size: usize) -> Result<VisualizedRobot, String> {
+        (self.create)(settings, map_size)
+    }
+}
+
+/// The factory of `ExampleRobot`, always registered as `example`.
+pub struct ExampleRobotFactory;
+
+impl RobotFactory for ExampleRobotFactory {
+    fn name(&self) -> &str {
+        DEFAULT_ROBOT
+    }
+
+    fn description(&self) -> &str {
+        "the scripted robot used to test the visualizer, see --behavior"
+    }
+
+    fn create(&self, settings: &RobotSettings, map_size: usize) -> Result<VisualizedRobot, String> {
+        let script_path = settings.script_path.as_ref().map(|path| path.display().to_string());
+        let behavior = Behavior::from_name(&settings.behavior, map_size, settings.seed, script_path.as_deref())?;
+        let mut robot = ExampleRobot::new(Robot::new(), Arc::new(Mutex::new(0)));
+        robot.set_behavior(behavior);
+        robot.set_map_size(map_size);
+        robot.set_view_radius(settings.view_radius);
+        if settings.play_sounds {
+            robot.set_audio(AudioManager::new(settings.audio.clone()));
+        }
+        robot.set_record_frames(settings.record_frames);
+        if let Some(trace_path) = &settings.trace_path {
+            match TraceSink::create(trace_path) {
+                Ok(trace_sink) => {
+                    info!("writing the event trace to {}", trace_path.display());
+                    robot.set_trace_sink(trace_sink)
+                }
+                Err(e) => warn!("{}", e),
+            }
+        }
+        Ok(VisualizedRobot::new(robot, map_size))
+    }
+}
+
+/// The robots that can be selected by name.
+pub struct RobotRegistry {
+    factories: BTreeMap<String, Box<dyn RobotFactory>>,
+}
+
+impl RobotRegistry {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self {
+            factories: BTreeMap::new(),
+        }
+    }
+
+    /// A registry containing the robots of this crate.
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(ExampleRobotFactory));
+        registry
+    }
+
+    /// Adds a factory, replacing the one with the same name if any.
+    pub fn register(&mut self, factory: Box<dyn RobotFactory>) {
+        if self.factories.contains_key(factory.name()) {
+            warn!("robot '{}' registered twice, the last one is used", factory.name());
+        }
+        self.factories.insert(factory.name().to_string(), factory);
+    }
+
+    /// The names and descriptions of the registered robots, sorted by name.
+    pub fn robots(&self) -> Vec<(&str, &str)> {
+        self.factories
+            .values()
+            .map(|factory| (factory.name(), factory.description()))
+            .collect()
+    }
+
+    /// Builds the robot registered with the given name.
+    pub fn create(&self, name: &str, settings: &RobotSettings, map_size: usize) -> Result<VisualizedRobot, String> {
+        match self.factories.get(name) {
+            Some(factory) => factory.create(settings, map_size),
+            None => Err(format!(
+                "unknown robot '{}', registered robots: {}",
+                name,
+                self.factories.keys().cloned().collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
+}
+
+impl Default for RobotRegistry {
+    fn default() -> Self {
+        Self::with_defaults()
+    }
+}
+
+/// The registry used by the visualizer binary, shared by the whole process.
+pub fn global_registry() -> &'static Mutex<RobotRegistry> {
+    static REGISTRY: OnceLock<Mutex<RobotRegistry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(RobotRegistry::with_defaults()))
+}
+
+/// Adds a factory to the global registry, so that the robot can be selected
+/// with `--robot <name>`.
+pub fn register_robot(factory: Box<dyn RobotFactory>) {
+    match global_registry().lock() {
+        Ok(mut registry) => registry.register(factory),
+        Err(e) => warn!("Couldnt lock the robot registry: {}", e),
+    }
+}
+
+/// Adds a robot built by a closure to the global registry, e.g.
+/// `register_robot_fn("mine", "my AI", |settings, size| Ok(VisualizedRobot::new(MyRobot::new(), size)))`.
+pub fn register_robot_fn<F>(name: &str, description: &str, create: F)
+where
+    F: Fn(&RobotSettings, usize) -> Result<VisualizedRobot, String> + Send + Sync + 'static,
+{
+    register_robot(Box::new(FnFactory {
+        name: name.to_string(),
+        description: description.to_string(),
+        create,
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A factory that fails with the given message, so that it can be told apart without building a robot.
+    fn failing_factory(name: &str, message: &'static str) -> Box<dyn RobotFactory> {
+        Box::new(FnFactory {
+            name: name.to_string(),
+            description: message.to_string(),
+            create: move |_: &RobotSettings, _: usize| -> Result<VisualizedRobot, String> { Err(message.to_string()) },
+        })
+    }
+
+    #[test]
+    fn unknown_robot_error_lists_the_registered_names() {
+        let mut registry = RobotRegistry::new();
+        registry.register(failing_factory("zeta", "z"));
+        registry.register(failing_factory("alpha", "a"));
+
+        let error = registry.create("missing", &RobotSettings::default(), 10).err().unwrap();
+        assert_eq!(error, "unknown robot 'missing', registered robots: alpha, zeta");
+        assert_eq!(registry.robots(), vec![("alpha", "a"), ("zeta", "z")]);
+    }
+
+    #[test]
+    fn registering_a_name_again_replaces_the_factory() {
+        let mut registry = RobotRegistry::with_defaults();
+        registry.register(failing_factory("mine", "first"));
+        registry.register(failing_factory("mine", "second"));
+
+        let error = registry.create("mine", &RobotSettings::default(), 10).err().unwrap();
+        assert_eq!(error, "second");
+        assert_eq!(registry.robots(), vec![(DEFAULT_ROBOT, ExampleRobotFactory.description()), ("mine", "second")]);
+    }
+}
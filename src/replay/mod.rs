@@ -0,0 +1,250 @@
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::state::{ColorMatrix, VisualizerState};
+
+/// A tile whose colors changed during a tick.
+///
+/// Attributes:
+/// - `row`, `col`: The position of the tile.
+/// - `tile_color`: The tile type color (transparent if undiscovered).
+/// - `content_color`: The content color.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplayTile {
+    pub row: usize,
+    pub col: usize,
+    pub tile_color: [f32; 4],
+    pub content_color: [f32; 4],
+}
+
+/// What changed during a single tick.
+///
+/// Attributes:
+/// - `tick`: The tick the step refers to.
+/// - `coordinates`: Coordinates of the robot as (row, col) at the end of the tick.
+/// - `energy`, `score`: Energy and score at the end of the tick.
+/// - `changed_tiles`: The tiles whose colors differ from the previous step.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReplayStep {
+    pub tick: usize,
+    pub coordinates: (usize, usize),
+    pub energy: usize,
+    pub score: f32,
+    pub changed_tiles: Vec<ReplayTile>,
+}
+
+/// A recorded run, stored as the differences between consecutive ticks so that
+/// long runs on big maps stay small.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Replay {
+    pub map_size: usize,
+    pub steps: Vec<ReplayStep>,
+}
+
+impl Replay {
+    /// Writes the replay as JSON to the given path.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the JSON file.
+    ///
+    /// # Returns
+    /// A result indicating the success or failure of the export.
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let json = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| format!("Error writing '{}': {}", path, e))
+    }
+
+    /// Reads a replay written by `save`.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the JSON file.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let json = fs::read_to_string(path).map_err(|e| format!("Error reading '{}': {}", path, e))?;
+        serde_json::from_str(&json).map_err(|e| format!("Error parsing '{}': {}", path, e))
+    }
+}
+
+/// Builds a `Replay` from the `VisualizerState` snapshots of a run.
+pub struct ReplayRecorder {
+    tile_colors: ColorMatrix,
+    content_colors: ColorMatrix,
+    last_tick: Option<usize>,
+    replay: Replay,
+}
+
+impl ReplayRecorder {
+    pub fn new(map_size: usize) -> Self {
+        let initial = VisualizerState::new(map_size);
+        Self {
+            tile_colors: initial.tile_colors,
+            content_colors: initial.content_colors,
+            last_tick: None,
+            replay: Replay {
+                map_size,
+                steps: Vec::new(),
+            },
+        }
+    }
+
+    /// Records a snapshot at the tick of its history. Snapshots of a tick that was already
+    /// recorded are ignored.
+    ///
+    /// # Returns
+    /// `true` if a new step was added to the replay.
+    pub fn record(&mut self, state: &VisualizerState) -> bool {
+        self.record_tick(state.tick(), state)
+    }
+
+    /// Same as `record`, with the tick counted by the caller, for the robots that don't
+    /// record their history.
+    pub fn record_tick(&mut self, tick: usize, state: &VisualizerState) -> bool {
+        if self.last_tick.is_some_and(|last_tick| tick <= last_tick) {
+            return false;
+        }
+
+        let mut changed_tiles = Vec::new();
+        for (row, (tile_row, content_row)) in state.tile_colors.iter().zip(state.content_colors.iter()).enumerate() {
+            for (col, (tile_color, content_color)) in tile_row.iter().zip(content_row.iter()).enumerate() {
+                let (Some(previous_tile), Some(previous_content)) = (
+                    self.tile_colors.get_mut(row).and_then(|r| r.get_mut(col)),
+                    self.content_colors.get_mut(row).and_then(|r| r.get_mut(col)),
+                ) else {
+                    continue;
+                };
+                if previous_tile != tile_color || previous_content != content_color {
+                    *previous_tile = *tile_color;
+                    *previous_content = *content_color;
+                    changed_tiles.push(ReplayTile {
+                        row,
+                        col,
+                        tile_color: *tile_color,
+                        content_color: *content_color,
+                    });
+                }
+            }
+        }
+
+        self.replay.steps.push(ReplayStep {
+            tick,
+            coordinates: state.coordinates,
+            energy: state.energy,
+            score: state.score,
+            changed_tiles,
+        });
+        self.last_tick = Some(tick);
+        true
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    pub fn finish(self) -> Replay {
+        self.replay
+    }
+}
+
+/// Plays a `Replay` back: rebuilds the colors of the map at any tick from the
+/// recorded differences.
+///
+/// Attributes:
+/// - `replay`: The replay played.
+/// - `tile_colors`, `content_colors`: The map at the current tick.
+/// - `applied_steps`: How many steps are applied to the map.
+pub struct ReplayPlayer {
+    replay: Replay,
+    tile_colors: ColorMatrix,
+    content_colors: ColorMatrix,
+    applied_steps: usize,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        let initial = VisualizerState::new(replay.map_size);
+        Self {
+            replay,
+            tile_colors: initial.tile_colors,
+            content_colors: initial.content_colors,
+            applied_steps: 0,
+        }
+    }
+
+    /// The first tick of the replay, 0 if it is empty.
+    pub fn first_tick(&self) -> usize {
+        self.replay.steps.first().map(|step| step.tick).unwrap_or(0)
+    }
+
+    /// The last tick of the replay, 0 if it is empty.
+    pub fn last_tick(&self) -> usize {
+        self.replay.steps.last().map(|step| step.tick).unwrap_or(0)
+    }
+
+    /// Moves to the given tick: the map shows every step recorded up to that tick.
+    /// Moving forward only applies the new steps, moving back rebuilds the map from the start.
+    pub fn seek(&mut self, tick: usize) {
+        if self.current_step().is_some_and(|step| step.tick > tick) {
+            let initial = VisualizerState::new(self.replay.map_size);
+            self.tile_colors = initial.tile_colors;
+            self.content_colors = initial.content_colors;
+            self.applied_steps = 0;
+        }
+        while let Some(step) = self.replay.steps.get(self.applied_steps) {
+            if step.tick > tick {
+                break;
+            }
+            for tile in &step.changed_tiles {
+                if let Some(color) = self.tile_colors.get_mut(tile.row).and_then(|row| row.get_mut(tile.col)) {
+                    *color = tile.tile_color;
+                }
+                if let Some(color) = self.content_colors.get_mut(tile.row).and_then(|row| row.get_mut(tile.col)) {
+                    *color = tile.content_color;
+                }
+            }
+            self.applied_steps += 1;
+        }
+    }
+
+    /// The last step applied, with the coordinates, energy and score at the current tick.
+    pub fn current_step(&self) -> Option<&ReplayStep> {
+        self.applied_steps.checked_sub(1).and_then(|index| self.replay.steps.get(index))
+    }
+
+    pub fn tile_colors(&self) -> &ColorMatrix {
+        &self.tile_colors
+    }
+
+    pub fn content_colors(&self) -> &ColorMatrix {
+        &self.content_colors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeking_back_gives_the_same_map_as_recording() {
+        let mut recorder = ReplayRecorder::new(2);
+        let mut states = Vec::new();
+        for tick in 1..=3 {
+            let mut state = VisualizerState::new(2);
+            for history_tick in 0..tick {
+                state.history.record(history_tick, 0, 0.0);
+            }
+            state.tile_colors[0][tick % 2] = [0.0, 1.0, 0.0, 1.0];
+            if tick == 3 {
+                state.tile_colors[1][1] = [0.0, 0.0, 1.0, 1.0];
+            }
+            recorder.record(&state);
+            states.push(state);
+        }
+
+        let mut player = ReplayPlayer::new(recorder.finish());
+        player.seek(3);
+        assert_eq!(player.tile_colors(), &states[2].tile_colors);
+        player.seek(1);
+        assert_eq!(player.tile_colors(), &states[0].tile_colors);
+        assert_eq!(player.current_step().map(|step| step.tick), Some(1));
+    }
+}
@@ -1,21 +1,27 @@
 use std::sync::{Arc, Mutex};
-use std::thread;
-use log::error;
+use log::{debug, error, trace, warn};
 
 use robotics_lib::energy::Energy;
 use robotics_lib::event::events::Event;
-use robotics_lib::interface::{Direction, get_score, go, robot_view};
+use robotics_lib::interface::{get_score, robot_view};
 use robotics_lib::interface::robot_map;
 use robotics_lib::runner::{Robot, Runnable};
 use robotics_lib::runner::backpack::BackPack;
 use robotics_lib::world::coordinates::Coordinate;
+use robotics_lib::world::environmental_conditions::EnvironmentalConditions;
 use robotics_lib::world::tile::Tile;
 use robotics_lib::world::World;
 
+use crate::audio::AudioManager;
+use crate::behavior::Behavior;
+use crate::events::EventLog;
 use crate::frame::Frames as OtherFrames;
-use crate::util::{backpack_to_text, clear_png_files_in_directory, DEFAULT_PNGS_PATH, play_sound, update_resource};
-
-const PLAY_SOUNDS: bool = false;
+use crate::groundtruth::TileUpdates;
+use crate::history::RunHistory;
+use crate::metrics::{count_discovered, ExplorationMetrics};
+use crate::teleport::{is_teleport_jump, TeleportJump};
+use crate::trace::TraceSink;
+use crate::util::{BackpackChange, BackpackInfo, clear_png_files_in_directory, robot_map_view, EnvironmentInfo, update_resource};
 
 pub trait Sentient: Runnable {
     fn act(&mut self, world: &mut World);
@@ -25,10 +31,16 @@ pub trait Visualizable {
     fn get_init_frames(&self) -> Arc<Mutex<OtherFrames>>;
     fn get_current_robot_map(&self) -> Arc<Mutex<Option<Vec<Vec<Option<Tile>>>>>>;
     fn get_current_robot_view(&self) -> Arc<Mutex<Vec<Vec<Option<Tile>>>>>;
-    fn get_current_robot_backpack(&self) -> Arc<Mutex<String>>;
+    fn get_current_robot_backpack(&self) -> Arc<Mutex<BackpackInfo>>;
     fn get_score(&self) -> Arc<Mutex<f32>>;
     fn get_current_robot_coordinates(&self) -> Arc<Mutex<(usize, usize)>>;
     fn get_current_energy(&self) -> Arc<Mutex<usize>>;
+    fn get_current_environment(&self) -> Arc<Mutex<EnvironmentInfo>>;
+    fn get_event_log(&self) -> Arc<Mutex<EventLog>>;
+    fn get_history(&self) -> Arc<Mutex<RunHistory>>;
+    fn get_metrics(&self) -> Arc<Mutex<ExplorationMetrics>>;
+    fn get_last_jump(&self) -> Arc<Mutex<Option<TeleportJump>>>;
+    fn get_tile_updates(&self) -> Arc<Mutex<TileUpdates>>;
 }
 
 pub struct ExampleRobot {
@@ -37,10 +49,23 @@ pub struct ExampleRobot {
     init_frames: Arc<Mutex<OtherFrames>>,
     current_robot_map: Arc<Mutex<Option<Vec<Vec<Option<Tile>>>>>>,
     current_robot_view: Arc<Mutex<Vec<Vec<Option<Tile>>>>>,
-    current_robot_backpack: Arc<Mutex<String>>,
+    current_robot_backpack: Arc<Mutex<BackpackInfo>>,
     score: Arc<Mutex<f32>>,
     current_robot_coordinates: Arc<Mutex<(usize, usize)>>,
     current_robot_energy: Arc<Mutex<usize>>,
+    current_environment: Arc<Mutex<EnvironmentInfo>>,
+    event_log: Arc<Mutex<EventLog>>,
+    history: Arc<Mutex<RunHistory>>,
+    metrics: Arc<Mutex<ExplorationMetrics>>,
+    last_jump: Arc<Mutex<Option<TeleportJump>>>,
+    tile_updates: Arc<Mutex<TileUpdates>>,
+    //the position before the last move, the `Moved` event only has the new one
+    previous_coordinates: Option<(usize, usize)>,
+    view_radius: usize,
+    trace_sink: Option<TraceSink>,
+    audio: Option<AudioManager>,
+    record_frames: bool,
+    behavior: Behavior,
 }
 
 impl Visualizable for ExampleRobot {
@@ -53,7 +78,7 @@ impl Visualizable for ExampleRobot {
     fn get_current_robot_view(&self) -> Arc<Mutex<Vec<Vec<Option<Tile>>>>> {
         self.current_robot_view.clone()
     }
-    fn get_current_robot_backpack(&self) -> Arc<Mutex<String>> {
+    fn get_current_robot_backpack(&self) -> Arc<Mutex<BackpackInfo>> {
         self.current_robot_backpack.clone()
     }
     fn get_score(&self) -> Arc<Mutex<f32>> {
@@ -65,6 +90,24 @@ impl Visualizable for ExampleRobot {
     fn get_current_energy(&self) -> Arc<Mutex<usize>> {
         self.current_robot_energy.clone()
     }
+    fn get_current_environment(&self) -> Arc<Mutex<EnvironmentInfo>> {
+        self.current_environment.clone()
+    }
+    fn get_event_log(&self) -> Arc<Mutex<EventLog>> {
+        self.event_log.clone()
+    }
+    fn get_history(&self) -> Arc<Mutex<RunHistory>> {
+        self.history.clone()
+    }
+    fn get_metrics(&self) -> Arc<Mutex<ExplorationMetrics>> {
+        self.metrics.clone()
+    }
+    fn get_last_jump(&self) -> Arc<Mutex<Option<TeleportJump>>> {
+        self.last_jump.clone()
+    }
+    fn get_tile_updates(&self) -> Arc<Mutex<TileUpdates>> {
+        self.tile_updates.clone()
+    }
 }
 
 impl ExampleRobot {
@@ -75,12 +118,60 @@ impl ExampleRobot {
             init_frames: Arc::new(Mutex::new(OtherFrames::new())),
             current_robot_map: Arc::new(Mutex::new(None)),
             current_robot_view: Arc::new(Mutex::new(vec![vec![None; 3]; 3])),
-            current_robot_backpack: Arc::new(Mutex::new(String::new())),
+            current_robot_backpack: Arc::new(Mutex::new(BackpackInfo::default())),
             score: Arc::new(Mutex::new(0.0)),
             current_robot_coordinates: Arc::new(Mutex::new((0, 0))),
             current_robot_energy: Arc::new(Mutex::new(0)),
+            current_environment: Arc::new(Mutex::new(EnvironmentInfo::default())),
+            event_log: Arc::new(Mutex::new(EventLog::default())),
+            history: Arc::new(Mutex::new(RunHistory::new())),
+            //the size of the world is only known once the robot runs, see `set_map_size`
+            metrics: Arc::new(Mutex::new(ExplorationMetrics::default())),
+            last_jump: Arc::new(Mutex::new(None)),
+            tile_updates: Arc::new(Mutex::new(Vec::new())),
+            previous_coordinates: None,
+            view_radius: 1,
+            trace_sink: None,
+            audio: None,
+            record_frames: true,
+            behavior: Behavior::default(),
+        }
+    }
+
+    /// Sets how many tiles are shown on each side of the robot in the robot view
+    /// (1 is the 3x3 view of `robot_view`, larger radii use the discovered map).
+    pub fn set_view_radius(&mut self, view_radius: usize) {
+        self.view_radius = view_radius.max(1);
+    }
+
+    /// Records every event received by `handle_event` into the given JSON-lines trace.
+    pub fn set_trace_sink(&mut self, trace_sink: TraceSink) {
+        self.trace_sink = Some(trace_sink);
+    }
+
+    /// Plays the sounds bound to the events (see `AudioConfig`).
+    pub fn set_audio(&mut self, audio: AudioManager) {
+        self.audio = Some(audio);
+    }
+
+    /// Saves a frame of the discovered map on every move, used to create the gif.
+    pub fn set_record_frames(&mut self, record_frames: bool) {
+        self.record_frames = record_frames;
+    }
+
+    /// Sets the size of the world, used for the percentage of discovered tiles.
+    pub fn set_map_size(&mut self, map_size: usize) {
+        //the metrics are updated in place, their handle may already be shared
+        match self.metrics.lock() {
+            Ok(mut metrics) => metrics.set_map_size(map_size),
+            Err(e) => error!("couldnt lock METRICS in set_map_size: {}", e),
         }
     }
+
+    /// Selects what the robot does every tick (see `Behavior`).
+    pub fn set_behavior(&mut self, behavior: Behavior) {
+        self.behavior = behavior;
+    }
 }
 
 //used for debug purpose
@@ -88,139 +179,96 @@ impl Sentient for ExampleRobot {
     fn act(&mut self, world: &mut World) {
         let index = *self.iterations.lock().unwrap(); //for debug
 
-        //example of AI to degub visualizer
-        //let environmental_conditions = look_at_sky(world);
-        //let _ = one_direction_view(self, world, Right, index);
-        //let _ = one_direction_view(self, world, Right, 5);
-        let direction;
-
-        if index % 2 == 0 {
-            direction = Direction::Down;
-        } else {
-            direction = Direction::Right;
-        }
-        let _ = go(self, world, direction);
-
-        /*
-        if index <= 25 {
-            direction = Direction::Right;
-        } else if index > 25 && index <= 27 {
-            direction = Direction::Down;
-        } else if index > 27 && index <= 52 {
-            direction = Direction::Left;
-        } else if index > 52 && index <= 54 {
-            direction = Direction::Down;
-        } else if index > 54 && index <= 79 {
-            direction = Direction::Right;
-        } else if index > 79 && index <= 81 {
-            direction = Direction::Down;
-        } else if index > 81 && index <= 83 {
-            direction = Direction::Left;
-        } else {
-            let mut rng = rand::thread_rng();
-            let rand_direction = rng.gen_range(0..=3);
-            match rand_direction {
-                0 => direction = Direction::Right,
-                1 => direction = Direction::Left,
-                2 => direction = Direction::Down,
-                3 => direction = Direction::Up,
-                _ => direction = Direction::Right,
-            }
-        }
-        */
-
-        //let _ = go(self, world, direction);
-        /*
-        if index < 300 {
-            let _ = destroy(self, world, Direction::Right);
-            let _ = destroy(self, world, Direction::Left);
-            let _ = destroy(self, world, Direction::Up);
-            let _ = destroy(self, world, Direction::Down);
-        } else {
-            let contained_content;
-            if let Some(&size) = self.get_backpack().get_contents().get(&Rock(1)) {
-                contained_content = 0;
-            } else if let Some(&size) = self.get_backpack().get_contents().get(&Garbage(1)) {
-                contained_content = 1;
-            } else if let Some(&size) = self.get_backpack().get_contents().get(&Coin(1)) {
-                contained_content = 2;
-            } else {
-                contained_content = 3;
-            }
+        //the behavior needs the robot itself to call the interface functions
+        let mut behavior = std::mem::take(&mut self.behavior);
+        behavior.act(self, world, index);
+        self.behavior = behavior;
 
-            if contained_content == 0 {
-                if let Err(_) = put(self, world, Rock(1), 1, Right) {
-                    if let Err(_) = put(self, world, Rock(1), 1, Left) {
-                        if let Err(_) = put(self, world, Rock(1), 1, Down) {
-                            if let Err(_) = put(self, world, Rock(1), 1, Up) {}
-                        }
-                    }
-                }
-            } else if contained_content == 1 {
-                if let Err(_) = put(self, world, Garbage(1), 1, Right) {
-                    if let Err(_) = put(self, world, Garbage(1), 1, Left) {
-                        if let Err(_) = put(self, world, Garbage(1), 1, Down) {
-                            if let Err(_) = put(self, world, Garbage(1), 1, Up) {}
-                        }
-                    }
-                }
-            } else if contained_content == 2 {
-                if let Err(_) = put(self, world, Coin(1), 1, Right) {
-                    if let Err(_) = put(self, world, Coin(1), 1, Left) {
-                        if let Err(_) = put(self, world, Coin(1), 1, Down) {
-                            if let Err(_) = put(self, world, Coin(1), 1, Up) {}
-                        }
-                    }
-                }
-            }
-        }
-         */
-        //debug
-        println!("{}", index);
+        debug!("tick {}", index);
         *self.iterations.lock().unwrap() = index + 1;
     }
 }
 
 impl Runnable for ExampleRobot {
     fn process_tick(&mut self, world: &mut World) {
+        let coordinate = self.get_coordinate();
+        self.previous_coordinates = Some((coordinate.get_row(), coordinate.get_col()));
         self.act(world);
         //non modificare le seguenti righe
         if let Err(e) = update_robot_map(self, world) {
-            eprintln!("{}", e)
+            error!("{}", e)
         }
-        if let Err(e) = update_robot_view(self, world) {
-            eprintln!("{}", e)
+        if let Err(e) = update_robot_wide_view(self, world, self.view_radius) {
+            error!("{}", e)
         }
         let new_score = get_score(world);
         if let Err(e) = update_robot_score(self, new_score) {
-            eprintln!("{}", e)
+            error!("{}", e)
+        }
+        //the tick has already been counted by act()
+        let tick = self.iterations.lock().unwrap().saturating_sub(1);
+        let energy = self.get_energy().get_energy_level();
+        if let Err(e) = update_robot_history(self, tick, energy, new_score) {
+            error!("{}", e)
+        }
+        if let Err(e) = update_robot_metrics(self, tick) {
+            error!("{}", e)
         }
     }
 
     //non modificare le seguenti righe (potete aggiungere roba se vi serve per debug ma non rimuovete le chiamate a metodi ecc)
     fn handle_event(&mut self, event: Event) {
+        let tick = *self.iterations.lock().unwrap();
+        if let Err(e) = update_robot_event_log(self, tick, &event) {
+            error!("couldnt lock EVENT_LOG in HandleEvent: {}", e)
+        }
+        let energy = self.get_energy().get_energy_level();
+        if let Some(trace_sink) = &mut self.trace_sink {
+            if let Err(e) = trace_sink.record(tick, energy, &event) {
+                warn!("couldnt write the event to the trace: {}", e)
+            }
+        }
+        if let Some(audio) = &mut self.audio {
+            audio.handle_event(&event);
+        }
+        match self.metrics.lock() {
+            Ok(mut metrics) => metrics.record_event(&event),
+            Err(e) => error!("couldnt lock METRICS in HandleEvent: {}", e),
+        }
+
         match event {
             Event::Ready => {
                 //clears the path were pngs are writted/read from to produce the gif
-                if let Err(e) = clear_png_files_in_directory(DEFAULT_PNGS_PATH) {
-                    eprintln!("Couldnt clear png path: {}", e)
+                //(only if this robot writes them, other robots of the run may be using it)
+                if !self.record_frames {
+                    return;
+                }
+                let directory = match self.init_frames.lock() {
+                    Ok(lock) => lock.directory().to_string(),
+                    Err(e) => {
+                        error!("couldnt lock init_frames in HandleEvent(Ready): {}", e);
+                        return;
+                    }
+                };
+                if let Err(e) = clear_png_files_in_directory(&directory) {
+                    error!("Couldnt clear png path: {}", e)
                 }
             }
             Event::Terminated => {}
-            Event::TimeChanged(_) => {
-                if PLAY_SOUNDS {
-                    thread::spawn(|| {
-                        if let Err(e) = play_sound("/prova.ogg", 0.5) {
-                            eprintln!("error playing sound for TimeChanged: {}", e)
-                        }
-                    });
+            Event::TimeChanged(conditions) => {
+                if let Err(e) = update_robot_environment(self, &conditions, false) {
+                    error!("couldnt lock CURRENT_ENVIRONMENT in HandleEvent(TimeChanged): {}", e)
+                }
+            }
+            Event::DayChanged(conditions) => {
+                if let Err(e) = update_robot_environment(self, &conditions, true) {
+                    error!("couldnt lock CURRENT_ENVIRONMENT in HandleEvent(DayChanged): {}", e)
                 }
             }
-            Event::DayChanged(_) => {}
             Event::EnergyRecharged(_) => {
                 let new_energy = self.get_energy();
                 if let Err(e) = update_robot_energy(self, new_energy) {
-                    eprintln!(
+                    error!(
                         "couldnt lock CURRENT_ROBOT_ENERGY in HandleEvent(Moved): {}",
                         e
                     )
@@ -229,31 +277,51 @@ impl Runnable for ExampleRobot {
             Event::EnergyConsumed(_) => {
                 let new_energy = self.get_energy();
                 if let Err(e) = update_robot_energy(self, new_energy) {
-                    eprintln!(
+                    error!(
                         "couldnt lock CURRENT_ROBOT_ENERGY in HandleEvent(Moved): {}",
                         e
                     )
                 }
             }
-            Event::Moved(_, _) => {
+            Event::Moved(tile, (row, col)) => {
+                if let Some(from) = self.previous_coordinates {
+                    if is_teleport_jump(from, (row, col), &tile) {
+                        if let Err(e) = update_robot_last_jump(self, from, (row, col)) {
+                            error!("couldnt lock LAST_JUMP in HandleEvent(Moved): {}", e)
+                        }
+                    }
+                }
+                self.previous_coordinates = Some((row, col));
+
                 let new_coord = self.get_coordinate();
                 if let Err(e) = update_robot_coord(self, new_coord) {
-                    eprintln!(
+                    error!(
                         "couldnt lock CURRENT_ROBOT_COORDINATES in HandleEvent(Moved): {}",
                         e
                     )
                 }
 
-                println!("moved");
+                trace!("moved to ({},{})", col, row);
+                if !self.record_frames {
+                    return;
+                }
+
+                let environment = match self.current_environment.lock() {
+                    Ok(lock) => lock.clone(),
+                    Err(e) => {
+                        error!("couldnt lock CURRENT_ENVIRONMENT in HandleEvent(Moved): {}", e);
+                        EnvironmentInfo::default()
+                    }
+                };
 
                 match self.init_frames.lock() {
                     Ok(mut init_frame_lock) => {
                        match &self.get_current_robot_map().lock() {
                            Ok(current_map_lock) => {
-                               init_frame_lock.add_frame(current_map_lock)
+                               init_frame_lock.add_frame(current_map_lock, &environment)
                            }
                            Err(e) => {
-                               eprintln!(
+                               error!(
                                    "Coultnd lock CURRENT_ROBOT_MAP in HandleEvent(Moved): {}",
                                    e
                                )
@@ -261,40 +329,30 @@ impl Runnable for ExampleRobot {
                        }
                     }
                     Err(e) => {
-                        eprintln!(
+                        error!(
                             "couldnt lock init_frames in HandleEvent(Moved): {}",
                             e
                         )
                     }
                 }
             }
-            Event::TileContentUpdated(_, _) => {}
-            Event::AddedToBackpack(_, _) => {
-                let current_backpack = self.get_backpack();
-                if let Err(e) = update_robot_backpack(self, current_backpack) {
-                    eprintln!("Couldnt update backpack: {}", e)
-                }
-                //the function must sleep for a while to allow the sound to play (0.2s)
-                //so it is better to do it in another thread and let the main function keep going
-                if PLAY_SOUNDS {
-                    thread::spawn(|| {
-                        if let Err(e) = play_sound("/AddedToBackpack.ogg", 0.5) {
-                            eprintln!("error playing sound for AddedToBackpack: {}", e)
-                        }
-                    });
+            Event::TileContentUpdated(tile, coordinates) => {
+                if let Err(e) = update_robot_tile_updates(self, tile, coordinates) {
+                    error!("couldnt lock TILE_UPDATES in HandleEvent(TileContentUpdated): {}", e)
                 }
             }
-            Event::RemovedFromBackpack(_, _) => {
+            Event::AddedToBackpack(content, amount) => {
+                let change = BackpackChange { content, amount: amount as isize, tick };
                 let current_backpack = self.get_backpack();
-                if let Err(e) = update_robot_backpack(self, current_backpack) {
-                    eprintln!("Couldnt update backpack: {}", e)
+                if let Err(e) = update_robot_backpack(self, current_backpack, Some(change)) {
+                    error!("Couldnt update backpack: {}", e)
                 }
-                if PLAY_SOUNDS {
-                    thread::spawn(|| {
-                        if let Err(e) = play_sound("/RemovedFromBackpack.ogg", 0.5) {
-                            eprintln!("error playing sound for AddedToBackpack: {}", e)
-                        }
-                    });
+            }
+            Event::RemovedFromBackpack(content, amount) => {
+                let change = BackpackChange { content, amount: -(amount as isize), tick };
+                let current_backpack = self.get_backpack();
+                if let Err(e) = update_robot_backpack(self, current_backpack, Some(change)) {
+                    error!("Couldnt update backpack: {}", e)
                 }
             }
         }
@@ -329,6 +387,24 @@ pub fn update_robot_view<'a, R>(robot: &'a R, world: &'a World) -> Result<(), St
     update_resource(&robot.get_current_robot_view(), robot_view(robot, world))
 }
 
+/// Updates the robot view with the (2 * radius + 1) square of tiles around the robot.
+/// `robot_view` is always called so that the 3x3 around the robot is discovered, wider views
+/// are then taken from the discovered map (e.g. tiles discovered with `one_direction_view`).
+pub fn update_robot_wide_view<'a, R>(robot: &'a R, world: &'a World, radius: usize) -> Result<(), String>
+    where
+        R: Visualizable + Runnable,
+{
+    let view = robot_view(robot, world);
+    if radius <= 1 {
+        return update_resource(&robot.get_current_robot_view(), view);
+    }
+    let coordinate = robot.get_coordinate();
+    update_resource(
+        &robot.get_current_robot_view(),
+        robot_map_view(&robot_map(world), (coordinate.get_row(), coordinate.get_col()), radius),
+    )
+}
+
 pub fn update_robot_map<'a, R>(robot: &'a R, world: &'a World) -> Result<(), String>
     where
         R: Visualizable + Runnable,
@@ -343,11 +419,39 @@ pub fn update_robot_coord<'a, R>(robot: &'a R, new_coord: &'a Coordinate) -> Res
     update_resource(&robot.get_current_robot_coordinates(), (new_coord.get_row(), new_coord.get_col()))
 }
 
-pub fn update_robot_backpack<'a, R>(robot: &'a R, back_pack: &'a BackPack) -> Result<(), String>
+/// Stores a jump of the robot between two teleports, numbered after the previous one.
+pub fn update_robot_last_jump<R>(robot: &R, from: (usize, usize), to: (usize, usize)) -> Result<(), String>
+    where
+        R: Visualizable + Runnable,
+{
+    match robot.get_last_jump().lock() {
+        Ok(mut lock) => {
+            let number = lock.map_or(1, |jump| jump.number + 1);
+            *lock = Some(TeleportJump { number, from, to });
+            Ok(())
+        }
+        Err(_) => Err("Mutex was poisoned".to_string()),
+    }
+}
+
+pub fn update_robot_tile_updates<R>(robot: &R, tile: Tile, coordinates: (usize, usize)) -> Result<(), String>
+    where
+        R: Visualizable + Runnable,
+{
+    match robot.get_tile_updates().lock() {
+        Ok(mut lock) => {
+            lock.push((coordinates, tile));
+            Ok(())
+        }
+        Err(_) => Err("Mutex was poisoned".to_string()),
+    }
+}
+
+pub fn update_robot_backpack<'a, R>(robot: &'a R, back_pack: &'a BackPack, last_change: Option<BackpackChange>) -> Result<(), String>
     where
         R: Visualizable + Runnable,
 {
-    update_resource(&robot.get_current_robot_backpack(), backpack_to_text(back_pack))
+    update_resource(&robot.get_current_robot_backpack(), BackpackInfo::from_backpack(back_pack, last_change))
 }
 
 pub fn update_robot_score<'a, R>(robot: &'a R, new_score: f32) -> Result<(), String>
@@ -364,3 +468,61 @@ pub fn update_robot_energy<'a, R>(robot: &'a R, new_energy: &'a Energy) -> Resul
     update_resource(&robot.get_current_energy(), new_energy.get_energy_level())
 }
 
+pub fn update_robot_environment<'a, R>(robot: &'a R, conditions: &'a EnvironmentalConditions, new_day: bool) -> Result<(), String>
+    where
+        R: Visualizable + Runnable,
+{
+    let environment = robot.get_current_environment();
+    let day = match environment.lock() {
+        Ok(lock) => lock.day,
+        Err(_) => return Err("Mutex was poisoned".to_string()),
+    };
+    let day = if new_day { day + 1 } else { day };
+    update_resource(&environment, EnvironmentInfo::from_conditions(conditions, day))
+}
+
+pub fn update_robot_event_log<'a, R>(robot: &'a R, tick: usize, event: &'a Event) -> Result<(), String>
+    where
+        R: Visualizable + Runnable,
+{
+    match robot.get_event_log().lock() {
+        Ok(mut lock) => {
+            lock.push(tick, event);
+            Ok(())
+        }
+        Err(_) => Err("Mutex was poisoned".to_string()),
+    }
+}
+
+pub fn update_robot_history<'a, R>(robot: &'a R, tick: usize, energy: usize, score: f32) -> Result<(), String>
+    where
+        R: Visualizable + Runnable,
+{
+    match robot.get_history().lock() {
+        Ok(mut lock) => {
+            lock.record(tick, energy, score);
+            Ok(())
+        }
+        Err(_) => Err("Mutex was poisoned".to_string()),
+    }
+}
+
+/// Stores the exploration metrics of the tick that ended, counting the tiles of the
+/// robot map updated by `update_robot_map`.
+pub fn update_robot_metrics<R>(robot: &R, tick: usize) -> Result<(), String>
+    where
+        R: Visualizable + Runnable,
+{
+    let discovered_tiles = match robot.get_current_robot_map().lock() {
+        Ok(lock) => count_discovered(&lock),
+        Err(_) => return Err("Mutex was poisoned".to_string()),
+    };
+    let coordinate = robot.get_coordinate();
+    match robot.get_metrics().lock() {
+        Ok(mut lock) => {
+            lock.end_tick(tick, (coordinate.get_row(), coordinate.get_col()), discovered_tiles);
+            Ok(())
+        }
+        Err(_) => Err("Mutex was poisoned".to_string()),
+    }
+}
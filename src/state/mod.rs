@@ -0,0 +1,208 @@
+use std::sync::{Arc, Mutex};
+
+use log::error;
+use robotics_lib::world::tile::Tile;
+
+use crate::events::EventLog;
+use crate::history::RunHistory;
+use crate::metrics::{ExplorationMetrics, MetricsSample};
+use crate::robot::Visualizable;
+use crate::teleport::{find_teleports, TeleportJump, TeleportTile};
+use crate::util::{convert_content_to_color_matrix, convert_to_color_matrix, BackpackInfo, EnvironmentInfo};
+
+/// A matrix of RGBA colors, indexed `[row][col]`.
+pub type ColorMatrix = Vec<Vec<[f32; 4]>>;
+
+///# Constants
+///* `HISTORY_TAIL_TICKS`: How many ticks of the history are sent to the renderers, the whole history stays with the robot.
+pub const HISTORY_TAIL_TICKS: usize = 500;
+
+/// Everything a renderer (the Piston window, the terminal) needs to draw the robot,
+/// sent from the robot's thread to the UI.
+///
+/// Attributes:
+/// - `tile_colors`: Tile Type converted to color matrix, indexed `[row][col]`.
+/// - `content_colors`: Content converted to color matrix, indexed `[row][col]`.
+/// - `coordinates`: Coordinates of the robot as (row, col).
+/// - `robot_view`: Robot View.
+/// - `backpack`: Backpack contents.
+/// - `energy`: Energy.
+/// - `score`: Score.
+/// - `environment`: Environmental conditions (weather, time of day and day counter).
+/// - `event_log`: Event log.
+/// - `history`: Energy and score of the last `HISTORY_TAIL_TICKS` ticks.
+/// - `metrics`: Exploration metrics of the last tick.
+/// - `teleports`: Teleport tiles of the discovered map.
+/// - `last_jump`: The last jump of the robot between two teleports, detected when it moved.
+#[derive(Clone, Debug)]
+pub struct VisualizerState {
+    pub tile_colors: ColorMatrix,
+    pub content_colors: ColorMatrix,
+    pub coordinates: (usize, usize),
+    pub robot_view: Vec<Vec<Option<Tile>>>,
+    pub backpack: BackpackInfo,
+    pub energy: usize,
+    pub score: f32,
+    pub environment: EnvironmentInfo,
+    pub event_log: EventLog,
+    pub history: RunHistory,
+    pub metrics: MetricsSample,
+    pub teleports: Vec<TeleportTile>,
+    pub last_jump: Option<TeleportJump>,
+}
+
+impl VisualizerState {
+    /// The state shown before the first update, with an undiscovered map of the given size.
+    pub fn new(map_size: usize) -> Self {
+        Self {
+            tile_colors: vec![vec![[0.0, 0.0, 0.0, 1.0]; map_size]; map_size],
+            content_colors: vec![vec![[0.0, 0.0, 0.0, 1.0]; map_size]; map_size],
+            coordinates: (0, 0),
+            robot_view: vec![vec![None; 3]; 3],
+            backpack: BackpackInfo::default(),
+            energy: 0,
+            score: 0.0,
+            environment: EnvironmentInfo::default(),
+            event_log: EventLog::default(),
+            history: RunHistory::new(),
+            metrics: MetricsSample::default(),
+            teleports: Vec::new(),
+            last_jump: None,
+        }
+    }
+
+    /// The current tick, i.e. the amount of ticks recorded in the history.
+    pub fn tick(&self) -> usize {
+        self.history.len()
+    }
+}
+
+/// Builds `VisualizerState` snapshots from the shared resources of a `Visualizable` robot.
+///
+/// The color matrices are kept between two snapshots, so that each snapshot only has to
+/// convert the robot map instead of allocating new matrices.
+pub struct StateCollector {
+    map_size: usize,
+    current_robot_map: Arc<Mutex<Option<Vec<Vec<Option<Tile>>>>>>,
+    current_robot_view: Arc<Mutex<Vec<Vec<Option<Tile>>>>>,
+    current_robot_backpack: Arc<Mutex<BackpackInfo>>,
+    score: Arc<Mutex<f32>>,
+    current_robot_coordinates: Arc<Mutex<(usize, usize)>>,
+    current_robot_energy: Arc<Mutex<usize>>,
+    current_environment: Arc<Mutex<EnvironmentInfo>>,
+    event_log: Arc<Mutex<EventLog>>,
+    history: Arc<Mutex<RunHistory>>,
+    metrics: Arc<Mutex<ExplorationMetrics>>,
+    last_jump: Arc<Mutex<Option<TeleportJump>>>,
+    tile_color_matrix: Arc<Mutex<ColorMatrix>>,
+    content_color_matrix: Arc<Mutex<ColorMatrix>>,
+}
+
+impl StateCollector {
+    /// # Arguments
+    /// * `robot` - The robot whose resources are collected, before it is moved into the `Runner`.
+    /// * `map_size` - The size of the world, used for the color matrices.
+    pub fn new<R: Visualizable>(robot: &R, map_size: usize) -> Self {
+        Self {
+            map_size,
+            current_robot_map: robot.get_current_robot_map(),
+            current_robot_view: robot.get_current_robot_view(),
+            current_robot_backpack: robot.get_current_robot_backpack(),
+            score: robot.get_score(),
+            current_robot_coordinates: robot.get_current_robot_coordinates(),
+            current_robot_energy: robot.get_current_energy(),
+            current_environment: robot.get_current_environment(),
+            event_log: robot.get_event_log(),
+            history: robot.get_history(),
+            metrics: robot.get_metrics(),
+            last_jump: robot.get_last_jump(),
+            tile_color_matrix: Arc::new(Mutex::new(vec![vec![[0.0, 0.0, 0.0, 1.0]; map_size]; map_size])),
+            content_color_matrix: Arc::new(Mutex::new(vec![vec![[0.0, 0.0, 0.0, 1.0]; map_size]; map_size])),
+        }
+    }
+
+    /// Returns a snapshot of the robot's resources. A resource whose mutex is poisoned is
+    /// replaced by its default value, so that the renderers can keep going.
+    pub fn collect(&self) -> VisualizerState {
+        let map_size = self.map_size;
+
+        let robot_map = lock_or(&self.current_robot_map, "CURRENT_ROBOT_MAP", || None);
+        //create a matrix containing the colors for each tile_type/content at index [row][col]
+        convert_content_to_color_matrix(&robot_map, &self.content_color_matrix);
+        convert_to_color_matrix(&robot_map, &self.tile_color_matrix);
+
+        VisualizerState {
+            tile_colors: lock_or(&self.tile_color_matrix, "TILE_COLOR_MATRIX", || {
+                vec![vec![[0.0, 0.0, 0.0, 1.0]; map_size]; map_size]
+            }),
+            content_colors: lock_or(&self.content_color_matrix, "CONTENT_COLOR_MATRIX", || {
+                vec![vec![[0.0, 0.0, 0.0, 1.0]; map_size]; map_size]
+            }),
+            coordinates: lock_or(&self.current_robot_coordinates, "CURRENT_ROBOT_COORDINATES", || (0, 0)),
+            robot_view: lock_or(&self.current_robot_view, "CURRENT_ROBOT_VIEW", || vec![vec![None; 3]; 3]),
+            backpack: lock_or(&self.current_robot_backpack, "CURRENT_ROBOT_BACKPACK", BackpackInfo::default),
+            energy: lock_or(&self.current_robot_energy, "CURRENT_ROBOT_ENERGY", || 0),
+            score: lock_or(&self.score, "SCORE", || 0.0),
+            environment: lock_or(&self.current_environment, "CURRENT_ENVIRONMENT", EnvironmentInfo::default),
+            event_log: lock_or(&self.event_log, "EVENT_LOG", EventLog::default),
+            history: match self.history.lock() {
+                Ok(lock) => lock.tail(HISTORY_TAIL_TICKS),
+                Err(e) => {
+                    error!(target: "sender", "Couldnt lock HISTORY in sender thread: {} -> value has been set to a default value", e);
+                    RunHistory::new()
+                }
+            },
+            //only the last sample is sent, the whole series stays with the robot
+            metrics: match self.metrics.lock() {
+                Ok(lock) => lock.current(),
+                Err(e) => {
+                    error!(target: "sender", "Couldnt lock METRICS in sender thread: {} -> value has been set to a default value", e);
+                    MetricsSample::default()
+                }
+            },
+            teleports: find_teleports(&robot_map),
+            last_jump: lock_or(&self.last_jump, "LAST_JUMP", || None),
+        }
+    }
+}
+
+/// Clones the value guarded by the mutex, or returns the default if the mutex is poisoned.
+fn lock_or<T: Clone>(resource: &Mutex<T>, name: &str, default: impl FnOnce() -> T) -> T {
+    match resource.lock() {
+        Ok(lock) => lock.clone(),
+        Err(e) => {
+            error!(target: "sender", "Couldnt lock {} in sender thread: {} -> value has been set to a default value", name, e);
+            default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_state_has_an_undiscovered_map_and_no_ticks() {
+        let mut state = VisualizerState::new(4);
+        assert_eq!(state.tile_colors.len(), 4);
+        assert!(state.tile_colors.iter().all(|row| row.len() == 4));
+        assert_eq!(state.tick(), 0);
+
+        state.history.record(2, 10, 1.0);
+        assert_eq!(state.tick(), 3);
+    }
+
+    #[test]
+    fn poisoned_resources_fall_back_to_the_default() {
+        let resource = Arc::new(Mutex::new(7));
+        assert_eq!(lock_or(&resource, "TEST", || 0), 7);
+
+        let poisoner = Arc::clone(&resource);
+        let _ = std::thread::spawn(move || {
+            let _lock = poisoner.lock().unwrap();
+            panic!("poisoning the mutex");
+        })
+        .join();
+        assert_eq!(lock_or(&resource, "TEST", || 0), 0);
+    }
+}
@@ -0,0 +1,221 @@
+use std::time::{Duration, Instant};
+
+use robotics_lib::world::tile::{Tile, TileType};
+
+///# Constants
+///* `ACTIVE_TELEPORT_COLOR`, `INACTIVE_TELEPORT_COLOR`: The border and label of a teleport, by activation state.
+///* `TELEPORT_LINK_COLOR`: The lines connecting the active teleports.
+///* `JUMP_DURATION`: How long the jump of the robot between two teleports is animated.
+pub const ACTIVE_TELEPORT_COLOR: [f32; 4] = [1.0, 0.3, 1.0, 1.0];
+pub const INACTIVE_TELEPORT_COLOR: [f32; 4] = [0.55, 0.45, 0.55, 1.0];
+pub const TELEPORT_LINK_COLOR: [f32; 4] = [1.0, 0.3, 1.0, 0.35];
+pub const JUMP_DURATION: Duration = Duration::from_millis(600);
+
+/// A teleport tile of the discovered map.
+///
+/// Attributes:
+/// - `coordinates`: The position of the tile as (row, col).
+/// - `active`: The payload of `TileType::Teleport`, the robot can only jump between active teleports.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TeleportTile {
+    pub coordinates: (usize, usize),
+    pub active: bool,
+}
+
+/// The teleport tiles of a robot map, in row-major order.
+pub fn find_teleports(robot_map: &Option<Vec<Vec<Option<Tile>>>>) -> Vec<TeleportTile> {
+    let Some(map) = robot_map else {
+        return Vec::new();
+    };
+    let mut teleports = Vec::new();
+    for (row, tiles) in map.iter().enumerate() {
+        for (col, tile) in tiles.iter().enumerate() {
+            if let Some(Tile {
+                tile_type: TileType::Teleport(active),
+                ..
+            }) = tile
+            {
+                teleports.push(TeleportTile {
+                    coordinates: (row, col),
+                    active: *active,
+                });
+            }
+        }
+    }
+    teleports
+}
+
+/// A teleport shown by the window, with the number of its label.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TeleportLink {
+    pub number: usize,
+    pub coordinates: (usize, usize),
+    pub active: bool,
+}
+
+/// The teleports discovered during the run, numbered in order of discovery so that the
+/// labels don't change when new teleports are found.
+#[derive(Clone, Debug, Default)]
+pub struct TeleportLinks {
+    links: Vec<TeleportLink>,
+}
+
+impl TeleportLinks {
+    /// Adds the teleports discovered since the last update and updates the activation state
+    /// of the known ones; an activated teleport stays active.
+    pub fn update<'a>(&mut self, teleports: impl IntoIterator<Item = &'a TeleportTile>) {
+        for teleport in teleports {
+            match self.links.iter_mut().find(|link| link.coordinates == teleport.coordinates) {
+                Some(link) => link.active |= teleport.active,
+                None => self.links.push(TeleportLink {
+                    number: self.links.len() + 1,
+                    coordinates: teleport.coordinates,
+                    active: teleport.active,
+                }),
+            }
+        }
+    }
+
+    pub fn links(&self) -> &[TeleportLink] {
+        &self.links
+    }
+
+    pub fn get(&self, coordinates: (usize, usize)) -> Option<&TeleportLink> {
+        self.links.iter().find(|link| link.coordinates == coordinates)
+    }
+}
+
+/// A jump of the robot between two teleports, recorded when the move happens.
+///
+/// Attributes:
+/// - `number`: How many jumps the robot did until this one, used to tell a new jump from the last one.
+/// - `from`, `to`: The two teleports as (row, col).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TeleportJump {
+    pub number: usize,
+    pub from: (usize, usize),
+    pub to: (usize, usize),
+}
+
+/// Recognizes the jump of a robot from a `Moved` event: a move onto a teleport from a
+/// position that is not next to it.
+///
+/// # Arguments
+/// * `from` - The position of the robot before the move, as (row, col).
+/// * `to` - The position of the robot after the move, as (row, col).
+/// * `destination` - The tile the robot moved onto.
+pub fn is_teleport_jump(from: (usize, usize), to: (usize, usize), destination: &Tile) -> bool {
+    let distance = from.0.abs_diff(to.0) + from.1.abs_diff(to.1);
+    distance > 1 && matches!(destination.tile_type, TileType::Teleport(_))
+}
+
+/// The robot flying from a teleport to another one.
+///
+/// Attributes:
+/// - `from`, `to`: The two teleports as (row, col).
+/// - `started`: When the jump was detected.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JumpAnimation {
+    pub from: (usize, usize),
+    pub to: (usize, usize),
+    pub started: Instant,
+}
+
+impl JumpAnimation {
+    /// # Arguments
+    /// * `jump` - The jump to animate.
+    /// * `started` - When the window received the jump.
+    pub fn new(jump: &TeleportJump, started: Instant) -> Self {
+        Self {
+            from: jump.from,
+            to: jump.to,
+            started,
+        }
+    }
+
+    /// How much of the jump is done, from 0 to 1.
+    pub fn progress(&self, now: Instant) -> f64 {
+        (now.saturating_duration_since(self.started).as_secs_f64() / JUMP_DURATION.as_secs_f64()).min(1.0)
+    }
+
+    pub fn is_over(&self, now: Instant) -> bool {
+        self.progress(now) >= 1.0
+    }
+
+    /// The position of the robot during the jump as (row, col), in tiles: it speeds up
+    /// when leaving the first teleport and slows down when reaching the second one.
+    pub fn position(&self, now: Instant) -> (f64, f64) {
+        let t = self.progress(now);
+        let eased = t * t * (3.0 - 2.0 * t);
+        (
+            self.from.0 as f64 + (self.to.0 as f64 - self.from.0 as f64) * eased,
+            self.from.1 as f64 + (self.to.1 as f64 - self.from.1 as f64) * eased,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use robotics_lib::world::tile::Content;
+
+    use super::*;
+
+    fn teleport(active: bool) -> Option<Tile> {
+        Some(Tile {
+            tile_type: TileType::Teleport(active),
+            content: Content::None,
+            elevation: 0,
+        })
+    }
+
+    #[test]
+    fn teleports_keep_their_number_and_activation() {
+        let mut links = TeleportLinks::default();
+        let map = Some(vec![vec![None, teleport(false)], vec![None, None]]);
+        links.update(&find_teleports(&map));
+
+        let map = Some(vec![vec![None, teleport(true)], vec![teleport(false), None]]);
+        links.update(&find_teleports(&map));
+        assert_eq!(
+            links.links(),
+            &[
+                TeleportLink {
+                    number: 1,
+                    coordinates: (0, 1),
+                    active: true
+                },
+                TeleportLink {
+                    number: 2,
+                    coordinates: (1, 0),
+                    active: false
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn only_distant_moves_onto_teleports_are_jumps() {
+        let destination = teleport(true).unwrap();
+        let grass = Tile {
+            tile_type: TileType::Grass,
+            content: Content::None,
+            elevation: 0,
+        };
+        assert!(!is_teleport_jump((0, 0), (0, 1), &destination));
+        assert!(!is_teleport_jump((0, 0), (3, 3), &grass));
+        assert!(is_teleport_jump((0, 0), (5, 5), &destination));
+
+        let now = Instant::now();
+        let jump = JumpAnimation::new(
+            &TeleportJump {
+                number: 1,
+                from: (0, 0),
+                to: (5, 5),
+            },
+            now,
+        );
+        assert_eq!(jump.position(now), (0.0, 0.0));
+        assert_eq!(jump.position(now + JUMP_DURATION / 2), (2.5, 2.5));
+        assert!(jump.is_over(now + JUMP_DURATION));
+    }
+}
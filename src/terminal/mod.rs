@@ -0,0 +1,207 @@
+use std::fmt::Write as FmtWrite;
+use std::io::{self, Write};
+
+use crate::grid::DEFAULT_MAX_ENERGY;
+use crate::state::VisualizerState;
+use crate::util::apply_environment_tint;
+
+///# Constants
+///* `DEFAULT_TERMINAL_SIZE`: The size (columns, rows) used when the terminal size is unknown.
+///* `HUD_ROWS`: The rows below the map used by the HUD.
+pub const DEFAULT_TERMINAL_SIZE: (usize, usize) = (100, 40);
+pub const HUD_ROWS: usize = 8;
+
+const RESET: &str = "\x1b[0m";
+const ROBOT_FOREGROUND: [u8; 3] = [191, 139, 255];
+const UNDISCOVERED_BACKGROUND: [u8; 3] = [30, 30, 30];
+
+/// Renders a `VisualizerState` in a terminal with ANSI truecolor escape sequences,
+/// for machines without a display (e.g. over SSH).
+///
+/// Every tile takes two characters, so that tiles look square: the background is the
+/// tile type color, a `()` in the content color marks a content and `@@` marks the robot.
+/// The visible part of the map follows the robot; below the map a HUD shows coordinates,
+/// energy, score, environment, backpack and the latest events.
+///
+/// Attributes:
+/// - `columns`, `rows`: The size of the terminal, in characters.
+pub struct TerminalRenderer {
+    columns: usize,
+    rows: usize,
+}
+
+impl TerminalRenderer {
+    pub fn new(columns: usize, rows: usize) -> Self {
+        Self {
+            columns: columns.max(20),
+            rows: rows.max(HUD_ROWS + 4),
+        }
+    }
+
+    /// Uses the `COLUMNS` and `LINES` environment variables, or `DEFAULT_TERMINAL_SIZE`.
+    pub fn from_env() -> Self {
+        let read = |name: &str| std::env::var(name).ok().and_then(|value| value.parse::<usize>().ok());
+        Self::new(
+            read("COLUMNS").unwrap_or(DEFAULT_TERMINAL_SIZE.0),
+            read("LINES").unwrap_or(DEFAULT_TERMINAL_SIZE.1),
+        )
+    }
+
+    /// The amount of tiles (columns, rows) that fit in the terminal.
+    pub fn viewport_size(&self) -> (usize, usize) {
+        (self.columns / 2, self.rows - HUD_ROWS)
+    }
+
+    /// Returns the first visible (row, col) of the map, so that the robot is centered
+    /// while the viewport stays inside the map.
+    ///
+    /// # Arguments
+    /// * `coordinates` - The robot's coordinates as (row, col).
+    /// * `map_size` - The size of the map as (rows, cols).
+    pub fn viewport_origin(&self, coordinates: (usize, usize), map_size: (usize, usize)) -> (usize, usize) {
+        let (viewport_cols, viewport_rows) = self.viewport_size();
+        let origin = |position: usize, visible: usize, size: usize| {
+            position
+                .saturating_sub(visible / 2)
+                .min(size.saturating_sub(visible))
+        };
+        (
+            origin(coordinates.0, viewport_rows, map_size.0),
+            origin(coordinates.1, viewport_cols, map_size.1),
+        )
+    }
+
+    /// Renders the state as a string of ANSI escape sequences, starting from the top-left
+    /// corner of the terminal (the screen is not cleared, so that redrawing doesn't flicker).
+    pub fn render(&self, state: &VisualizerState) -> String {
+        let mut output = String::from("\x1b[H");
+        let map_rows = state.tile_colors.len();
+        let map_cols = state.tile_colors.first().map(|row| row.len()).unwrap_or(0);
+        let (viewport_cols, viewport_rows) = self.viewport_size();
+        let (start_row, start_col) = self.viewport_origin(state.coordinates, (map_rows, map_cols));
+
+        for row in start_row..(start_row + viewport_rows).min(map_rows) {
+            for col in start_col..(start_col + viewport_cols).min(map_cols) {
+                let tile_color = state.tile_colors[row][col];
+                let background = if tile_color[3] == 0.0 {
+                    UNDISCOVERED_BACKGROUND
+                } else {
+                    to_rgb(apply_environment_tint(tile_color, &state.environment))
+                };
+
+                let content_color = state.content_colors.get(row).and_then(|r| r.get(col)).copied();
+                let (foreground, text) = if (row, col) == state.coordinates {
+                    (ROBOT_FOREGROUND, "@@")
+                } else {
+                    match content_color {
+                        Some(color) if color[3] > 0.0 && tile_color[3] > 0.0 => (to_rgb(color), "()"),
+                        _ => (background, "  "),
+                    }
+                };
+                let _ = write!(
+                    output,
+                    "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m{}",
+                    foreground[0], foreground[1], foreground[2], background[0], background[1], background[2], text
+                );
+            }
+            output += RESET;
+            output += "\x1b[K\n";
+        }
+
+        for line in self.hud_lines(state) {
+            output += &line;
+            output += "\x1b[K\n";
+        }
+        output += "\x1b[J";
+        output
+    }
+
+    /// Writes the rendered state to the given output and flushes it.
+    pub fn draw<W: Write>(&self, state: &VisualizerState, out: &mut W) -> io::Result<()> {
+        out.write_all(self.render(state).as_bytes())?;
+        out.flush()
+    }
+
+    /// Clears the terminal and hides the cursor, to be called before the first `draw`.
+    pub fn start<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        out.write_all(b"\x1b[2J\x1b[?25l")?;
+        out.flush()
+    }
+
+    /// Restores the cursor and the colors, to be called when the rendering stops.
+    pub fn stop<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "{}\x1b[?25h", RESET)?;
+        out.flush()
+    }
+
+    fn hud_lines(&self, state: &VisualizerState) -> Vec<String> {
+        let bar_width = 20;
+        let filled = (state.energy.min(DEFAULT_MAX_ENERGY) * bar_width) / DEFAULT_MAX_ENERGY;
+        let mut lines = vec![
+            format!(
+                "tick {}  robot coordinates:({},{})  SCORE: {}",
+                state.tick(),
+                state.coordinates.1,
+                state.coordinates.0,
+                state.score.floor()
+            ),
+            format!(
+                "ENERGY: [{}{}] {}",
+                "#".repeat(filled),
+                "-".repeat(bar_width - filled),
+                state.energy
+            ),
+            state.environment.to_text(),
+            state.backpack.to_text(),
+        ];
+        for entry in state.event_log.entries().iter().rev().take(HUD_ROWS - lines.len()) {
+            lines.push(entry.to_text());
+        }
+        lines
+            .into_iter()
+            .map(|line| line.chars().take(self.columns).collect())
+            .collect()
+    }
+}
+
+fn to_rgb(color: [f32; 4]) -> [u8; 3] {
+    [
+        (color[0].clamp(0.0, 1.0) * 255.0).round() as u8,
+        (color[1].clamp(0.0, 1.0) * 255.0).round() as u8,
+        (color[2].clamp(0.0, 1.0) * 255.0).round() as u8,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn viewport_follows_the_robot_inside_the_map() {
+        // 10 columns and 6 rows of tiles
+        let renderer = TerminalRenderer::new(20, HUD_ROWS + 6);
+        assert_eq!(renderer.viewport_size(), (10, 6));
+
+        assert_eq!(renderer.viewport_origin((15, 15), (30, 30)), (12, 10));
+        // clamped at the top left corner
+        assert_eq!(renderer.viewport_origin((2, 3), (30, 30)), (0, 0));
+        // clamped at map_size - viewport
+        assert_eq!(renderer.viewport_origin((29, 28), (30, 30)), (24, 20));
+        // a map smaller than the viewport starts at 0
+        assert_eq!(renderer.viewport_origin((3, 3), (4, 4)), (0, 0));
+    }
+
+    #[test]
+    fn render_marks_the_robot_and_shows_the_energy() {
+        // wide enough for the HUD lines not to be cut
+        let renderer = TerminalRenderer::new(80, HUD_ROWS + 6);
+        let mut state = VisualizerState::new(3);
+        state.coordinates = (1, 2);
+        state.energy = DEFAULT_MAX_ENERGY / 2;
+
+        let output = renderer.render(&state);
+        assert_eq!(output.matches("@@").count(), 1);
+        assert!(output.contains("robot coordinates:(2,1)"));
+        assert!(output.contains(&format!("ENERGY: [{}{}]", "#".repeat(10), "-".repeat(10))));
+    }
+}
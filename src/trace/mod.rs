@@ -0,0 +1,154 @@
+use std::fs::File;
+use std::io::{LineWriter, Write};
+use std::path::Path;
+
+use robotics_lib::event::events::Event;
+use serde::Serialize;
+
+use crate::events::{describe_event, EventKind};
+
+/// A single line of the trace, serialized as a JSON object.
+///
+/// Attributes:
+/// - `tick`: The tick in which the event happened.
+/// - `kind`: The category of the event (see `EventKind`).
+/// - `message`: The same description shown in the event log panel.
+/// - `energy`: The energy of the robot after the event.
+/// - `coordinates`: `[row, col]` for `Moved` and `TileContentUpdated`.
+/// - `tile_type`, `content`, `amount`: The payload of the event, when it has one.
+#[derive(Clone, Debug, Serialize)]
+pub struct TraceRecord {
+    pub tick: usize,
+    pub kind: EventKind,
+    pub message: String,
+    pub energy: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coordinates: Option<(usize, usize)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tile_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<usize>,
+}
+
+impl TraceRecord {
+    pub fn from_event(tick: usize, energy: usize, event: &Event) -> Self {
+        let mut record = Self {
+            tick,
+            kind: EventKind::from_event(event),
+            message: describe_event(event),
+            energy,
+            coordinates: None,
+            tile_type: None,
+            content: None,
+            amount: None,
+        };
+
+        match event {
+            Event::Moved(tile, coordinates) | Event::TileContentUpdated(tile, coordinates) => {
+                record.coordinates = Some(*coordinates);
+                record.tile_type = Some(format!("{:?}", tile.tile_type));
+                record.content = Some(format!("{:?}", tile.content));
+            }
+            Event::AddedToBackpack(content, amount) | Event::RemovedFromBackpack(content, amount) => {
+                record.content = Some(format!("{:?}", content));
+                record.amount = Some(*amount);
+            }
+            Event::EnergyConsumed(amount) | Event::EnergyRecharged(amount) => {
+                record.amount = Some(*amount);
+            }
+            _ => {}
+        }
+        record
+    }
+}
+
+/// Writes the visualizer events as JSON lines (one `TraceRecord` per line),
+/// so that a run can be analyzed offline.
+pub struct TraceSink {
+    writer: LineWriter<File>,
+}
+
+impl TraceSink {
+    /// Creates (or truncates) the trace file.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the JSON-lines file.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let file = File::create(path.as_ref())
+            .map_err(|e| format!("Error creating trace file '{}': {}", path.as_ref().display(), e))?;
+        Ok(Self {
+            writer: LineWriter::new(file),
+        })
+    }
+
+    /// Appends an event to the trace.
+    ///
+    /// # Arguments
+    /// * `tick` - The tick in which the event happened.
+    /// * `energy` - The energy of the robot after the event.
+    /// * `event` - The event to record.
+    pub fn record(&mut self, tick: usize, energy: usize, event: &Event) -> Result<(), String> {
+        self.write_record(&TraceRecord::from_event(tick, energy, event))
+    }
+
+    pub fn write_record(&mut self, record: &TraceRecord) -> Result<(), String> {
+        let line = serde_json::to_string(record).map_err(|e| e.to_string())?;
+        writeln!(self.writer, "{}", line).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use robotics_lib::world::tile::{Content, Tile, TileType};
+
+    use super::*;
+
+    #[test]
+    fn records_keep_the_payload_of_the_event() {
+        let tile = Tile {
+            tile_type: TileType::Grass,
+            content: Content::Rock(2),
+            elevation: 0,
+        };
+        let moved = TraceRecord::from_event(4, 90, &Event::Moved(tile, (1, 3)));
+        assert_eq!(moved.tick, 4);
+        assert_eq!(moved.energy, 90);
+        assert_eq!(moved.coordinates, Some((1, 3)));
+        assert_eq!(moved.tile_type.as_deref(), Some("Grass"));
+        assert_eq!(moved.content.as_deref(), Some("Rock(2)"));
+        assert_eq!(moved.amount, None);
+
+        let added = TraceRecord::from_event(5, 80, &Event::AddedToBackpack(Content::Coin(0), 3));
+        assert_eq!(added.coordinates, None);
+        assert_eq!(added.content.as_deref(), Some("Coin(0)"));
+        assert_eq!(added.amount, Some(3));
+
+        let consumed = TraceRecord::from_event(6, 70, &Event::EnergyConsumed(10));
+        assert_eq!(consumed.content, None);
+        assert_eq!(consumed.amount, Some(10));
+    }
+
+    #[test]
+    fn sink_writes_one_json_object_per_line() {
+        let path = std::env::temp_dir().join(format!("visualizer-trace-{}.jsonl", std::process::id()));
+        {
+            let mut sink = TraceSink::create(&path).unwrap();
+            sink.record(1, 100, &Event::Ready).unwrap();
+            sink.record(2, 95, &Event::EnergyConsumed(5)).unwrap();
+            sink.record(3, 95, &Event::Terminated).unwrap();
+        }
+
+        let text = fs::read_to_string(&path).unwrap();
+        let lines: Vec<serde_json::Value> = text.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1]["tick"], 2);
+        assert_eq!(lines[1]["energy"], 95);
+        assert_eq!(lines[1]["amount"], 5);
+        assert!(lines[0].get("coordinates").is_none());
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -1,71 +1,140 @@
 use robotics_lib::runner::backpack::BackPack;
-use rodio::source::Source;
-use rodio::{Decoder, OutputStream};
-use std::fs::File;
-use std::io::BufReader;
 use std::path::Path;
 use std::process::{Command, Stdio};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
 use std::{fs, io};
 
+use log::info;
+
+use robotics_lib::world::environmental_conditions::{EnvironmentalConditions, WeatherType};
 use robotics_lib::world::tile::{Content, Tile, TileType};
 
+use crate::state::VisualizerState;
+
 ///# Types
 /// * `Infos': The type used to pass information from the robot's channel to the UI
-///     * Tile Type converted to color matrix
-///     * Content converted to color matrix
-///     * Coordinates of the robot
-///     * Robot View
-///     * Backpack
-///     * Energy
-///     * Score
-pub type Infos = (
-    Vec<Vec<[f32; 4]>>,
-    Vec<Vec<[f32; 4]>>,
-    (usize, usize),
-    Vec<Vec<Option<Tile>>>,
-    String,
-    usize,
-    f32
-);
+pub type Infos = VisualizerState;
+
+/// Snapshot of the `EnvironmentalConditions` the robot is currently living in.
+///
+/// `EnvironmentalConditions` keeps its fields private, so the values the visualizer
+/// needs are extracted once when a `TimeChanged`/`DayChanged` event is received.
+///
+/// Attributes:
+/// - `weather`: The current weather.
+/// - `time`: The time of day formatted as `HH:MM`.
+/// - `hour`, `minute`: The time of day as numbers, used to compute the night darkening.
+/// - `day`: The day counter, starting from 1 and increased on every `DayChanged` event.
+#[derive(Clone, Debug)]
+pub struct EnvironmentInfo {
+    pub weather: WeatherType,
+    pub time: String,
+    pub hour: u8,
+    pub minute: u8,
+    pub day: usize,
+}
+
+impl Default for EnvironmentInfo {
+    fn default() -> Self {
+        Self {
+            weather: WeatherType::Sunny,
+            time: "12:00".to_string(),
+            hour: 12,
+            minute: 0,
+            day: 1,
+        }
+    }
+}
+
+impl EnvironmentInfo {
+    /// Builds the snapshot from the conditions carried by a `TimeChanged`/`DayChanged` event.
+    ///
+    /// # Arguments
+    /// * `conditions` - The environmental conditions received with the event.
+    /// * `day` - The day counter to associate with the snapshot.
+    pub fn from_conditions(conditions: &EnvironmentalConditions, day: usize) -> Self {
+        let time = conditions.get_time_of_day_string();
+        let mut parts = time.split(':').map(|part| part.trim().parse::<u8>().unwrap_or(0));
+        let hour = parts.next().unwrap_or(0);
+        let minute = parts.next().unwrap_or(0);
+
+        Self {
+            weather: conditions.get_weather_condition(),
+            time,
+            hour,
+            minute,
+            day,
+        }
+    }
+
+    /// Returns how much the map should be darkened, from 0.0 (full daylight) to
+    /// `MAX_NIGHT_DARKNESS` (middle of the night), fading in at dusk and out at dawn.
+    pub fn darkness(&self) -> f32 {
+        let hour = self.hour as f32 + self.minute as f32 / 60.0;
+        let factor = if hour < DAWN.0 || hour >= DUSK.1 {
+            1.0
+        } else if hour < DAWN.1 {
+            (DAWN.1 - hour) / (DAWN.1 - DAWN.0)
+        } else if hour < DUSK.0 {
+            0.0
+        } else {
+            (hour - DUSK.0) / (DUSK.1 - DUSK.0)
+        };
+        factor * MAX_NIGHT_DARKNESS
+    }
+
+    /// Returns true if it is raining, which is rendered with a rain overlay.
+    pub fn is_raining(&self) -> bool {
+        matches!(self.weather, WeatherType::Rainy | WeatherType::TropicalMonsoon)
+    }
+
+    /// Text shown in the HUD, e.g. `DAY 3  14:30  Sunny`.
+    pub fn to_text(&self) -> String {
+        format!("DAY {}  {}  {:?}", self.day, self.time, self.weather)
+    }
+}
 
 ///# Constants
 ///* `DEFAULT_PNGS_PATH`: The default directory path where PNG files are stored.
 ///* `DEFAULT_SOUNDS_PATH`: The default directory path where sound files are stored.
+///* `DEFAULT_HISTORY_PATH`: The default path of the csv file with the energy and score of every tick.
+///* `DEFAULT_VIDEO_PATH`: The default path of the video made from the PNG files.
 pub const DEFAULT_PNGS_PATH: &str = "../pngs";
+pub const DEFAULT_VIDEO_PATH: &str = "output_video.mp4";
 pub const DEFAULT_SOUNDS_PATH: &str = "../sounds";
+pub const DEFAULT_HISTORY_PATH: &str = "../history.csv";
+
+///* `MAX_NIGHT_DARKNESS`: How much the map is darkened in the middle of the night (0.0 - 1.0).
+///* `DAWN`, `DUSK`: Hours (start, end) in which the darkness fades out and in.
+///* `RAIN_TINT`: The RGBA color blended over the map while it is raining.
+pub const MAX_NIGHT_DARKNESS: f32 = 0.6;
+pub const DAWN: (f32, f32) = (5.0, 8.0);
+pub const DUSK: (f32, f32) = (18.0, 21.0);
+pub const RAIN_TINT: [f32; 4] = [0.1, 0.2, 0.5, 0.2];
 
 /// Converts a numerical ID to a file path string.
 ///
 /// # Arguments
+/// * `directory` - The directory the frame is saved in.
 /// * `id` - The frame ID to convert.
 ///
 /// # Returns
-/// A file path string with leading zeroes based on the ID's value.
-pub(crate) fn id_to_path_string(id: usize) -> String {
-    match id {
-        0..=9 => format!("{}/0000{}.png", DEFAULT_PNGS_PATH, id),
-        10..=99 => format!("{}/000{}.png", DEFAULT_PNGS_PATH, id),
-        100..=999 => format!("{}/00{}.png", DEFAULT_PNGS_PATH, id),
-        1000..=9999 => format!("{}/0{}.png", DEFAULT_PNGS_PATH, id),
-        10000..=99999 => format!("{}/{}.png", DEFAULT_PNGS_PATH, id),
-        _ => format!("99999/{}.png", DEFAULT_PNGS_PATH),
-    }
+/// A file path string with leading zeroes based on the ID's value, the pattern read by `gif_creator`.
+pub(crate) fn id_to_path_string(directory: &str, id: usize) -> String {
+    format!("{}/{:05}.png", directory, id)
 }
 
 /// Generates a GIF animation from sequentially numbered PNG files found in the
-/// default PNG directory. Utilizes FFmpeg for the conversion process.
+/// given directory. Utilizes FFmpeg, looked up on the PATH, for the conversion process.
+///
+/// # Arguments
+/// * `directory` - The directory containing the PNG files.
+/// * `output_video` - The path of the video file.
 ///
 /// # Returns
 /// A result indicating success or failure of the GIF creation process.
-pub fn gif_creator() -> Result<(), String> {
-    // Specify the directory containing the PNG files
-    let directory = DEFAULT_PNGS_PATH;
-    // Specify the output video file name
-    let output_video = "output_video.mp4";
-
-    return match Command::new("/opt/homebrew/bin/ffmpeg")
+pub fn gif_creator(directory: &str, output_video: &str) -> Result<(), String> {
+    return match Command::new("ffmpeg")
         .args(&[
             "-y", // Overwrite output files without asking
             "-framerate",
@@ -87,13 +156,14 @@ pub fn gif_creator() -> Result<(), String> {
         .spawn()
     {
         Ok(ffmpeg_cmd) => match ffmpeg_cmd.wait_with_output() {
-            Ok(output) => {
-                println!("ffmpeg finished with status: {}", output.status);
+            Ok(output) if output.status.success() => {
+                info!("ffmpeg finished with status: {}", output.status);
                 Ok(())
             }
+            Ok(output) => Err(format!("ffmpeg finished with status: {}", output.status)),
             Err(e) => Err(e.to_string()),
         },
-        Err(e) => Err(e.to_string()),
+        Err(e) => Err(format!("Couldnt run ffmpeg: {}", e)),
     };
 }
 
@@ -199,63 +269,111 @@ pub fn match_content_color_to_type_piston(tile_contet: &Content) -> [f32; 4] {
     ]
 }
 
-/// Plays a sound file from the default sounds directory with an optional
-/// amplification value.
+/// Applies the night darkening and the rain tint of the given environment to a color,
+/// the same way the window overlays do, so that exported frames look like the window.
 ///
 /// # Arguments
-/// * `path` - The path to the sound file, relative to the default sounds directory.
-/// * `amplify_value` - The amplification factor for the sound playback.
+/// * `color` - The RGBA color to tint.
+/// * `environment` - The environmental conditions to apply.
 ///
 /// # Returns
-/// A result indicating success or failure of the sound playback operation.
-pub fn play_sound(path: &str, amplify_value: f32) -> Result<(), String> {
-    let final_path = format!("{}{}", DEFAULT_SOUNDS_PATH, path);
-    // Try to get the default output stream
-    let (_stream, stream_handle) =
-        OutputStream::try_default().map_err(|e| format!("Error obtaining output stream: {}", e))?;
-
-    // Try to open the file
-    let file = BufReader::new(
-        File::open(final_path).map_err(|e| format!("Error opening file '{}': {}", path, e))?,
-    );
-
-    // Try to decode the sound file
-    let source = Decoder::new(file)
-        .map_err(|e| format!("Error decoding '{}': {}", path, e))?
-        .amplify(amplify_value);
-
-    // Try to play the sound
-    stream_handle
-        .play_raw(source.convert_samples())
-        .map_err(|e| format!("Error playing sound: {}", e))?;
-
-    // Sleep for a while to allow the sound to play
-    std::thread::sleep(Duration::from_secs_f64(0.5));
-    Ok(())
+/// The tinted RGBA color, with the original transparency.
+pub fn apply_environment_tint(color: [f32; 4], environment: &EnvironmentInfo) -> [f32; 4] {
+    let mut result = color;
+    if environment.is_raining() {
+        for (channel, tint) in result.iter_mut().zip(RAIN_TINT).take(3) {
+            *channel = *channel * (1.0 - RAIN_TINT[3]) + tint * RAIN_TINT[3];
+        }
+    }
+    let light = 1.0 - environment.darkness();
+    for channel in result.iter_mut().take(3) {
+        *channel *= light;
+    }
+    result
 }
 
-/// Converts the contents of a `BackPack` object into a formatted string
-/// representation, listing each item and its quantity.
+/// A change of the backpack, used by the backpack panel to highlight the changed content.
 ///
-/// # Arguments
-/// * `backpack` - The `BackPack` object to convert.
+/// Attributes:
+/// - `content`: The content that was added or removed.
+/// - `amount`: The amount added (positive) or removed (negative).
+/// - `tick`: The tick in which the change happened.
+#[derive(Clone, Debug)]
+pub struct BackpackChange {
+    pub content: Content,
+    pub amount: isize,
+    pub tick: usize,
+}
+
+/// Snapshot of the robot's `BackPack` as typed data.
 ///
-/// # Returns
-/// A string representation of the backpack's contents.
-pub fn backpack_to_text(backpack: &BackPack) -> String {
-    if backpack.get_size() > 0 && !backpack.get_contents().is_empty() {
-        let mut result = format!("Backpack (Size: {}):  ", backpack.get_size());
-        for (content, size) in backpack.get_contents() {
-            if size > &(0usize) {
+/// Attributes:
+/// - `size`: The capacity of the backpack.
+/// - `contents`: Every content with a quantity greater than zero, sorted by name.
+/// - `last_change`: The last addition or removal, if any.
+#[derive(Clone, Debug, Default)]
+pub struct BackpackInfo {
+    pub size: usize,
+    pub contents: Vec<(Content, usize)>,
+    pub last_change: Option<BackpackChange>,
+}
+
+impl BackpackInfo {
+    /// Converts the contents of a `BackPack` object into a `BackpackInfo`.
+    ///
+    /// # Arguments
+    /// * `backpack` - The `BackPack` object to convert.
+    /// * `last_change` - The change that produced this state of the backpack, if any.
+    pub fn from_backpack(backpack: &BackPack, last_change: Option<BackpackChange>) -> Self {
+        let mut contents: Vec<(Content, usize)> = backpack
+            .get_contents()
+            .iter()
+            .filter(|(_, quantity)| **quantity > 0)
+            .map(|(content, quantity)| (content.clone(), *quantity))
+            .collect();
+        contents.sort_by_key(|(content, _)| content.to_string());
+
+        Self {
+            size: backpack.get_size(),
+            contents,
+            last_change,
+        }
+    }
+
+    /// The amount of items in the backpack.
+    pub fn used(&self) -> usize {
+        self.contents.iter().map(|(_, quantity)| quantity).sum()
+    }
+
+    /// Returns the quantity of the given content (whatever its value), 0 if missing.
+    pub fn quantity_of(&self, content: &Content) -> usize {
+        self.contents
+            .iter()
+            .find(|(other, _)| same_content_kind(other, content))
+            .map(|(_, quantity)| *quantity)
+            .unwrap_or(0)
+    }
+
+    /// A formatted string listing each item and its quantity.
+    pub fn to_text(&self) -> String {
+        if self.size > 0 && !self.contents.is_empty() {
+            let mut result = format!("Backpack (Size: {}):  ", self.size);
+            for (content, size) in &self.contents {
                 result += &format!("{}: {}  ", content, size);
             }
+            result
+        } else {
+            "Empty backpack".to_string()
         }
-        result
-    } else {
-        "Empty backpack".to_string()
     }
 }
 
+/// Returns true if the two contents are the same variant, ignoring the value they carry
+/// (the backpack stores e.g. `Rock(0)` while the events carry the amount).
+pub fn same_content_kind(a: &Content, b: &Content) -> bool {
+    std::mem::discriminant(a) == std::mem::discriminant(b)
+}
+
 pub fn convert_to_color_matrix(
     tile_matrix: &Option<Vec<Vec<Option<Tile>>>>,
     color_matrix: &Arc<Mutex<Vec<Vec<[f32; 4]>>>>,
@@ -269,7 +387,9 @@ pub fn convert_to_color_matrix(
                     Some(tile) => match_color_to_type_piston(&tile.tile_type),
                     None => [0.0, 0.0, 0.0, 0.0], // Default color for None
                 };
-                color_matrix_guard[j][i] = color;
+                if let Some(cell) = color_matrix_guard.get_mut(i).and_then(|r| r.get_mut(j)) {
+                    *cell = color;
+                }
             }
         }
     }
@@ -278,13 +398,18 @@ pub fn convert_to_color_matrix(
 /// Converts a matrix of `Tile`s, `Content`, or robot views into a corresponding
 /// color matrix for visualization purposes, using predefined color mappings.
 ///
+/// Every matrix, in input and in output, is indexed as `matrix[row][col]`, like the
+/// robot map and the robot view returned by robotics_lib: the row is the y coordinate
+/// on screen and the column is the x coordinate.
+///
 /// # Arguments
 /// * Various, depending on the function.
 ///
 /// # Returns
 /// A color matrix for use in visual representations.
 pub fn convert_robot_view_to_color_matrix(view: &Vec<Vec<Option<Tile>>>) -> Vec<Vec<[f32; 4]>> {
-    let mut result = vec![vec![[0.0, 0.0, 0.0, 0.0]; 3]; 3];
+    let (rows, cols) = view_size(view);
+    let mut result = vec![vec![[0.0, 0.0, 0.0, 0.0]; cols]; rows];
 
 
     for (i, row) in view.iter().enumerate() {
@@ -293,14 +418,15 @@ pub fn convert_robot_view_to_color_matrix(view: &Vec<Vec<Option<Tile>>>) -> Vec<
                 Some(tile) => match_color_to_type_piston(&tile.tile_type),
                 None => [105.0/255.0 , 105.0/255.0 , 105.0/255.0 , 1.0],
             };
-            result[j][i] = color;
+            result[i][j] = color;
         }
     }
 
     result
 }
 pub fn convert_robot_content_view_to_color_matrix(view: &Vec<Vec<Option<Tile>>>) -> Vec<Vec<[f32; 4]>> {
-    let mut result = vec![vec![[0.0, 0.0, 0.0, 1.0]; 3]; 3];
+    let (rows, cols) = view_size(view);
+    let mut result = vec![vec![[0.0, 0.0, 0.0, 1.0]; cols]; rows];
 
 
     for (i, row) in view.iter().enumerate() {
@@ -309,12 +435,83 @@ pub fn convert_robot_content_view_to_color_matrix(view: &Vec<Vec<Option<Tile>>>)
                 Some(tile) => match_content_color_to_type_piston(&tile.content),
                 None => [0.0, 0.0, 0.0, 0.0],
             };
-            result[j][i] = color;
+            result[i][j] = color;
+        }
+    }
+
+    result
+}
+pub fn convert_robot_view_to_quantity_matrix(view: &Vec<Vec<Option<Tile>>>) -> Vec<Vec<Option<usize>>> {
+    let (rows, cols) = view_size(view);
+    let mut result = vec![vec![None; cols]; rows];
+
+    for (i, row) in view.iter().enumerate() {
+        for (j, tile_option) in row.iter().enumerate() {
+            result[i][j] = tile_option.as_ref().and_then(|tile| content_quantity(&tile.content));
         }
     }
 
     result
 }
+
+/// Returns the number of rows and columns of a view; the columns are the ones of
+/// the longest row, so that rows of different length don't go out of bounds.
+fn view_size(view: &Vec<Vec<Option<Tile>>>) -> (usize, usize) {
+    (view.len(), view.iter().map(|row| row.len()).max().unwrap_or(0))
+}
+
+/// Returns the quantity carried by a content, if it has one.
+///
+/// Contents holding a range (`Bin`, `Crate`, `Bank`) return the amount currently stored.
+pub fn content_quantity(content: &Content) -> Option<usize> {
+    match content {
+        Content::Rock(quantity)
+        | Content::Tree(quantity)
+        | Content::Garbage(quantity)
+        | Content::Coin(quantity)
+        | Content::Water(quantity)
+        | Content::Market(quantity)
+        | Content::Fish(quantity)
+        | Content::Bush(quantity)
+        | Content::JollyBlock(quantity) => Some(*quantity),
+        Content::Bin(range) | Content::Crate(range) | Content::Bank(range) => Some(range.start),
+        Content::Fire | Content::Building | Content::Scarecrow | Content::None => None,
+    }
+}
+
+/// Extracts from the discovered map the square of tiles centered on the robot, so that
+/// the robot view can be wider than the 3x3 returned by `robot_view`.
+///
+/// # Arguments
+/// * `map` - The robot's discovered map.
+/// * `coordinates` - The robot's coordinates as (row, col).
+/// * `radius` - How many tiles are shown on each side of the robot.
+///
+/// # Returns
+/// A (2 * radius + 1) x (2 * radius + 1) view, with `None` for undiscovered or out of map tiles.
+pub fn robot_map_view(
+    map: &Option<Vec<Vec<Option<Tile>>>>,
+    coordinates: (usize, usize),
+    radius: usize,
+) -> Vec<Vec<Option<Tile>>> {
+    let side = 2 * radius + 1;
+    let mut result = vec![vec![None; side]; side];
+
+    if let Some(map) = map {
+        for (i, result_row) in result.iter_mut().enumerate() {
+            let row = (coordinates.0 + i).checked_sub(radius);
+            for (j, result_tile) in result_row.iter_mut().enumerate() {
+                let col = (coordinates.1 + j).checked_sub(radius);
+                if let (Some(row), Some(col)) = (row, col) {
+                    *result_tile = map.get(row).and_then(|r| r.get(col)).cloned().flatten();
+                }
+            }
+        }
+    }
+
+    result
+}
+
 pub fn convert_content_to_color_matrix(
     tile_matrix: &Option<Vec<Vec<Option<Tile>>>>,
     color_matrix: &Arc<Mutex<Vec<Vec<[f32; 4]>>>>,
@@ -328,7 +525,9 @@ pub fn convert_content_to_color_matrix(
                     Some(tile) => match_content_color_to_type_piston(&tile.content),
                     None => [0.0, 0.0, 0.0, 0.0], // Default color for None
                 };
-                color_matrix_guard[j][i] = color;
+                if let Some(cell) = color_matrix_guard.get_mut(i).and_then(|r| r.get_mut(j)) {
+                    *cell = color;
+                }
             }
         }
     }
@@ -354,3 +553,168 @@ pub fn update_resource<T>(resource: &Mutex<T>, new_value: T) -> Result<(), Strin
         Err(_) => Err("Mutex was poisoned".to_string()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNDISCOVERED: [f32; 4] = [0.0, 0.0, 0.0, 0.0];
+
+    fn tile(tile_type: TileType, content: Content) -> Option<Tile> {
+        Some(Tile {
+            tile_type,
+            content,
+            elevation: 0,
+        })
+    }
+
+    /// 2 rows x 3 columns, every cell different so that a transposition is detected
+    fn asymmetric_view() -> Vec<Vec<Option<Tile>>> {
+        vec![
+            vec![
+                tile(TileType::Grass, Content::Rock(1)),
+                tile(TileType::Sand, Content::None),
+                None,
+            ],
+            vec![
+                tile(TileType::Lava, Content::Coin(7)),
+                tile(TileType::Snow, Content::Fire),
+                tile(TileType::DeepWater, Content::Bank(4..10)),
+            ],
+        ]
+    }
+
+    fn tile_type_of(tile: &Option<Tile>) -> Option<TileType> {
+        tile.as_ref().map(|tile| tile.tile_type)
+    }
+
+    #[test]
+    fn convert_to_color_matrix_is_row_major() {
+        let color_matrix = Arc::new(Mutex::new(vec![vec![[1.0; 4]; 3]; 2]));
+        convert_to_color_matrix(&Some(asymmetric_view()), &color_matrix);
+        let result = color_matrix.lock().unwrap();
+
+        assert_eq!(result[0][1], match_color_to_type_piston(&TileType::Sand));
+        assert_eq!(result[0][2], UNDISCOVERED);
+        assert_eq!(result[1][0], match_color_to_type_piston(&TileType::Lava));
+        assert_eq!(result[1][2], match_color_to_type_piston(&TileType::DeepWater));
+    }
+
+    #[test]
+    fn convert_to_color_matrix_ignores_tiles_outside_the_matrix() {
+        let color_matrix = Arc::new(Mutex::new(vec![vec![[1.0; 4]; 2]; 1]));
+        convert_to_color_matrix(&Some(asymmetric_view()), &color_matrix);
+        let result = color_matrix.lock().unwrap();
+
+        assert_eq!(*result, vec![vec![
+            match_color_to_type_piston(&TileType::Grass),
+            match_color_to_type_piston(&TileType::Sand),
+        ]]);
+    }
+
+    #[test]
+    fn convert_to_color_matrix_keeps_the_matrix_without_a_map() {
+        let color_matrix = Arc::new(Mutex::new(vec![vec![[1.0; 4]; 3]; 2]));
+        convert_to_color_matrix(&None, &color_matrix);
+
+        assert_eq!(*color_matrix.lock().unwrap(), vec![vec![[1.0; 4]; 3]; 2]);
+    }
+
+    #[test]
+    fn convert_content_to_color_matrix_is_row_major() {
+        let color_matrix = Arc::new(Mutex::new(vec![vec![[1.0; 4]; 3]; 2]));
+        convert_content_to_color_matrix(&Some(asymmetric_view()), &color_matrix);
+        let result = color_matrix.lock().unwrap();
+
+        assert_eq!(result[0][0], match_content_color_to_type_piston(&Content::Rock(0)));
+        assert_eq!(result[0][2], UNDISCOVERED);
+        assert_eq!(result[1][0], match_content_color_to_type_piston(&Content::Coin(0)));
+        assert_eq!(result[1][1], match_content_color_to_type_piston(&Content::Fire));
+    }
+
+    #[test]
+    fn convert_robot_view_to_color_matrix_is_row_major() {
+        let result = convert_robot_view_to_color_matrix(&asymmetric_view());
+
+        assert_eq!(result.len(), 2);
+        assert!(result.iter().all(|row| row.len() == 3));
+        assert_eq!(result[0][1], match_color_to_type_piston(&TileType::Sand));
+        assert_eq!(result[0][2], [105.0 / 255.0, 105.0 / 255.0, 105.0 / 255.0, 1.0]);
+        assert_eq!(result[1][0], match_color_to_type_piston(&TileType::Lava));
+    }
+
+    #[test]
+    fn convert_robot_content_view_to_color_matrix_is_row_major() {
+        let result = convert_robot_content_view_to_color_matrix(&asymmetric_view());
+
+        assert_eq!(result.len(), 2);
+        assert!(result.iter().all(|row| row.len() == 3));
+        assert_eq!(result[0][0], match_content_color_to_type_piston(&Content::Rock(0)));
+        assert_eq!(result[0][2], UNDISCOVERED);
+        assert_eq!(result[1][2], match_content_color_to_type_piston(&Content::Bank(0..0)));
+    }
+
+    #[test]
+    fn convert_robot_view_to_quantity_matrix_is_row_major() {
+        let result = convert_robot_view_to_quantity_matrix(&asymmetric_view());
+
+        assert_eq!(
+            result,
+            vec![vec![Some(1), None, None], vec![Some(7), None, Some(4)]]
+        );
+    }
+
+    #[test]
+    fn environment_tint_darkens_the_night_and_tints_the_rain() {
+        let color = [1.0, 0.5, 0.0, 0.7];
+        let noon = EnvironmentInfo::default();
+        assert_eq!(apply_environment_tint(color, &noon), color);
+
+        let midnight = EnvironmentInfo {
+            hour: 0,
+            ..EnvironmentInfo::default()
+        };
+        let dark = apply_environment_tint(color, &midnight);
+        let light = 1.0 - MAX_NIGHT_DARKNESS;
+        assert_eq!(dark, [1.0 * light, 0.5 * light, 0.0, 0.7]);
+
+        let rain = EnvironmentInfo {
+            weather: WeatherType::Rainy,
+            ..EnvironmentInfo::default()
+        };
+        let tinted = apply_environment_tint(color, &rain);
+        for ((tinted, original), tint) in tinted.iter().zip(color).zip(RAIN_TINT).take(3) {
+            let expected = original * (1.0 - RAIN_TINT[3]) + tint * RAIN_TINT[3];
+            assert!((tinted - expected).abs() < 1e-6);
+        }
+        assert_eq!(tinted[3], color[3]);
+    }
+
+    #[test]
+    fn robot_map_view_is_centered_on_the_robot() {
+        // the robot is in (row 1, col 2) of the 2x3 map
+        let result = robot_map_view(&Some(asymmetric_view()), (1, 2), 1);
+
+        assert_eq!(result.len(), 3);
+        assert!(result.iter().all(|row| row.len() == 3));
+        assert_eq!(tile_type_of(&result[1][1]), tile_type_of(&asymmetric_view()[1][2]));
+        assert_eq!(tile_type_of(&result[0][0]), tile_type_of(&asymmetric_view()[0][1]));
+        assert_eq!(tile_type_of(&result[1][0]), tile_type_of(&asymmetric_view()[1][1]));
+        // undiscovered, out of the map on the right and below
+        assert!(result[0][1].is_none());
+        assert!(result[1][2].is_none());
+        assert!(result[2][1].is_none());
+    }
+
+    #[test]
+    fn robot_map_view_handles_the_map_corner() {
+        let result = robot_map_view(&Some(asymmetric_view()), (0, 0), 2);
+
+        assert_eq!(result.len(), 5);
+        assert_eq!(tile_type_of(&result[2][2]), tile_type_of(&asymmetric_view()[0][0]));
+        assert_eq!(tile_type_of(&result[2][3]), tile_type_of(&asymmetric_view()[0][1]));
+        assert_eq!(tile_type_of(&result[3][2]), tile_type_of(&asymmetric_view()[1][0]));
+        assert!(result[1][2].is_none());
+        assert!(result[2][1].is_none());
+    }
+}
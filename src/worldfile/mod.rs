@@ -0,0 +1,510 @@
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+
+use robotics_lib::world::environmental_conditions::{EnvironmentalConditions, WeatherType};
+use robotics_lib::world::tile::{Content, Tile, TileType};
+use robotics_lib::world::world_generator::Generator;
+use serde::{Deserialize, Serialize};
+
+///# Constants
+///* `WORLD_FILE_VERSION`: The version written in the exported files, increased when the format changes.
+///* `DEFAULT_TIME_PROGRESSION_MINUTES`: The in-game minutes that pass every tick, the same as the generators.
+pub const WORLD_FILE_VERSION: u32 = 2;
+pub const DEFAULT_TIME_PROGRESSION_MINUTES: u8 = 15;
+
+/// `TileType` as stored in a world file.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TileTypeRecord {
+    DeepWater,
+    ShallowWater,
+    Sand,
+    Grass,
+    Street,
+    Hill,
+    Mountain,
+    Snow,
+    Lava,
+    Teleport(bool),
+    Wall,
+}
+
+impl From<&TileType> for TileTypeRecord {
+    fn from(tile_type: &TileType) -> Self {
+        match tile_type {
+            TileType::DeepWater => TileTypeRecord::DeepWater,
+            TileType::ShallowWater => TileTypeRecord::ShallowWater,
+            TileType::Sand => TileTypeRecord::Sand,
+            TileType::Grass => TileTypeRecord::Grass,
+            TileType::Street => TileTypeRecord::Street,
+            TileType::Hill => TileTypeRecord::Hill,
+            TileType::Mountain => TileTypeRecord::Mountain,
+            TileType::Snow => TileTypeRecord::Snow,
+            TileType::Lava => TileTypeRecord::Lava,
+            TileType::Teleport(active) => TileTypeRecord::Teleport(*active),
+            TileType::Wall => TileTypeRecord::Wall,
+        }
+    }
+}
+
+impl From<TileTypeRecord> for TileType {
+    fn from(record: TileTypeRecord) -> Self {
+        match record {
+            TileTypeRecord::DeepWater => TileType::DeepWater,
+            TileTypeRecord::ShallowWater => TileType::ShallowWater,
+            TileTypeRecord::Sand => TileType::Sand,
+            TileTypeRecord::Grass => TileType::Grass,
+            TileTypeRecord::Street => TileType::Street,
+            TileTypeRecord::Hill => TileType::Hill,
+            TileTypeRecord::Mountain => TileType::Mountain,
+            TileTypeRecord::Snow => TileType::Snow,
+            TileTypeRecord::Lava => TileType::Lava,
+            TileTypeRecord::Teleport(active) => TileType::Teleport(active),
+            TileTypeRecord::Wall => TileType::Wall,
+        }
+    }
+}
+
+/// `Content` as stored in a world file.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentRecord {
+    Rock(usize),
+    Tree(usize),
+    Garbage(usize),
+    Fire,
+    Coin(usize),
+    Bin(Range<usize>),
+    Crate(Range<usize>),
+    Bank(Range<usize>),
+    Water(usize),
+    Market(usize),
+    Fish(usize),
+    Building,
+    Bush(usize),
+    JollyBlock(usize),
+    Scarecrow,
+    #[default]
+    None,
+}
+
+impl From<&Content> for ContentRecord {
+    fn from(content: &Content) -> Self {
+        match content {
+            Content::Rock(amount) => ContentRecord::Rock(*amount),
+            Content::Tree(amount) => ContentRecord::Tree(*amount),
+            Content::Garbage(amount) => ContentRecord::Garbage(*amount),
+            Content::Fire => ContentRecord::Fire,
+            Content::Coin(amount) => ContentRecord::Coin(*amount),
+            Content::Bin(range) => ContentRecord::Bin(range.clone()),
+            Content::Crate(range) => ContentRecord::Crate(range.clone()),
+            Content::Bank(range) => ContentRecord::Bank(range.clone()),
+            Content::Water(amount) => ContentRecord::Water(*amount),
+            Content::Market(amount) => ContentRecord::Market(*amount),
+            Content::Fish(amount) => ContentRecord::Fish(*amount),
+            Content::Building => ContentRecord::Building,
+            Content::Bush(amount) => ContentRecord::Bush(*amount),
+            Content::JollyBlock(amount) => ContentRecord::JollyBlock(*amount),
+            Content::Scarecrow => ContentRecord::Scarecrow,
+            Content::None => ContentRecord::None,
+        }
+    }
+}
+
+impl From<ContentRecord> for Content {
+    fn from(record: ContentRecord) -> Self {
+        match record {
+            ContentRecord::Rock(amount) => Content::Rock(amount),
+            ContentRecord::Tree(amount) => Content::Tree(amount),
+            ContentRecord::Garbage(amount) => Content::Garbage(amount),
+            ContentRecord::Fire => Content::Fire,
+            ContentRecord::Coin(amount) => Content::Coin(amount),
+            ContentRecord::Bin(range) => Content::Bin(range),
+            ContentRecord::Crate(range) => Content::Crate(range),
+            ContentRecord::Bank(range) => Content::Bank(range),
+            ContentRecord::Water(amount) => Content::Water(amount),
+            ContentRecord::Market(amount) => Content::Market(amount),
+            ContentRecord::Fish(amount) => Content::Fish(amount),
+            ContentRecord::Building => Content::Building,
+            ContentRecord::Bush(amount) => Content::Bush(amount),
+            ContentRecord::JollyBlock(amount) => Content::JollyBlock(amount),
+            ContentRecord::Scarecrow => Content::Scarecrow,
+            ContentRecord::None => Content::None,
+        }
+    }
+}
+
+/// `WeatherType` as stored in a world file.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WeatherRecord {
+    Sunny,
+    Rainy,
+    Foggy,
+    TropicalMonsoon,
+    TrentinoSnow,
+}
+
+impl From<&WeatherType> for WeatherRecord {
+    fn from(weather: &WeatherType) -> Self {
+        match weather {
+            WeatherType::Sunny => WeatherRecord::Sunny,
+            WeatherType::Rainy => WeatherRecord::Rainy,
+            WeatherType::Foggy => WeatherRecord::Foggy,
+            WeatherType::TropicalMonsoon => WeatherRecord::TropicalMonsoon,
+            WeatherType::TrentinoSnow => WeatherRecord::TrentinoSnow,
+        }
+    }
+}
+
+impl From<WeatherRecord> for WeatherType {
+    fn from(record: WeatherRecord) -> Self {
+        match record {
+            WeatherRecord::Sunny => WeatherType::Sunny,
+            WeatherRecord::Rainy => WeatherType::Rainy,
+            WeatherRecord::Foggy => WeatherType::Foggy,
+            WeatherRecord::TropicalMonsoon => WeatherType::TropicalMonsoon,
+            WeatherRecord::TrentinoSnow => WeatherType::TrentinoSnow,
+        }
+    }
+}
+
+/// A single tile of a world file; content and elevation are omitted when empty.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TileRecord {
+    pub tile_type: TileTypeRecord,
+    #[serde(default, skip_serializing_if = "is_no_content")]
+    pub content: ContentRecord,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub elevation: usize,
+}
+
+fn is_no_content(content: &ContentRecord) -> bool {
+    *content == ContentRecord::None
+}
+
+fn is_zero(elevation: &usize) -> bool {
+    *elevation == 0
+}
+
+/// The environmental conditions a world starts with.
+///
+/// Attributes:
+/// - `weather`: The weather forecast, cycled by robotics_lib every day.
+/// - `time_progression_minutes`: The in-game minutes that pass every tick, from 1 to 60;
+///   missing in the files of version 1, which get the default.
+/// - `hour`: The starting hour, from 0 to 23; robotics_lib always starts at the full hour.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EnvironmentRecord {
+    pub weather: Vec<WeatherRecord>,
+    #[serde(default = "default_time_progression")]
+    pub time_progression_minutes: u8,
+    pub hour: u8,
+}
+
+fn default_time_progression() -> u8 {
+    DEFAULT_TIME_PROGRESSION_MINUTES
+}
+
+impl EnvironmentRecord {
+    /// Builds the conditions given to the `Runner`.
+    pub fn to_conditions(&self) -> Result<EnvironmentalConditions, String> {
+        let weather: Vec<WeatherType> = self.weather.iter().map(|weather| WeatherType::from(*weather)).collect();
+        EnvironmentalConditions::new(&weather, self.time_progression_minutes, self.hour)
+            .map_err(|e| format!("invalid environmental conditions: {:?}", e))
+    }
+}
+
+/// Everything `Generator::gen` returns, in a format that can be saved and shared.
+///
+/// Attributes:
+/// - `version`: The version of the format (see `WORLD_FILE_VERSION`).
+/// - `spawn`: The starting coordinates of the robot as (row, col).
+/// - `environment`: The starting environmental conditions.
+/// - `max_score`: The max score of the world.
+/// - `score_table`: The score of each content, if the world has a custom one.
+/// - `tiles`: The tiles, indexed `[row][col]`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WorldFile {
+    pub version: u32,
+    pub spawn: (usize, usize),
+    pub environment: EnvironmentRecord,
+    pub max_score: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score_table: Option<Vec<(ContentRecord, f32)>>,
+    pub tiles: Vec<Vec<TileRecord>>,
+}
+
+impl WorldFile {
+    /// Builds a world file from the output of a generator.
+    ///
+    /// `EnvironmentalConditions` only exposes the current weather and time, so the
+    /// exported forecast contains the current weather only and the time progression is
+    /// `DEFAULT_TIME_PROGRESSION_MINUTES`.
+    pub fn from_generated(
+        tiles: &[Vec<Tile>],
+        spawn: (usize, usize),
+        conditions: &EnvironmentalConditions,
+        max_score: f32,
+        score_table: Option<&HashMap<Content, f32>>,
+    ) -> Self {
+        let time = conditions.get_time_of_day_string();
+        let hour = time
+            .split(':')
+            .next()
+            .and_then(|part| part.trim().parse::<u8>().ok())
+            .unwrap_or(12);
+
+        Self {
+            version: WORLD_FILE_VERSION,
+            spawn,
+            environment: EnvironmentRecord {
+                weather: vec![WeatherRecord::from(&conditions.get_weather_condition())],
+                time_progression_minutes: DEFAULT_TIME_PROGRESSION_MINUTES,
+                hour,
+            },
+            max_score,
+            score_table: score_table.map(|table| {
+                table
+                    .iter()
+                    .map(|(content, score)| (ContentRecord::from(content), *score))
+                    .collect()
+            }),
+            tiles: tiles
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|tile| TileRecord {
+                            tile_type: TileTypeRecord::from(&tile.tile_type),
+                            content: ContentRecord::from(&tile.content),
+                            elevation: tile.elevation,
+                        })
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    /// The amount of rows of the world.
+    pub fn size(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Checks that the world is square, not empty, that the spawn point is inside it and
+    /// that the environmental conditions are in range.
+    pub fn validate(&self) -> Result<(), String> {
+        let size = self.size();
+        if size == 0 {
+            return Err(String::from("the world has no tiles"));
+        }
+        if let Some(row) = self.tiles.iter().position(|row| row.len() != size) {
+            return Err(format!("row {} has {} tiles, expected {}", row, self.tiles[row].len(), size));
+        }
+        if self.spawn.0 >= size || self.spawn.1 >= size {
+            return Err(format!("spawn point ({},{}) is outside the world", self.spawn.1, self.spawn.0));
+        }
+        if self.environment.weather.is_empty() {
+            return Err(String::from("the weather forecast is empty"));
+        }
+        if self.environment.hour >= 24 {
+            return Err(format!("starting hour {} is not between 0 and 23", self.environment.hour));
+        }
+        if !(1..=60).contains(&self.environment.time_progression_minutes) {
+            return Err(format!(
+                "time progression of {} minutes is not between 1 and 60",
+                self.environment.time_progression_minutes
+            ));
+        }
+        Ok(())
+    }
+
+    /// Writes the world as JSON to the given path.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the JSON file.
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let json = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| format!("Error writing '{}': {}", path, e))
+    }
+
+    /// Reads and validates a world written by `save`.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the JSON file.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let json = fs::read_to_string(path).map_err(|e| format!("Error reading '{}': {}", path, e))?;
+        let world: WorldFile = serde_json::from_str(&json).map_err(|e| format!("Error parsing '{}': {}", path, e))?;
+        world.validate().map_err(|e| format!("Invalid world '{}': {}", path, e))?;
+        Ok(world)
+    }
+}
+
+/// Generates the world stored in a `WorldFile`.
+///
+/// Attributes:
+/// - `world`: The stored world.
+/// - `conditions`: The environmental conditions of the world, built when the file is loaded
+///   so that invalid ones are reported there instead of in `gen`.
+#[derive(Clone)]
+pub struct FileGenerator {
+    world: WorldFile,
+    conditions: EnvironmentalConditions,
+}
+
+impl FileGenerator {
+    pub fn new(world: WorldFile) -> Result<Self, String> {
+        world.validate()?;
+        let conditions = world.environment.to_conditions()?;
+        Ok(Self { world, conditions })
+    }
+
+    /// # Arguments
+    /// * `path` - The path of a JSON file written by `WorldFile::save` or `export_world`.
+    pub fn load(path: &str) -> Result<Self, String> {
+        Self::new(WorldFile::load(path)?).map_err(|e| format!("Invalid world '{}': {}", path, e))
+    }
+
+    pub fn world(&self) -> &WorldFile {
+        &self.world
+    }
+
+    pub fn size(&self) -> usize {
+        self.world.size()
+    }
+}
+
+impl Generator for FileGenerator {
+    fn gen(
+        &mut self,
+    ) -> (
+        Vec<Vec<Tile>>,
+        (usize, usize),
+        EnvironmentalConditions,
+        f32,
+        Option<HashMap<Content, f32>>,
+    ) {
+        let tiles = self
+            .world
+            .tiles
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|tile| Tile {
+                        tile_type: TileType::from(tile.tile_type),
+                        content: Content::from(tile.content.clone()),
+                        elevation: tile.elevation,
+                    })
+                    .collect()
+            })
+            .collect();
+        let score_table = self.world.score_table.as_ref().map(|table| {
+            table
+                .iter()
+                .map(|(content, score)| (Content::from(content.clone()), *score))
+                .collect()
+        });
+        (tiles, self.world.spawn, self.conditions.clone(), self.world.max_score, score_table)
+    }
+}
+
+/// Runs the generator once and saves the world it produced, so that it can be loaded
+/// again with `FileGenerator`.
+///
+/// # Arguments
+/// * `generator` - The generator of the world.
+/// * `path` - The path of the JSON file.
+pub fn export_world(generator: &mut impl Generator, path: &str) -> Result<WorldFile, String> {
+    let (tiles, spawn, conditions, max_score, score_table) = generator.gen();
+    let world = WorldFile::from_generated(&tiles, spawn, &conditions, max_score, score_table.as_ref());
+    world.save(path)?;
+    Ok(world)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_record_converts_back_to_the_same_value() {
+        let contents = [
+            Content::Rock(1),
+            Content::Bin(2..5),
+            Content::Fire,
+            Content::JollyBlock(3),
+            Content::None,
+        ];
+        for content in contents {
+            let record = ContentRecord::from(&content);
+            assert_eq!(ContentRecord::from(&Content::from(record.clone())), record);
+        }
+        for tile_type in [TileType::Teleport(true), TileType::Wall, TileType::Grass] {
+            assert_eq!(TileType::from(TileTypeRecord::from(&tile_type)), tile_type);
+        }
+    }
+
+    #[test]
+    fn empty_content_and_elevation_are_not_written() {
+        let tile = TileRecord {
+            tile_type: TileTypeRecord::Grass,
+            content: ContentRecord::None,
+            elevation: 0,
+        };
+        let json = serde_json::to_string(&tile).unwrap();
+        assert_eq!(json, r#"{"tile_type":"grass"}"#);
+        assert_eq!(serde_json::from_str::<TileRecord>(&json).unwrap(), tile);
+    }
+
+    fn environment(time_progression_minutes: u8, hour: u8) -> EnvironmentRecord {
+        EnvironmentRecord {
+            weather: vec![WeatherRecord::Sunny],
+            time_progression_minutes,
+            hour,
+        }
+    }
+
+    #[test]
+    fn spawn_outside_the_world_is_rejected() {
+        let world = WorldFile {
+            version: WORLD_FILE_VERSION,
+            spawn: (2, 0),
+            environment: environment(DEFAULT_TIME_PROGRESSION_MINUTES, 12),
+            max_score: 10.0,
+            score_table: None,
+            tiles: vec![vec![TileRecord {
+                tile_type: TileTypeRecord::Grass,
+                content: ContentRecord::None,
+                elevation: 0,
+            }; 2]; 2],
+        };
+        assert!(world.validate().is_err());
+        assert!(FileGenerator::new(WorldFile { spawn: (1, 1), ..world }).is_ok());
+    }
+
+    #[test]
+    fn environment_out_of_range_is_rejected() {
+        let world = WorldFile {
+            version: WORLD_FILE_VERSION,
+            spawn: (0, 0),
+            environment: environment(DEFAULT_TIME_PROGRESSION_MINUTES, 24),
+            max_score: 10.0,
+            score_table: None,
+            tiles: vec![vec![TileRecord {
+                tile_type: TileTypeRecord::Grass,
+                content: ContentRecord::None,
+                elevation: 0,
+            }]],
+        };
+        assert!(FileGenerator::new(world.clone()).is_err());
+        assert!(FileGenerator::new(WorldFile {
+            environment: environment(0, 12),
+            ..world
+        })
+        .is_err());
+
+        // files of version 1 have no time progression
+        let json = r#"{ "weather": ["sunny"], "hour": 7, "minute": 0 }"#;
+        let record: EnvironmentRecord = serde_json::from_str(json).unwrap();
+        assert_eq!(record, environment(DEFAULT_TIME_PROGRESSION_MINUTES, 7));
+    }
+}
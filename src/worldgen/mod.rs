@@ -0,0 +1,463 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use noise::{NoiseFn, Perlin};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use robotics_lib::world::environmental_conditions::EnvironmentalConditions;
+use robotics_lib::world::environmental_conditions::WeatherType::{Foggy, Rainy, Sunny};
+use robotics_lib::world::tile::{Content, Tile, TileType};
+use robotics_lib::world::world_generator::Generator;
+use serde::{Deserialize, Serialize};
+
+use crate::worldfile::FileGenerator;
+use crate::worldimage::ImageGenerator;
+
+///# Constants
+///* `DEFAULT_MAX_SCORE`: The max score of every generated world.
+///* `NOISE_SCALE`: How many tiles a noise period spans; bigger values give bigger continents.
+///* `NOISE_OCTAVES`: The amount of noise layers summed to get the elevation.
+///* `MIN_WORLD_SIZE`: The smallest world that can be generated, the maze needs a cell surrounded by walls.
+pub const DEFAULT_MAX_SCORE: f32 = 1000.0;
+pub const NOISE_SCALE: f64 = 48.0;
+pub const NOISE_OCTAVES: usize = 4;
+pub const MIN_WORLD_SIZE: usize = 3;
+
+/// The kinds of world `SeededGenerator` can build.
+///
+/// - `Flat`: Grass only, with a row of coins; the old debug map.
+/// - `Noise`: Terrain from layered Perlin noise, from deep water to snowy peaks.
+/// - `Islands`: Noise terrain that sinks towards the borders, so the land is surrounded by water.
+/// - `Maze`: A perfect maze of walls, with grass corridors one tile wide.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorldPreset {
+    Flat,
+    #[default]
+    Noise,
+    Islands,
+    Maze,
+}
+
+impl WorldPreset {
+    pub const ALL: [WorldPreset; 4] = [WorldPreset::Flat, WorldPreset::Noise, WorldPreset::Islands, WorldPreset::Maze];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WorldPreset::Flat => "flat",
+            WorldPreset::Noise => "noise",
+            WorldPreset::Islands => "islands",
+            WorldPreset::Maze => "maze",
+        }
+    }
+}
+
+impl FromStr for WorldPreset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        WorldPreset::ALL
+            .into_iter()
+            .find(|preset| preset.name() == s.to_lowercase())
+            .ok_or_else(|| format!("unknown world '{}', expected one of flat, noise, islands, maze", s))
+    }
+}
+
+impl fmt::Display for WorldPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The probability of each content being placed on a tile that can hold it.
+/// The probabilities are checked in the order of the fields, so the sum should stay below 1.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContentDensities {
+    pub rock: f32,
+    pub tree: f32,
+    pub bush: f32,
+    pub garbage: f32,
+    pub coin: f32,
+    pub fire: f32,
+    pub fish: f32,
+    pub bin: f32,
+    pub crate_: f32,
+    pub bank: f32,
+    pub market: f32,
+}
+
+impl Default for ContentDensities {
+    fn default() -> Self {
+        Self {
+            rock: 0.04,
+            tree: 0.06,
+            bush: 0.03,
+            garbage: 0.02,
+            coin: 0.02,
+            fire: 0.005,
+            fish: 0.05,
+            bin: 0.002,
+            crate_: 0.002,
+            bank: 0.001,
+            market: 0.001,
+        }
+    }
+}
+
+impl ContentDensities {
+    /// Picks the content of a tile: every content the tile type can hold gets a chance,
+    /// in field order, and the first one that passes is placed.
+    fn pick(&self, tile_type: &TileType, rng: &mut StdRng) -> Content {
+        let candidates = [
+            (self.rock, Content::Rock(rng.gen_range(1..=5))),
+            (self.tree, Content::Tree(rng.gen_range(1..=5))),
+            (self.bush, Content::Bush(rng.gen_range(1..=3))),
+            (self.garbage, Content::Garbage(rng.gen_range(1..=5))),
+            (self.coin, Content::Coin(rng.gen_range(1..=10))),
+            (self.fire, Content::Fire),
+            (self.fish, Content::Fish(rng.gen_range(1..=5))),
+            (self.bin, Content::Bin(0..5)),
+            (self.crate_, Content::Crate(0..5)),
+            (self.bank, Content::Bank(0..20)),
+            (self.market, Content::Market(rng.gen_range(1..=10))),
+        ];
+        for (density, content) in candidates {
+            if can_hold(tile_type, &content) && rng.gen::<f32>() < density {
+                return content;
+            }
+        }
+        Content::None
+    }
+}
+
+/// Whether the generators may place the content on the tile type.
+pub fn can_hold(tile_type: &TileType, content: &Content) -> bool {
+    match tile_type {
+        TileType::DeepWater | TileType::ShallowWater => matches!(content, Content::Fish(_)),
+        TileType::Grass => !matches!(content, Content::Fish(_)),
+        TileType::Sand => matches!(content, Content::Rock(_) | Content::Garbage(_) | Content::Coin(_) | Content::Crate(_)),
+        TileType::Street => matches!(
+            content,
+            Content::Garbage(_) | Content::Coin(_) | Content::Bin(_) | Content::Crate(_) | Content::Bank(_) | Content::Market(_)
+        ),
+        TileType::Hill => matches!(content, Content::Rock(_) | Content::Tree(_) | Content::Bush(_) | Content::Coin(_)),
+        TileType::Mountain | TileType::Snow => matches!(content, Content::Rock(_) | Content::Coin(_)),
+        TileType::Lava | TileType::Wall | TileType::Teleport(_) => false,
+    }
+}
+
+/// Whether the robot can stand on the tile type; used to choose the spawn point.
+pub fn is_walkable(tile_type: &TileType) -> bool {
+    !matches!(tile_type, TileType::DeepWater | TileType::Lava | TileType::Wall)
+}
+
+/// Returns the walkable tile closest to the target, as (row, col), or (0, 0) if no tile is walkable.
+///
+/// # Arguments
+/// * `tiles` - The tiles of the world, indexed `[row][col]`.
+/// * `target` - The preferred position as (row, col).
+pub fn nearest_walkable(tiles: &[Vec<Tile>], target: (usize, usize)) -> (usize, usize) {
+    let mut spawn = None;
+    let mut best_distance = usize::MAX;
+    for (row, tiles_row) in tiles.iter().enumerate() {
+        for (col, tile) in tiles_row.iter().enumerate() {
+            let distance = row.abs_diff(target.0) + col.abs_diff(target.1);
+            if is_walkable(&tile.tile_type) && distance < best_distance {
+                best_distance = distance;
+                spawn = Some((row, col));
+            }
+        }
+    }
+    spawn.unwrap_or((0, 0))
+}
+
+/// A reproducible world generator: the same preset, size, seed and densities always
+/// produce the same world.
+///
+/// Attributes:
+/// - `preset`: The kind of world.
+/// - `size`: The amount of rows and columns of the world.
+/// - `seed`: The seed of every random choice.
+/// - `densities`: The probabilities of the contents.
+#[derive(Clone, Debug)]
+pub struct SeededGenerator {
+    pub preset: WorldPreset,
+    pub size: usize,
+    pub seed: u64,
+    pub densities: ContentDensities,
+}
+
+impl SeededGenerator {
+    /// # Returns
+    /// The generator, or an error if the size is below `MIN_WORLD_SIZE`.
+    pub fn new(preset: WorldPreset, size: usize, seed: u64, densities: ContentDensities) -> Result<Self, String> {
+        if size < MIN_WORLD_SIZE {
+            return Err(format!(
+                "the {} world must be at least {}x{}, got {}x{}",
+                preset, MIN_WORLD_SIZE, MIN_WORLD_SIZE, size, size
+            ));
+        }
+        Ok(Self {
+            preset,
+            size,
+            seed,
+            densities,
+        })
+    }
+
+    /// Builds the tiles of the world, indexed `[row][col]`.
+    pub fn tiles(&self) -> Vec<Vec<Tile>> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let terrain = match self.preset {
+            WorldPreset::Flat => vec![vec![(TileType::Grass, 0); self.size]; self.size],
+            WorldPreset::Noise => self.noise_terrain(false),
+            WorldPreset::Islands => self.noise_terrain(true),
+            WorldPreset::Maze => self.maze_terrain(&mut rng),
+        };
+
+        terrain
+            .into_iter()
+            .enumerate()
+            .map(|(row, terrain_row)| {
+                terrain_row
+                    .into_iter()
+                    .map(|(tile_type, elevation)| {
+                        let content = match self.preset {
+                            //the old debug map: the second row is filled with coins
+                            WorldPreset::Flat if row == 1 => Content::Coin(rng.gen_range(0..=20)),
+                            WorldPreset::Flat => Content::None,
+                            _ => self.densities.pick(&tile_type, &mut rng),
+                        };
+                        Tile {
+                            tile_type,
+                            content,
+                            elevation,
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the walkable tile closest to the center of the world, as (row, col).
+    pub fn spawn_point(&self, tiles: &[Vec<Tile>]) -> (usize, usize) {
+        nearest_walkable(tiles, (self.size / 2, self.size / 2))
+    }
+
+    fn noise_terrain(&self, islands: bool) -> Vec<Vec<(TileType, usize)>> {
+        let perlin = Perlin::new(noise_seed(self.seed));
+        let half = self.size as f64 / 2.0;
+        (0..self.size)
+            .map(|row| {
+                (0..self.size)
+                    .map(|col| {
+                        let mut height = 0.0;
+                        let mut amplitude = 1.0;
+                        let mut frequency = 1.0 / NOISE_SCALE;
+                        let mut total_amplitude = 0.0;
+                        for _ in 0..NOISE_OCTAVES {
+                            height += amplitude * perlin.get([row as f64 * frequency, col as f64 * frequency]);
+                            total_amplitude += amplitude;
+                            amplitude *= 0.5;
+                            frequency *= 2.0;
+                        }
+                        //from [-1, 1] to [0, 1]
+                        let mut height = (height / total_amplitude + 1.0) / 2.0;
+                        if islands {
+                            let dx = (col as f64 - half) / half;
+                            let dy = (row as f64 - half) / half;
+                            height -= 0.5 * (dx * dx + dy * dy);
+                            height += 0.15;
+                        }
+                        let height = height.clamp(0.0, 1.0);
+                        (tile_type_for_height(height), (height * 100.0) as usize)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Carves a maze with a randomized depth first search over the odd cells.
+    fn maze_terrain(&self, rng: &mut StdRng) -> Vec<Vec<(TileType, usize)>> {
+        let mut terrain = vec![vec![(TileType::Wall, 0); self.size]; self.size];
+        let cells = (self.size - 1) / 2;
+        let mut visited = vec![vec![false; cells]; cells];
+        let mut stack = vec![(0usize, 0usize)];
+        visited[0][0] = true;
+        terrain[1][1] = (TileType::Grass, 0);
+
+        while let Some(&(row, col)) = stack.last() {
+            let mut neighbours = Vec::with_capacity(4);
+            if row > 0 && !visited[row - 1][col] {
+                neighbours.push((row - 1, col));
+            }
+            if row + 1 < cells && !visited[row + 1][col] {
+                neighbours.push((row + 1, col));
+            }
+            if col > 0 && !visited[row][col - 1] {
+                neighbours.push((row, col - 1));
+            }
+            if col + 1 < cells && !visited[row][col + 1] {
+                neighbours.push((row, col + 1));
+            }
+
+            match neighbours.choose(rng) {
+                Some(&(next_row, next_col)) => {
+                    visited[next_row][next_col] = true;
+                    //the wall between the two cells is opened
+                    terrain[row + next_row + 1][col + next_col + 1] = (TileType::Grass, 0);
+                    terrain[2 * next_row + 1][2 * next_col + 1] = (TileType::Grass, 0);
+                    stack.push((next_row, next_col));
+                }
+                None => {
+                    stack.pop();
+                }
+            }
+        }
+        terrain
+    }
+}
+
+/// The seed of the noise, which only takes 32 bits: the upper half of the seed is folded
+/// into the lower one, so that seeds differing only in the upper bits give different worlds.
+fn noise_seed(seed: u64) -> u32 {
+    (seed ^ (seed >> 32)) as u32
+}
+
+fn tile_type_for_height(height: f64) -> TileType {
+    match height {
+        h if h < 0.30 => TileType::DeepWater,
+        h if h < 0.40 => TileType::ShallowWater,
+        h if h < 0.44 => TileType::Sand,
+        h if h < 0.62 => TileType::Grass,
+        h if h < 0.72 => TileType::Hill,
+        h if h < 0.84 => TileType::Mountain,
+        _ => TileType::Snow,
+    }
+}
+
+impl Generator for SeededGenerator {
+    fn gen(
+        &mut self,
+    ) -> (
+        Vec<Vec<Tile>>,
+        (usize, usize),
+        EnvironmentalConditions,
+        f32,
+        Option<HashMap<Content, f32>>,
+    ) {
+        let tiles = self.tiles();
+        let spawn = match self.preset {
+            WorldPreset::Flat => (0, 0),
+            _ => self.spawn_point(&tiles),
+        };
+        let environmental_conditions = EnvironmentalConditions::new(&[Sunny, Rainy, Foggy], 15, 12).unwrap();
+        (tiles, spawn, environmental_conditions, DEFAULT_MAX_SCORE, None)
+    }
+}
+
+/// The generator chosen at runtime: `Runner::new` needs a concrete `Generator`,
+/// so the possible generators are wrapped in a single type.
+#[derive(Clone)]
+pub enum WorldSource {
+    Seeded(SeededGenerator),
+    File(FileGenerator),
+    Image(ImageGenerator),
+}
+
+impl WorldSource {
+    /// The amount of rows and columns of the world that will be generated.
+    pub fn size(&self) -> usize {
+        match self {
+            WorldSource::Seeded(generator) => generator.size,
+            WorldSource::File(generator) => generator.size(),
+            WorldSource::Image(generator) => generator.size(),
+        }
+    }
+}
+
+impl Generator for WorldSource {
+    fn gen(
+        &mut self,
+    ) -> (
+        Vec<Vec<Tile>>,
+        (usize, usize),
+        EnvironmentalConditions,
+        f32,
+        Option<HashMap<Content, f32>>,
+    ) {
+        match self {
+            WorldSource::Seeded(generator) => generator.gen(),
+            WorldSource::File(generator) => generator.gen(),
+            WorldSource::Image(generator) => generator.gen(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tile_types(generator: &SeededGenerator) -> Vec<Vec<TileType>> {
+        generator
+            .tiles()
+            .into_iter()
+            .map(|row| row.into_iter().map(|tile| tile.tile_type).collect())
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_world() {
+        for preset in WorldPreset::ALL {
+            let first = SeededGenerator::new(preset, 31, 7, ContentDensities::default()).unwrap();
+            let second = SeededGenerator::new(preset, 31, 7, ContentDensities::default()).unwrap();
+            assert_eq!(tile_types(&first), tile_types(&second), "{}", preset);
+        }
+    }
+
+    #[test]
+    fn worlds_smaller_than_the_preset_minimum_are_rejected() {
+        for preset in WorldPreset::ALL {
+            for size in 0..MIN_WORLD_SIZE {
+                assert!(SeededGenerator::new(preset, size, 1, ContentDensities::default()).is_err(), "{} {}", preset, size);
+            }
+            let smallest = SeededGenerator::new(preset, MIN_WORLD_SIZE, 1, ContentDensities::default()).unwrap();
+            assert_eq!(smallest.tiles().len(), MIN_WORLD_SIZE);
+        }
+        let maze = SeededGenerator::new(WorldPreset::Maze, MIN_WORLD_SIZE, 1, ContentDensities::default()).unwrap();
+        assert_eq!(tile_types(&maze)[1][1], TileType::Grass);
+    }
+
+    #[test]
+    fn noise_seed_uses_every_bit_of_the_seed() {
+        assert_eq!(noise_seed(7), 7);
+        assert_ne!(noise_seed(7), noise_seed(7 + (1 << 32)));
+        assert_ne!(noise_seed(1 << 40), 0);
+    }
+
+    #[test]
+    fn maze_corridors_are_connected_to_the_spawn() {
+        let generator = SeededGenerator::new(WorldPreset::Maze, 21, 3, ContentDensities::default()).unwrap();
+        let tiles = tile_types(&generator);
+        let (spawn_row, spawn_col) = generator.spawn_point(&generator.tiles());
+
+        let mut reached = vec![vec![false; 21]; 21];
+        let mut stack = vec![(spawn_row, spawn_col)];
+        while let Some((row, col)) = stack.pop() {
+            if reached[row][col] || !is_walkable(&tiles[row][col]) {
+                continue;
+            }
+            reached[row][col] = true;
+            stack.extend([(row - 1, col), (row + 1, col), (row, col - 1), (row, col + 1)]);
+        }
+
+        for row in 0..21 {
+            for col in 0..21 {
+                assert_eq!(is_walkable(&tiles[row][col]), reached[row][col], "({},{})", row, col);
+            }
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+
+use image::RgbaImage;
+use robotics_lib::world::environmental_conditions::EnvironmentalConditions;
+use robotics_lib::world::environmental_conditions::WeatherType::{Rainy, Sunny};
+use robotics_lib::world::tile::{Content, Tile, TileType};
+use robotics_lib::world::world_generator::Generator;
+
+use crate::util::{match_color_to_content, match_color_to_type};
+use crate::worldgen::{nearest_walkable, DEFAULT_MAX_SCORE};
+
+///# Constants
+///* `DEFAULT_CONTENT_AMOUNT`: The quantity given to the contents painted in the content image.
+///* `TRANSPARENT_ALPHA`: Pixels of the content image with a lower alpha have no content.
+pub const DEFAULT_CONTENT_AMOUNT: usize = 1;
+pub const TRANSPARENT_ALPHA: u8 = 128;
+
+/// The tile types that can be painted. `Wall` has the same color as `Hill` in the frame
+/// palette, so it can't be painted and the pixels of that color are `Hill`; the active and
+/// inactive teleports share their color too, the painted teleports are `Teleport(false)`.
+const PAINTABLE_TILE_TYPES: [TileType; 10] = [
+    TileType::Grass,
+    TileType::Street,
+    TileType::ShallowWater,
+    TileType::DeepWater,
+    TileType::Sand,
+    TileType::Hill,
+    TileType::Mountain,
+    TileType::Teleport(false),
+    TileType::Lava,
+    TileType::Snow,
+];
+
+/// The contents that can be painted, with the quantity they get.
+fn paintable_contents() -> [Content; 15] {
+    [
+        Content::Rock(DEFAULT_CONTENT_AMOUNT),
+        Content::Tree(DEFAULT_CONTENT_AMOUNT),
+        Content::Garbage(DEFAULT_CONTENT_AMOUNT),
+        Content::Fire,
+        Content::Coin(DEFAULT_CONTENT_AMOUNT),
+        Content::Bin(0..5),
+        Content::Crate(0..5),
+        Content::Bank(0..20),
+        Content::Water(DEFAULT_CONTENT_AMOUNT),
+        Content::Market(DEFAULT_CONTENT_AMOUNT),
+        Content::Fish(DEFAULT_CONTENT_AMOUNT),
+        Content::Building,
+        Content::Bush(DEFAULT_CONTENT_AMOUNT),
+        Content::JollyBlock(DEFAULT_CONTENT_AMOUNT),
+        Content::Scarecrow,
+    ]
+}
+
+fn color_distance(a: [u8; 4], b: (u8, u8, u8, u8)) -> u32 {
+    let channel = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    channel(a[0], b.0) + channel(a[1], b.1) + channel(a[2], b.2)
+}
+
+/// Returns the tile type whose color (the one used by the frames and the window)
+/// is the closest to the pixel.
+pub fn tile_type_from_color(pixel: [u8; 4]) -> TileType {
+    let mut best = PAINTABLE_TILE_TYPES[0];
+    let mut best_distance = u32::MAX;
+    for tile_type in PAINTABLE_TILE_TYPES {
+        let distance = color_distance(pixel, match_color_to_type(&tile_type));
+        if distance < best_distance {
+            best = tile_type;
+            best_distance = distance;
+        }
+    }
+    best
+}
+
+/// Returns the content whose color is the closest to the pixel, or `Content::None`
+/// if the pixel is transparent.
+pub fn content_from_color(pixel: [u8; 4]) -> Content {
+    if pixel[3] < TRANSPARENT_ALPHA {
+        return Content::None;
+    }
+    let mut best = Content::None;
+    let mut best_distance = u32::MAX;
+    for content in paintable_contents() {
+        let distance = color_distance(pixel, match_color_to_content(&content));
+        if distance < best_distance {
+            best_distance = distance;
+            best = content;
+        }
+    }
+    best
+}
+
+/// Builds a world from images painted with the palette of the frames: every pixel of
+/// the tile image is a tile, and the optional content image (same size, transparent
+/// where there is no content) places the contents.
+///
+/// Colors don't need to be exact, each pixel takes the closest color of the palette.
+///
+/// Attributes:
+/// - `tiles`: The tile image.
+/// - `contents`: The content image, if any.
+/// - `spawn`: The starting position of the robot as (row, col); the walkable tile closest
+///   to the center is used if missing.
+#[derive(Clone)]
+pub struct ImageGenerator {
+    tiles: RgbaImage,
+    contents: Option<RgbaImage>,
+    spawn: Option<(usize, usize)>,
+}
+
+impl ImageGenerator {
+    /// # Arguments
+    /// * `tile_path` - The image of the tile types; it must be square.
+    /// * `content_path` - The image of the contents, with the same size.
+    pub fn load(tile_path: &str, content_path: Option<&str>) -> Result<Self, String> {
+        let open = |path: &str| {
+            image::open(path)
+                .map(|image| image.to_rgba8())
+                .map_err(|e| format!("Error reading '{}': {}", path, e))
+        };
+        let tiles = open(tile_path)?;
+        let contents = content_path.map(open).transpose()?;
+        Self::new(tiles, contents)
+    }
+
+    pub fn new(tiles: RgbaImage, contents: Option<RgbaImage>) -> Result<Self, String> {
+        let (width, height) = tiles.dimensions();
+        if width != height || width == 0 {
+            return Err(format!("the tile image must be square, it is {}x{}", width, height));
+        }
+        if let Some(contents) = &contents {
+            if contents.dimensions() != (width, height) {
+                let (content_width, content_height) = contents.dimensions();
+                return Err(format!(
+                    "the content image is {}x{}, expected {}x{}",
+                    content_width, content_height, width, height
+                ));
+            }
+        }
+        Ok(Self {
+            tiles,
+            contents,
+            spawn: None,
+        })
+    }
+
+    /// Sets the starting position of the robot as (row, col).
+    pub fn with_spawn(mut self, spawn: (usize, usize)) -> Self {
+        self.spawn = Some(spawn);
+        self
+    }
+
+    pub fn size(&self) -> usize {
+        self.tiles.height() as usize
+    }
+
+    /// Converts the images to tiles, indexed `[row][col]` (the row is the y of the pixel).
+    pub fn world_tiles(&self) -> Vec<Vec<Tile>> {
+        (0..self.tiles.height())
+            .map(|y| {
+                (0..self.tiles.width())
+                    .map(|x| Tile {
+                        tile_type: tile_type_from_color(self.tiles.get_pixel(x, y).0),
+                        content: self
+                            .contents
+                            .as_ref()
+                            .map(|contents| content_from_color(contents.get_pixel(x, y).0))
+                            .unwrap_or(Content::None),
+                        elevation: 0,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl Generator for ImageGenerator {
+    fn gen(
+        &mut self,
+    ) -> (
+        Vec<Vec<Tile>>,
+        (usize, usize),
+        EnvironmentalConditions,
+        f32,
+        Option<HashMap<Content, f32>>,
+    ) {
+        let tiles = self.world_tiles();
+        let size = self.size();
+        let spawn = match self.spawn {
+            Some((row, col)) if row < size && col < size => (row, col),
+            _ => nearest_walkable(&tiles, (size / 2, size / 2)),
+        };
+        let environmental_conditions = EnvironmentalConditions::new(&[Sunny, Rainy], 15, 12).unwrap();
+        (tiles, spawn, environmental_conditions, DEFAULT_MAX_SCORE, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn rgba(color: (u8, u8, u8, u8)) -> [u8; 4] {
+        [color.0, color.1, color.2, color.3]
+    }
+
+    #[test]
+    fn palette_colors_convert_back_to_their_tile_type() {
+        for tile_type in PAINTABLE_TILE_TYPES {
+            assert_eq!(tile_type_from_color(rgba(match_color_to_type(&tile_type))), tile_type);
+        }
+        assert_eq!(tile_type_from_color(rgba(match_color_to_type(&TileType::Wall))), TileType::Hill);
+        assert_eq!(tile_type_from_color(rgba(match_color_to_type(&TileType::Teleport(true)))), TileType::Teleport(false));
+        // slightly off colors still work
+        assert_eq!(tile_type_from_color([10, 240, 12, 255]), TileType::Grass);
+    }
+
+    #[test]
+    fn transparent_content_pixels_have_no_content() {
+        assert!(matches!(content_from_color([255, 215, 0, 0]), Content::None));
+        assert!(matches!(content_from_color([255, 215, 0, 255]), Content::Coin(DEFAULT_CONTENT_AMOUNT)));
+    }
+
+    #[test]
+    fn pixel_rows_become_tile_rows() {
+        // 2x2 image: top right pixel is water
+        let mut tiles = RgbaImage::from_pixel(2, 2, Rgba(rgba(match_color_to_type(&TileType::Grass))));
+        tiles.put_pixel(1, 0, Rgba(rgba(match_color_to_type(&TileType::DeepWater))));
+        let generator = ImageGenerator::new(tiles, None).unwrap();
+        let world = generator.world_tiles();
+        assert_eq!(world[0][1].tile_type, TileType::DeepWater);
+        assert_eq!(world[1][0].tile_type, TileType::Grass);
+    }
+
+    #[test]
+    fn images_of_different_size_are_rejected() {
+        let tiles = RgbaImage::new(2, 2);
+        assert!(ImageGenerator::new(tiles.clone(), Some(RgbaImage::new(3, 3))).is_err());
+        assert!(ImageGenerator::new(RgbaImage::new(2, 3), None).is_err());
+    }
+}